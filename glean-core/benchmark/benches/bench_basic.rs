@@ -14,6 +14,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         application_id: "glean.bench".into(),
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
 
     let mut glean = Glean::new(cfg).unwrap();
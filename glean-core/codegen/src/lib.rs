@@ -0,0 +1,676 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+#![deny(missing_docs)]
+
+//! Generate Rust definitions of metrics and pings for `glean-preview`
+//! from `metrics.yaml` and `pings.yaml` files.
+//!
+//! The generated code has one module per metric category,
+//! with a `Lazy` static per metric named after the metric,
+//! and a `pings` module with a `Lazy` static per ping.
+//! Events get an enum of their extra keys and pings an enum of their reasons,
+//! so that only valid keys and reasons can be recorded.
+//!
+//! The crate using the generated code needs to depend on `glean-preview` and `once_cell`.
+//!
+//! ## Example
+//!
+//! In `build.rs`:
+//!
+//! ```rust,no_run
+//! glean_codegen::Generator::new()
+//!     .metrics_file("metrics.yaml")
+//!     .pings_file("pings.yaml")
+//!     .build("glean_metrics.rs")
+//!     .unwrap();
+//! ```
+//!
+//! In the crate:
+//!
+//! ```rust,ignore
+//! include!(concat!(env!("OUT_DIR"), "/glean_metrics.rs"));
+//!
+//! browser::page_loads.add(1);
+//! pings::register_pings();
+//! ```
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{self, Display, Write};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use glean_core::metrics::ObjectSchema;
+
+use glean_core::registry::{MetricDefinition, MetricKind, MetricRegistry, PingDefinition};
+
+/// The name of the module the pings are generated in.
+const PINGS_MODULE: &str = "pings";
+
+/// The keywords that can't be used as identifiers without the `r#` prefix.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// The errors that can happen while generating code.
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read or written.
+    Io(io::Error),
+    /// A file contains invalid definitions.
+    Definition(glean_core::Error),
+    /// Two definitions map to the same Rust identifier.
+    Conflict(String),
+    /// `OUT_DIR` is not set, because the generator doesn't run in a build script.
+    NoOutDir,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "An I/O error occurred: {}", e),
+            Error::Definition(e) => write!(f, "{}", e),
+            Error::Conflict(e) => write!(f, "Conflicting identifiers: {}", e),
+            Error::NoOutDir => write!(f, "OUT_DIR is not set, is this a build script?"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl From<glean_core::Error> for Error {
+    fn from(error: glean_core::Error) -> Error {
+        Error::Definition(error)
+    }
+}
+
+/// A specialized `Result` type for code generation.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Generates Rust code from `metrics.yaml` and `pings.yaml` files.
+#[derive(Debug, Default)]
+pub struct Generator {
+    metrics_files: Vec<PathBuf>,
+    pings_files: Vec<PathBuf>,
+}
+
+impl Generator {
+    /// Create a generator without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `metrics.yaml` file.
+    pub fn metrics_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.metrics_files.push(path.into());
+        self
+    }
+
+    /// Add a `pings.yaml` file.
+    pub fn pings_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.pings_files.push(path.into());
+        self
+    }
+
+    /// Load the files and generate the code.
+    pub fn generate(&self) -> Result<String> {
+        let mut registry = MetricRegistry::new();
+        for path in &self.metrics_files {
+            registry.load_metrics_file(path)?;
+        }
+        for path in &self.pings_files {
+            registry.load_pings_file(path)?;
+        }
+        generate_code(&registry)
+    }
+
+    /// Generate the code into a file in `OUT_DIR`, from a build script.
+    ///
+    /// This also tells cargo to run the build script again when one of the files changes.
+    ///
+    /// ## Arguments
+    ///
+    /// * `file_name` - the name of the file to generate in `OUT_DIR`.
+    ///
+    /// ## Return value
+    ///
+    /// The path to the generated file.
+    pub fn build(&self, file_name: &str) -> Result<PathBuf> {
+        for path in self.metrics_files.iter().chain(self.pings_files.iter()) {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+
+        let out_dir = env::var_os("OUT_DIR").ok_or(Error::NoOutDir)?;
+        let path = PathBuf::from(out_dir).join(file_name);
+        fs::write(&path, self.generate()?)?;
+        Ok(path)
+    }
+}
+
+/// Generate the code for all metrics and pings of a registry.
+pub fn generate_code(registry: &MetricRegistry) -> Result<String> {
+    registry.check_denominators()?;
+
+    let mut categories: BTreeMap<String, Vec<&MetricDefinition>> = BTreeMap::new();
+    for metric in registry.metrics() {
+        categories
+            .entry(metric.category.clone())
+            .or_default()
+            .push(metric);
+    }
+
+    let mut code = String::new();
+    code.push_str("// This file was generated by glean-codegen. Do not edit it.\n");
+
+    let mut modules = BTreeMap::new();
+    for (category, metrics) in &categories {
+        let module = module_name(category);
+        check_unique(&mut modules, module.clone(), category)?;
+        code.push('\n');
+        generate_category(&mut code, &module, category, metrics)?;
+    }
+
+    let pings: Vec<&PingDefinition> = registry.pings().collect();
+    if !pings.is_empty() {
+        check_unique(&mut modules, PINGS_MODULE.to_string(), "the pings")?;
+        code.push('\n');
+        generate_pings(&mut code, &pings)?;
+    }
+
+    Ok(code)
+}
+
+/// Record that a Rust identifier is used for a definition, failing if it's already used.
+fn check_unique(used: &mut BTreeMap<String, String>, identifier: String, by: &str) -> Result<()> {
+    if let Some(previous) = used.insert(identifier.clone(), by.to_string()) {
+        return Err(Error::Conflict(format!(
+            "'{}' and '{}' both map to `{}`",
+            previous, by, identifier
+        )));
+    }
+    Ok(())
+}
+
+fn generate_category(
+    code: &mut String,
+    module: &str,
+    category: &str,
+    metrics: &[&MetricDefinition],
+) -> Result<()> {
+    writeln!(code, "/// The metrics of the `{}` category.", category).unwrap();
+    writeln!(code, "pub mod {} {{", module).unwrap();
+    code.push_str("    #![allow(non_upper_case_globals)]\n\n");
+    code.push_str("    use glean_preview::metrics::*;\n");
+    code.push_str("    use glean_preview::{CommonMetricData, Lifetime};\n");
+    code.push_str("    use once_cell::sync::Lazy;\n");
+
+    let mut identifiers = BTreeMap::new();
+    for metric in metrics {
+        let name = identifier(&metric.name);
+        check_unique(&mut identifiers, name.clone(), &metric.name)?;
+
+        let (metric_type, constructor) = match metric.kind {
+            MetricKind::Event => {
+                let keys = if metric.extra_keys.is_empty() {
+                    "NoExtraKeys".to_string()
+                } else {
+                    let keys = format!("{}Keys", camel_case(&metric.name));
+                    check_unique(&mut identifiers, keys.clone(), &metric.name)?;
+                    generate_extra_keys(code, &keys, metric)?;
+                    keys
+                };
+                (
+                    format!("TypedEventMetric<{}>", keys),
+                    "TypedEventMetric::new(meta)".to_string(),
+                )
+            }
+            kind => metric_constructor(kind, metric),
+        };
+
+        code.push('\n');
+        write_doc(code, "    ", &metric.description);
+        writeln!(
+            code,
+            "    pub static {}: Lazy<{}> = Lazy::new(|| {{",
+            name, metric_type
+        )
+        .unwrap();
+        write_common_metric_data(code, metric);
+        writeln!(code, "        {}", constructor).unwrap();
+        code.push_str("    });\n");
+    }
+
+    code.push_str("}\n");
+    Ok(())
+}
+
+/// The type and constructor call of a metric, other than an event.
+fn metric_constructor(kind: MetricKind, metric: &MetricDefinition) -> (String, String) {
+    let unit = |unit: Option<String>| unit.unwrap_or_default();
+    let time_unit = unit(metric.time_unit.map(|u| format!("TimeUnit::{:?}", u)));
+    let memory_unit = unit(metric.memory_unit.map(|u| format!("MemoryUnit::{:?}", u)));
+    let custom_buckets = || {
+        let (range_min, range_max, bucket_count, histogram_type) = metric
+            .custom_buckets
+            .expect("Custom distribution definitions are validated to have buckets");
+        format!(
+            ", {}, {}, {}, HistogramType::{:?}",
+            range_min, range_max, bucket_count, histogram_type
+        )
+    };
+    let labels = match &metric.labels {
+        Some(labels) => format!("Some(vec![{}])", string_list(labels)),
+        None => "None".to_string(),
+    };
+    // Labeled metrics whose submetrics need more than the metadata are built from a submetric.
+    let labeled_submetric = |submetric: &str, arguments: String| {
+        (
+            format!("LabeledMetric<{}>", submetric),
+            format!(
+                "LabeledMetric::with_submetric({}::new(meta{}), {})",
+                submetric, arguments, labels
+            ),
+        )
+    };
+
+    let (metric_type, arguments) = match kind {
+        MetricKind::Boolean => ("BooleanMetric", String::new()),
+        MetricKind::Counter if !metric.numerators.is_empty() => {
+            let numerators: Vec<String> = metric
+                .numerators
+                .iter()
+                .map(|numerator| {
+                    format!(
+                        "(*super::{}::{}).clone()",
+                        module_name(&numerator.category),
+                        identifier(&numerator.name)
+                    )
+                })
+                .collect();
+            (
+                "DenominatorMetric",
+                format!(", vec![{}]", numerators.join(", ")),
+            )
+        }
+        MetricKind::Counter => ("CounterMetric", String::new()),
+        MetricKind::Quantity => ("QuantityMetric", String::new()),
+        MetricKind::String => ("StringMetric", String::new()),
+        MetricKind::StringList => ("StringListMetric", String::new()),
+        MetricKind::Uuid => ("UuidMetric", String::new()),
+        MetricKind::Datetime => ("DatetimeMetric", format!(", {}", time_unit)),
+        MetricKind::Timespan => ("TimespanMetric", format!(", {}", time_unit)),
+        MetricKind::TimingDistribution => ("TimingDistributionMetric", format!(", {}", time_unit)),
+        MetricKind::MemoryDistribution => {
+            ("MemoryDistributionMetric", format!(", {}", memory_unit))
+        }
+        MetricKind::CustomDistribution => ("CustomDistributionMetric", custom_buckets()),
+        MetricKind::Rate if metric.denominator_metric.is_some() => {
+            ("NumeratorMetric", String::new())
+        }
+        MetricKind::Rate => ("RateMetric", String::new()),
+        MetricKind::Text => ("TextMetric", String::new()),
+        MetricKind::Url => ("UrlMetric", String::new()),
+        MetricKind::Jwe => ("JweMetric", String::new()),
+        MetricKind::Object => {
+            let schema = metric
+                .object_schema
+                .as_ref()
+                .expect("Object definitions are validated to have a structure");
+            ("ObjectMetric", format!(", {}", object_schema(schema)))
+        }
+        MetricKind::LabeledBoolean => ("LabeledMetric<BooleanMetric>", format!(", {}", labels)),
+        MetricKind::LabeledCounter => ("LabeledMetric<CounterMetric>", format!(", {}", labels)),
+        MetricKind::LabeledString => ("LabeledMetric<StringMetric>", format!(", {}", labels)),
+        MetricKind::LabeledQuantity => ("LabeledMetric<QuantityMetric>", format!(", {}", labels)),
+        MetricKind::LabeledTimespan => {
+            return labeled_submetric("TimespanMetric", format!(", {}", time_unit))
+        }
+        MetricKind::LabeledTimingDistribution => {
+            return labeled_submetric("TimingDistributionMetric", format!(", {}", time_unit))
+        }
+        MetricKind::LabeledMemoryDistribution => {
+            return labeled_submetric("MemoryDistributionMetric", format!(", {}", memory_unit))
+        }
+        MetricKind::LabeledCustomDistribution => {
+            return labeled_submetric("CustomDistributionMetric", custom_buckets())
+        }
+        MetricKind::Event => unreachable!("Events are generated separately"),
+    };
+
+    let constructor_type = metric_type.split('<').next().unwrap();
+    (
+        metric_type.to_string(),
+        format!("{}::new(meta{})", constructor_type, arguments),
+    )
+}
+
+/// The expression building an object schema.
+fn object_schema(schema: &ObjectSchema) -> String {
+    match schema {
+        ObjectSchema::Boolean => "ObjectSchema::Boolean".to_string(),
+        ObjectSchema::Number => "ObjectSchema::Number".to_string(),
+        ObjectSchema::String => "ObjectSchema::String".to_string(),
+        ObjectSchema::Array { items, max_length } => format!(
+            "ObjectSchema::Array {{ items: Box::new({}), max_length: {} }}",
+            object_schema(items),
+            max_length
+        ),
+        ObjectSchema::Object(properties) => {
+            // Sorted, so the generated code doesn't change between runs.
+            let properties: BTreeMap<_, _> = properties.iter().collect();
+            let properties: Vec<String> = properties
+                .into_iter()
+                .map(|(key, value)| format!("({:?}.to_string(), {})", key, object_schema(value)))
+                .collect();
+            format!(
+                "ObjectSchema::Object(vec![{}].into_iter().collect())",
+                properties.join(", ")
+            )
+        }
+    }
+}
+
+fn generate_extra_keys(code: &mut String, keys: &str, metric: &MetricDefinition) -> Result<()> {
+    let mut variants = BTreeMap::new();
+    for key in &metric.extra_keys {
+        check_unique(&mut variants, camel_case(key), key)?;
+    }
+
+    writeln!(
+        code,
+        "\n    /// The extra keys of the `{}` event.",
+        metric.name
+    )
+    .unwrap();
+    code.push_str("    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]\n");
+    writeln!(code, "    pub enum {} {{", keys).unwrap();
+    for key in &metric.extra_keys {
+        writeln!(code, "        /// The `{}` extra key.", key).unwrap();
+        writeln!(code, "        {},", camel_case(key)).unwrap();
+    }
+    code.push_str("    }\n\n");
+
+    writeln!(code, "    impl ExtraKeys for {} {{", keys).unwrap();
+    writeln!(
+        code,
+        "        const ALLOWED_KEYS: &'static [&'static str] = &[{}];\n",
+        string_list(&metric.extra_keys).replace(".into()", "")
+    )
+    .unwrap();
+    code.push_str("        fn as_str(self) -> &'static str {\n");
+    code.push_str("            Self::ALLOWED_KEYS[self as usize]\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    Ok(())
+}
+
+fn generate_pings(code: &mut String, pings: &[&PingDefinition]) -> Result<()> {
+    code.push_str("/// The pings.\n");
+    writeln!(code, "pub mod {} {{", PINGS_MODULE).unwrap();
+    code.push_str("    #![allow(non_upper_case_globals)]\n\n");
+    code.push_str("    use glean_preview::metrics::*;\n");
+    code.push_str("    use once_cell::sync::Lazy;\n");
+
+    let mut identifiers = BTreeMap::new();
+    check_unique(
+        &mut identifiers,
+        "register_pings".to_string(),
+        "register_pings",
+    )?;
+    for ping in pings {
+        let name = identifier(&ping.name);
+        check_unique(&mut identifiers, name.clone(), &ping.name)?;
+
+        let reasons = if ping.reason_codes.is_empty() {
+            "NoReasonCodes".to_string()
+        } else {
+            let reasons = format!("{}ReasonCodes", camel_case(&ping.name));
+            check_unique(&mut identifiers, reasons.clone(), &ping.name)?;
+            generate_reason_codes(code, &reasons, ping)?;
+            reasons
+        };
+
+        code.push('\n');
+        write_doc(code, "    ", &ping.description);
+        writeln!(
+            code,
+            "    pub static {}: Lazy<TypedPingType<{}>> =",
+            name, reasons
+        )
+        .unwrap();
+        let include_text = if ping.include_text {
+            ".with_include_text(true)"
+        } else {
+            ""
+        };
+        writeln!(
+            code,
+            "        Lazy::new(|| TypedPingType::new({:?}, {}, {}){});",
+            ping.name, ping.include_client_id, ping.send_if_empty, include_text
+        )
+        .unwrap();
+    }
+
+    code.push_str("\n    /// Register all pings with Glean.\n");
+    code.push_str("    pub fn register_pings() {\n");
+    for ping in pings {
+        writeln!(
+            code,
+            "        glean_preview::register_ping_type({}.ping_type());",
+            identifier(&ping.name)
+        )
+        .unwrap();
+    }
+    code.push_str("    }\n");
+    code.push_str("}\n");
+    Ok(())
+}
+
+fn generate_reason_codes(code: &mut String, reasons: &str, ping: &PingDefinition) -> Result<()> {
+    let mut variants = BTreeMap::new();
+    for reason in &ping.reason_codes {
+        check_unique(&mut variants, camel_case(reason), reason)?;
+    }
+
+    writeln!(
+        code,
+        "\n    /// The reasons the `{}` ping can be submitted for.",
+        ping.name
+    )
+    .unwrap();
+    code.push_str("    #[derive(Clone, Copy, Debug, PartialEq, Eq)]\n");
+    writeln!(code, "    pub enum {} {{", reasons).unwrap();
+    for reason in &ping.reason_codes {
+        writeln!(code, "        /// The `{}` reason.", reason).unwrap();
+        writeln!(code, "        {},", camel_case(reason)).unwrap();
+    }
+    code.push_str("    }\n\n");
+
+    writeln!(code, "    impl ReasonCodes for {} {{", reasons).unwrap();
+    writeln!(
+        code,
+        "        const REASON_CODES: &'static [&'static str] = &[{}];\n",
+        string_list(&ping.reason_codes).replace(".into()", "")
+    )
+    .unwrap();
+    code.push_str("        fn as_str(self) -> &'static str {\n");
+    code.push_str("            Self::REASON_CODES[self as usize]\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    Ok(())
+}
+
+fn write_common_metric_data(code: &mut String, metric: &MetricDefinition) {
+    // Expired metrics are disabled, as `glean_parser` does.
+    let disabled = metric.disabled || metric.expires == "expired";
+
+    code.push_str("        let meta = CommonMetricData {\n");
+    writeln!(code, "            name: {:?}.into(),", metric.name).unwrap();
+    writeln!(
+        code,
+        "            category: {:?}.into(),",
+        metric.common_metric_data().category
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "            send_in_pings: vec![{}],",
+        string_list(&metric.send_in_pings)
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "            lifetime: Lifetime::{:?},",
+        metric.lifetime
+    )
+    .unwrap();
+    writeln!(code, "            disabled: {},", disabled).unwrap();
+    code.push_str("            ..Default::default()\n");
+    code.push_str("        };\n");
+}
+
+/// Write a description as doc comment.
+fn write_doc(code: &mut String, indent: &str, description: &str) {
+    for line in description.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            writeln!(code, "{}///", indent).unwrap();
+        } else {
+            writeln!(code, "{}/// {}", indent, line).unwrap();
+        }
+    }
+}
+
+/// A comma-separated list of string literals converted to `String`s.
+fn string_list(strings: &[String]) -> String {
+    strings
+        .iter()
+        .map(|s| format!("{:?}.into()", s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The module name of a category: dots and dashes become underscores.
+fn module_name(category: &str) -> String {
+    identifier(&category.replace('.', "_"))
+}
+
+/// A snake case identifier for a name, escaping keywords.
+fn identifier(name: &str) -> String {
+    let name = name.replace('-', "_");
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// A camel case identifier for a name, e.g. `object.id` becomes `ObjectId`.
+fn camel_case(name: &str) -> String {
+    let mut result: String = name
+        .split(&['_', '.', '-'][..])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_identifiers() {
+        assert_eq!("page_loads", identifier("page_loads"));
+        assert_eq!("deletion_request", identifier("deletion-request"));
+        assert_eq!("r#type", identifier("type"));
+        assert_eq!(
+            "glean_internal_metrics",
+            module_name("glean.internal.metrics")
+        );
+
+        assert_eq!("ObjectId", camel_case("object_id"));
+        assert_eq!("ObjectId", camel_case("object.id"));
+        assert_eq!("DirtyStartup", camel_case("dirty_startup"));
+        assert_eq!("_1", camel_case("_1"));
+    }
+
+    fn load(metrics: &str) -> MetricRegistry {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(&format!(
+                "$schema: moz://mozilla.org/schemas/glean/metrics/1-0-0\n{}",
+                metrics
+            ))
+            .unwrap();
+        registry
+    }
+
+    const COMMON_FIELDS: &str = "    description: A metric.\n    bugs: [1]\n    \
+                                 data_reviews: [1]\n    notification_emails: [a@b.c]\n    \
+                                 expires: never\n";
+
+    #[test]
+    fn rejects_conflicting_identifiers() {
+        let registry = load(&format!(
+            "cat:\n  a-b:\n    type: counter\n{0}  a_b:\n    type: counter\n{0}",
+            COMMON_FIELDS
+        ));
+        match generate_code(&registry) {
+            Err(Error::Conflict(_)) => {}
+            _ => panic!("Expected conflicting identifiers"),
+        }
+
+        let registry = load(&format!(
+            "cat:\n  click:\n    type: event\n    extra_keys:\n      a_b: {{}}\n      \
+             a.b: {{}}\n{}",
+            COMMON_FIELDS
+        ));
+        match generate_code(&registry) {
+            Err(Error::Conflict(_)) => {}
+            _ => panic!("Expected conflicting identifiers"),
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_denominators() {
+        let registry = load(&format!(
+            "cat:\n  crashes:\n    type: rate\n    denominator_metric: cat.sessions\n{}",
+            COMMON_FIELDS
+        ));
+        match generate_code(&registry) {
+            Err(Error::Definition(_)) => {}
+            _ => panic!("Expected an invalid definition"),
+        }
+    }
+
+    #[test]
+    fn disables_expired_metrics() {
+        let registry = load(&format!(
+            "cat:\n  old:\n    type: counter\n{}",
+            COMMON_FIELDS.replace("never", "expired")
+        ));
+        let code = generate_code(&registry).unwrap();
+        assert!(code.contains("disabled: true,"));
+    }
+}
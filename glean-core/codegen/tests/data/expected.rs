@@ -0,0 +1,214 @@
+// This file was generated by glean-codegen. Do not edit it.
+
+/// The metrics of the `browser.engagement` category.
+pub mod browser_engagement {
+    #![allow(non_upper_case_globals)]
+
+    use glean_preview::metrics::*;
+    use glean_preview::{CommonMetricData, Lifetime};
+    use once_cell::sync::Lazy;
+
+    /// The number of crashes per browsing session.
+    pub static crashes: Lazy<NumeratorMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "crashes".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["metrics".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        NumeratorMetric::new(meta)
+    });
+
+    /// The number of page load errors, by kind of error.
+    pub static load_errors: Lazy<LabeledMetric<CounterMetric>> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "load_errors".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["metrics".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        LabeledMetric::new(meta, Some(vec!["network".into(), "timeout".into()]))
+    });
+
+    /// How long the phases of loading a page take.
+    pub static load_phases: Lazy<LabeledMetric<TimespanMetric>> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "load_phases".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["metrics".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        LabeledMetric::with_submetric(TimespanMetric::new(meta, TimeUnit::Microsecond), Some(vec!["fetch".into(), "render".into()]))
+    });
+
+    /// How long loading a page takes.
+    pub static load_time: Lazy<TimingDistributionMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "load_time".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["baseline".into(), "metrics".into()],
+            lifetime: Lifetime::Application,
+            disabled: true,
+            ..Default::default()
+        };
+        TimingDistributionMetric::new(meta, TimeUnit::Millisecond)
+    });
+
+    /// The number of pages loaded.
+    pub static page_loads: Lazy<CounterMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "page_loads".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["metrics".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        CounterMetric::new(meta)
+    });
+
+    /// The number of browsing sessions.
+    pub static sessions: Lazy<DenominatorMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "sessions".into(),
+            category: "browser.engagement".into(),
+            send_in_pings: vec!["metrics".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        DenominatorMetric::new(meta, vec![(*super::browser_engagement::crashes).clone()])
+    });
+}
+
+/// The metrics of the `crash` category.
+pub mod crash {
+    #![allow(non_upper_case_globals)]
+
+    use glean_preview::metrics::*;
+    use glean_preview::{CommonMetricData, Lifetime};
+    use once_cell::sync::Lazy;
+
+    /// The modules loaded at the time of the crash.
+    pub static modules: Lazy<ObjectMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "modules".into(),
+            category: "crash".into(),
+            send_in_pings: vec!["crash-report".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        ObjectMetric::new(meta, ObjectSchema::Array { items: Box::new(ObjectSchema::Object(vec![("name".to_string(), ObjectSchema::String), ("version".to_string(), ObjectSchema::Number)].into_iter().collect())), max_length: 10 })
+    });
+
+    /// A sanitized summary of the crash.
+    pub static summary: Lazy<TextMetric> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "summary".into(),
+            category: "crash".into(),
+            send_in_pings: vec!["crash-report".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        TextMetric::new(meta)
+    });
+}
+
+/// The metrics of the `ui` category.
+pub mod ui {
+    #![allow(non_upper_case_globals)]
+
+    use glean_preview::metrics::*;
+    use glean_preview::{CommonMetricData, Lifetime};
+    use once_cell::sync::Lazy;
+
+    /// The extra keys of the `click` event.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum ClickKeys {
+        /// The `object_id` extra key.
+        ObjectId,
+        /// The `other` extra key.
+        Other,
+    }
+
+    impl ExtraKeys for ClickKeys {
+        const ALLOWED_KEYS: &'static [&'static str] = &["object_id", "other"];
+
+        fn as_str(self) -> &'static str {
+            Self::ALLOWED_KEYS[self as usize]
+        }
+    }
+
+    /// A click on a button.
+    pub static click: Lazy<TypedEventMetric<ClickKeys>> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "click".into(),
+            category: "ui".into(),
+            send_in_pings: vec!["events".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        TypedEventMetric::new(meta)
+    });
+
+    /// Text was typed.
+    pub static r#type: Lazy<TypedEventMetric<NoExtraKeys>> = Lazy::new(|| {
+        let meta = CommonMetricData {
+            name: "type".into(),
+            category: "ui".into(),
+            send_in_pings: vec!["events".into()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+            ..Default::default()
+        };
+        TypedEventMetric::new(meta)
+    });
+}
+
+/// The pings.
+pub mod pings {
+    #![allow(non_upper_case_globals)]
+
+    use glean_preview::metrics::*;
+    use once_cell::sync::Lazy;
+
+    /// Sent after a crash.
+    pub static crash_report: Lazy<TypedPingType<NoReasonCodes>> =
+        Lazy::new(|| TypedPingType::new("crash-report", false, true).with_include_text(true));
+
+    /// The reasons the `session` ping can be submitted for.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum SessionReasonCodes {
+        /// The `closed` reason.
+        Closed,
+        /// The `idle_timeout` reason.
+        IdleTimeout,
+    }
+
+    impl ReasonCodes for SessionReasonCodes {
+        const REASON_CODES: &'static [&'static str] = &["closed", "idle_timeout"];
+
+        fn as_str(self) -> &'static str {
+            Self::REASON_CODES[self as usize]
+        }
+    }
+
+    /// Sent at the end of a browsing session.
+    pub static session: Lazy<TypedPingType<SessionReasonCodes>> =
+        Lazy::new(|| TypedPingType::new("session", true, false));
+
+    /// Register all pings with Glean.
+    pub fn register_pings() {
+        glean_preview::register_ping_type(crash_report.ping_type());
+        glean_preview::register_ping_type(session.ping_type());
+    }
+}
@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use glean_codegen::Generator;
+use glean_preview::metrics::{ExtraKeys, Rate, ReasonCodes};
+use glean_preview::{ClientInfoMetrics, Configuration};
+
+// The expected output is compiled too, to make sure the generated code builds.
+#[allow(dead_code)]
+mod generated {
+    include!("data/expected.rs");
+}
+
+use generated::{browser_engagement, crash, pings, ui};
+
+fn generate() -> String {
+    Generator::new()
+        .metrics_file("tests/data/metrics.yaml")
+        .pings_file("tests/data/pings.yaml")
+        .generate()
+        .unwrap()
+}
+
+#[test]
+fn generates_the_expected_code() {
+    assert_eq!(include_str!("data/expected.rs"), generate());
+}
+
+#[test]
+fn generated_types_map_to_the_definitions() {
+    assert_eq!(&["object_id", "other"], ui::ClickKeys::ALLOWED_KEYS);
+    assert_eq!("other", ui::ClickKeys::Other.as_str());
+    assert_eq!(
+        "idle_timeout",
+        pings::SessionReasonCodes::IdleTimeout.as_str()
+    );
+}
+
+#[test]
+fn generated_metrics_record_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: "org.mozilla.glean.codegen.test".into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: false,
+        channel: None,
+        server_endpoint: None,
+        uploader: None,
+    };
+    glean_preview::initialize(cfg, ClientInfoMetrics::unknown()).unwrap();
+    pings::register_pings();
+
+    browser_engagement::page_loads.add(2);
+    assert_eq!(
+        Some(2),
+        browser_engagement::page_loads.test_get_value("metrics")
+    );
+
+    browser_engagement::load_errors.get("timeout").add(1);
+    assert_eq!(
+        Some(1),
+        browser_engagement::load_errors
+            .get("timeout")
+            .test_get_value("metrics")
+    );
+
+    browser_engagement::load_phases
+        .get("render")
+        .set_raw(Duration::from_micros(30));
+    assert_eq!(
+        Some(30),
+        browser_engagement::load_phases
+            .get("render")
+            .test_get_value("metrics")
+    );
+
+    // `crashes` uses `sessions` as its denominator.
+    browser_engagement::sessions.add(2);
+    browser_engagement::crashes.add_to_numerator(1);
+    assert_eq!(
+        Some(Rate {
+            numerator: 1,
+            denominator: 2
+        }),
+        browser_engagement::crashes.test_get_value("metrics")
+    );
+
+    // The `crash-report` ping includes text.
+    crash::summary.set("out of memory");
+    assert_eq!(
+        Some("out of memory".to_string()),
+        crash::summary.test_get_value("crash-report")
+    );
+
+    crash::modules.set_string(r#"[{"name": "gfx", "version": 2}]"#);
+    assert_eq!(
+        Some(serde_json::json!([{"name": "gfx", "version": 2}])),
+        crash::modules.test_get_value("crash-report")
+    );
+    crash::modules.set_string(r#"[{"name": "gfx", "size": 2}]"#);
+    assert_eq!(
+        Some(serde_json::json!([{"name": "gfx", "version": 2}])),
+        crash::modules.test_get_value("crash-report")
+    );
+
+    let mut extra = HashMap::new();
+    extra.insert(ui::ClickKeys::ObjectId, "reload".into());
+    ui::click.record(extra);
+    let events = ui::click.test_get_value("events").unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(
+        Some("reload"),
+        events[0].extra.as_ref().unwrap()["object_id"].as_str()
+    );
+
+    // Expired metrics are disabled.
+    browser_engagement::load_time.stop_and_accumulate(browser_engagement::load_time.start());
+    assert!(browser_engagement::load_time
+        .test_get_value("metrics")
+        .is_none());
+}
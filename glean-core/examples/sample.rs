@@ -23,6 +23,9 @@ fn main() {
         upload_enabled: true,
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
     let mut glean = Glean::new(cfg).unwrap();
     glean.register_ping_type(&PingType::new("baseline", true, false, vec![]));
@@ -165,9 +165,15 @@ typedef struct {
  *   char *headers;
  * } FfiPingUploadTask_Upload_Body;
  *
+ * typedef struct {
+ *   FfiPingUploadTask_Tag tag;
+ *   uint64_t time;
+ * } FfiPingUploadTask_Wait_Body;
+ *
  * typedef union {
  *   FfiPingUploadTask_Tag tag;
  *   FfiPingUploadTask_Upload_Body upload;
+ *   FfiPingUploadTask_Wait_Body wait;
  * } FfiPingUploadTask;
  *
  * ```
@@ -205,9 +211,15 @@ typedef struct {
   char *headers;
 } FfiPingUploadTask_Upload_Body;
 
+typedef struct {
+  FfiPingUploadTask_Tag tag;
+  uint64_t time;
+} FfiPingUploadTask_Wait_Body;
+
 typedef union {
   FfiPingUploadTask_Tag tag;
   FfiPingUploadTask_Upload_Body upload;
+  FfiPingUploadTask_Wait_Body wait;
 } FfiPingUploadTask;
 
 /**
@@ -278,6 +290,16 @@ char *glean_datetime_test_get_value_as_string(uint64_t metric_id, FfiStr storage
 
 uint8_t glean_datetime_test_has_value(uint64_t metric_id, FfiStr storage_name);
 
+void glean_denominator_add(uint64_t metric_id, int32_t amount);
+
+int32_t glean_denominator_test_get_num_recorded_errors(uint64_t metric_id,
+                                                       int32_t error_type,
+                                                       FfiStr storage_name);
+
+int32_t glean_denominator_test_get_value(uint64_t metric_id, FfiStr storage_name);
+
+uint8_t glean_denominator_test_has_value(uint64_t metric_id, FfiStr storage_name);
+
 void glean_destroy_boolean_metric(uint64_t v);
 
 void glean_destroy_counter_metric(uint64_t v);
@@ -286,22 +308,40 @@ void glean_destroy_custom_distribution_metric(uint64_t v);
 
 void glean_destroy_datetime_metric(uint64_t v);
 
+void glean_destroy_denominator_metric(uint64_t v);
+
 void glean_destroy_event_metric(uint64_t v);
 
 void glean_destroy_glean(void);
 
+void glean_destroy_jwe_metric(uint64_t v);
+
 void glean_destroy_labeled_boolean_metric(uint64_t v);
 
 void glean_destroy_labeled_counter_metric(uint64_t v);
 
+void glean_destroy_labeled_custom_distribution_metric(uint64_t v);
+
+void glean_destroy_labeled_memory_distribution_metric(uint64_t v);
+
+void glean_destroy_labeled_quantity_metric(uint64_t v);
+
 void glean_destroy_labeled_string_metric(uint64_t v);
 
+void glean_destroy_labeled_timespan_metric(uint64_t v);
+
+void glean_destroy_labeled_timing_distribution_metric(uint64_t v);
+
 void glean_destroy_memory_distribution_metric(uint64_t v);
 
+void glean_destroy_numerator_metric(uint64_t v);
+
 void glean_destroy_ping_type(uint64_t v);
 
 void glean_destroy_quantity_metric(uint64_t v);
 
+void glean_destroy_rate_metric(uint64_t v);
+
 void glean_destroy_string_list_metric(uint64_t v);
 
 void glean_destroy_string_metric(uint64_t v);
@@ -310,6 +350,8 @@ void glean_destroy_timespan_metric(uint64_t v);
 
 void glean_destroy_timing_distribution_metric(uint64_t v);
 
+void glean_destroy_url_metric(uint64_t v);
+
 void glean_destroy_uuid_metric(uint64_t v);
 
 /**
@@ -338,6 +380,10 @@ uint8_t glean_experiment_test_is_active(FfiStr experiment_id);
 
 void glean_get_upload_task(FfiPingUploadTask *result);
 
+void glean_handle_client_active(void);
+
+void glean_handle_client_inactive(void);
+
 /**
  * # Safety
  *
@@ -351,6 +397,23 @@ uint8_t glean_is_first_run(void);
 
 uint8_t glean_is_upload_enabled(void);
 
+void glean_jwe_set(uint64_t metric_id,
+                   FfiStr header,
+                   FfiStr key,
+                   FfiStr init_vector,
+                   FfiStr cipher_text,
+                   FfiStr auth_tag);
+
+void glean_jwe_set_with_compact_representation(uint64_t metric_id, FfiStr value);
+
+int32_t glean_jwe_test_get_num_recorded_errors(uint64_t metric_id,
+                                               int32_t error_type,
+                                               FfiStr storage_name);
+
+char *glean_jwe_test_get_value(uint64_t metric_id, FfiStr storage_name);
+
+uint8_t glean_jwe_test_has_value(uint64_t metric_id, FfiStr storage_name);
+
 /**
  * Create a new instance of the sub-metric of this labeled metric.
  */
@@ -369,6 +432,36 @@ int32_t glean_labeled_counter_test_get_num_recorded_errors(uint64_t metric_id,
                                                            int32_t error_type,
                                                            FfiStr storage_name);
 
+/**
+ * Create a new instance of the sub-metric of this labeled metric.
+ */
+uint64_t glean_labeled_custom_distribution_metric_get(uint64_t handle,
+                                                      FfiStr label);
+
+int32_t glean_labeled_custom_distribution_test_get_num_recorded_errors(uint64_t metric_id,
+                                                                       int32_t error_type,
+                                                                       FfiStr storage_name);
+
+/**
+ * Create a new instance of the sub-metric of this labeled metric.
+ */
+uint64_t glean_labeled_memory_distribution_metric_get(uint64_t handle,
+                                                      FfiStr label);
+
+int32_t glean_labeled_memory_distribution_test_get_num_recorded_errors(uint64_t metric_id,
+                                                                       int32_t error_type,
+                                                                       FfiStr storage_name);
+
+/**
+ * Create a new instance of the sub-metric of this labeled metric.
+ */
+uint64_t glean_labeled_quantity_metric_get(uint64_t handle,
+                                           FfiStr label);
+
+int32_t glean_labeled_quantity_test_get_num_recorded_errors(uint64_t metric_id,
+                                                            int32_t error_type,
+                                                            FfiStr storage_name);
+
 /**
  * Create a new instance of the sub-metric of this labeled metric.
  */
@@ -378,6 +471,26 @@ int32_t glean_labeled_string_test_get_num_recorded_errors(uint64_t metric_id,
                                                           int32_t error_type,
                                                           FfiStr storage_name);
 
+/**
+ * Create a new instance of the sub-metric of this labeled metric.
+ */
+uint64_t glean_labeled_timespan_metric_get(uint64_t handle,
+                                           FfiStr label);
+
+int32_t glean_labeled_timespan_test_get_num_recorded_errors(uint64_t metric_id,
+                                                            int32_t error_type,
+                                                            FfiStr storage_name);
+
+/**
+ * Create a new instance of the sub-metric of this labeled metric.
+ */
+uint64_t glean_labeled_timing_distribution_metric_get(uint64_t handle,
+                                                      FfiStr label);
+
+int32_t glean_labeled_timing_distribution_test_get_num_recorded_errors(uint64_t metric_id,
+                                                                       int32_t error_type,
+                                                                       FfiStr storage_name);
+
 void glean_memory_distribution_accumulate(uint64_t metric_id, uint64_t sample);
 
 void glean_memory_distribution_accumulate_samples(uint64_t metric_id,
@@ -426,6 +539,20 @@ uint64_t glean_new_datetime_metric(FfiStr category,
                                    uint8_t disabled,
                                    int32_t time_unit);
 
+/**
+ * Create a new denominator metric.
+ *
+ * The numerators are given as handles of previously created numerator metrics.
+ */
+uint64_t glean_new_denominator_metric(FfiStr category,
+                                      FfiStr name,
+                                      RawStringArray send_in_pings,
+                                      int32_t send_in_pings_len,
+                                      int32_t lifetime,
+                                      uint8_t disabled,
+                                      RawInt64Array numerators,
+                                      int32_t numerators_len);
+
 uint64_t glean_new_event_metric(FfiStr category,
                                 FfiStr name,
                                 RawStringArray send_in_pings,
@@ -435,6 +562,13 @@ uint64_t glean_new_event_metric(FfiStr category,
                                 RawStringArray extra_keys,
                                 int32_t extra_keys_len);
 
+uint64_t glean_new_jwe_metric(FfiStr category,
+                              FfiStr name,
+                              RawStringArray send_in_pings,
+                              int32_t send_in_pings_len,
+                              int32_t lifetime,
+                              uint8_t disabled);
+
 /**
  * Create a new labeled metric.
  */
@@ -459,6 +593,47 @@ uint64_t glean_new_labeled_counter_metric(FfiStr category,
                                           RawStringArray labels,
                                           int32_t label_count);
 
+/**
+ * Create a new labeled metric.
+ */
+uint64_t glean_new_labeled_custom_distribution_metric(FfiStr category,
+                                                      FfiStr name,
+                                                      RawStringArray send_in_pings,
+                                                      int32_t send_in_pings_len,
+                                                      int32_t lifetime,
+                                                      uint8_t disabled,
+                                                      uint64_t range_min,
+                                                      uint64_t range_max,
+                                                      uint64_t bucket_count,
+                                                      int32_t histogram_type,
+                                                      RawStringArray labels,
+                                                      int32_t label_count);
+
+/**
+ * Create a new labeled metric.
+ */
+uint64_t glean_new_labeled_memory_distribution_metric(FfiStr category,
+                                                      FfiStr name,
+                                                      RawStringArray send_in_pings,
+                                                      int32_t send_in_pings_len,
+                                                      int32_t lifetime,
+                                                      uint8_t disabled,
+                                                      int32_t memory_unit,
+                                                      RawStringArray labels,
+                                                      int32_t label_count);
+
+/**
+ * Create a new labeled metric.
+ */
+uint64_t glean_new_labeled_quantity_metric(FfiStr category,
+                                           FfiStr name,
+                                           RawStringArray send_in_pings,
+                                           int32_t send_in_pings_len,
+                                           int32_t lifetime,
+                                           uint8_t disabled,
+                                           RawStringArray labels,
+                                           int32_t label_count);
+
 /**
  * Create a new labeled metric.
  */
@@ -471,6 +646,32 @@ uint64_t glean_new_labeled_string_metric(FfiStr category,
                                          RawStringArray labels,
                                          int32_t label_count);
 
+/**
+ * Create a new labeled metric.
+ */
+uint64_t glean_new_labeled_timespan_metric(FfiStr category,
+                                           FfiStr name,
+                                           RawStringArray send_in_pings,
+                                           int32_t send_in_pings_len,
+                                           int32_t lifetime,
+                                           uint8_t disabled,
+                                           int32_t time_unit,
+                                           RawStringArray labels,
+                                           int32_t label_count);
+
+/**
+ * Create a new labeled metric.
+ */
+uint64_t glean_new_labeled_timing_distribution_metric(FfiStr category,
+                                                      FfiStr name,
+                                                      RawStringArray send_in_pings,
+                                                      int32_t send_in_pings_len,
+                                                      int32_t lifetime,
+                                                      uint8_t disabled,
+                                                      int32_t time_unit,
+                                                      RawStringArray labels,
+                                                      int32_t label_count);
+
 uint64_t glean_new_memory_distribution_metric(FfiStr category,
                                               FfiStr name,
                                               RawStringArray send_in_pings,
@@ -479,6 +680,13 @@ uint64_t glean_new_memory_distribution_metric(FfiStr category,
                                               uint8_t disabled,
                                               int32_t memory_unit);
 
+uint64_t glean_new_numerator_metric(FfiStr category,
+                                    FfiStr name,
+                                    RawStringArray send_in_pings,
+                                    int32_t send_in_pings_len,
+                                    int32_t lifetime,
+                                    uint8_t disabled);
+
 uint64_t glean_new_ping_type(FfiStr ping_name,
                              uint8_t include_client_id,
                              uint8_t send_if_empty,
@@ -492,6 +700,13 @@ uint64_t glean_new_quantity_metric(FfiStr category,
                                    int32_t lifetime,
                                    uint8_t disabled);
 
+uint64_t glean_new_rate_metric(FfiStr category,
+                               FfiStr name,
+                               RawStringArray send_in_pings,
+                               int32_t send_in_pings_len,
+                               int32_t lifetime,
+                               uint8_t disabled);
+
 uint64_t glean_new_string_list_metric(FfiStr category,
                                       FfiStr name,
                                       RawStringArray send_in_pings,
@@ -522,6 +737,13 @@ uint64_t glean_new_timing_distribution_metric(FfiStr category,
                                               uint8_t disabled,
                                               int32_t time_unit);
 
+uint64_t glean_new_url_metric(FfiStr category,
+                              FfiStr name,
+                              RawStringArray send_in_pings,
+                              int32_t send_in_pings_len,
+                              int32_t lifetime,
+                              uint8_t disabled);
+
 uint64_t glean_new_uuid_metric(FfiStr category,
                                FfiStr name,
                                RawStringArray send_in_pings,
@@ -529,6 +751,18 @@ uint64_t glean_new_uuid_metric(FfiStr category,
                                int32_t lifetime,
                                uint8_t disabled);
 
+void glean_numerator_add_to_numerator(uint64_t metric_id, int32_t amount);
+
+int32_t glean_numerator_test_get_denominator(uint64_t metric_id, FfiStr storage_name);
+
+int32_t glean_numerator_test_get_num_recorded_errors(uint64_t metric_id,
+                                                     int32_t error_type,
+                                                     FfiStr storage_name);
+
+int32_t glean_numerator_test_get_numerator(uint64_t metric_id, FfiStr storage_name);
+
+uint8_t glean_numerator_test_has_value(uint64_t metric_id, FfiStr storage_name);
+
 uint8_t glean_on_ready_to_submit_pings(void);
 
 char *glean_ping_collect(uint64_t ping_type_handle, FfiStr reason);
@@ -557,8 +791,24 @@ int64_t glean_quantity_test_get_value(uint64_t metric_id, FfiStr storage_name);
 
 uint8_t glean_quantity_test_has_value(uint64_t metric_id, FfiStr storage_name);
 
+void glean_rate_add_to_denominator(uint64_t metric_id, int32_t amount);
+
+void glean_rate_add_to_numerator(uint64_t metric_id, int32_t amount);
+
+int32_t glean_rate_test_get_denominator(uint64_t metric_id, FfiStr storage_name);
+
+int32_t glean_rate_test_get_num_recorded_errors(uint64_t metric_id,
+                                                int32_t error_type,
+                                                FfiStr storage_name);
+
+int32_t glean_rate_test_get_numerator(uint64_t metric_id, FfiStr storage_name);
+
+uint8_t glean_rate_test_has_value(uint64_t metric_id, FfiStr storage_name);
+
 void glean_register_ping_type(uint64_t ping_type_handle);
 
+uint8_t glean_set_debug_view_tag(FfiStr tag);
+
 void glean_set_dirty_flag(uint8_t flag);
 
 void glean_set_experiment_active(FfiStr experiment_id,
@@ -569,6 +819,10 @@ void glean_set_experiment_active(FfiStr experiment_id,
 
 void glean_set_experiment_inactive(FfiStr experiment_id);
 
+void glean_set_log_pings(uint8_t value);
+
+uint8_t glean_set_source_tags(RawStringArray raw_tags, int32_t tags_count);
+
 void glean_set_upload_enabled(uint8_t flag);
 
 /**
@@ -649,6 +903,16 @@ char *glean_timing_distribution_test_get_value_as_json_string(uint64_t metric_id
 
 uint8_t glean_timing_distribution_test_has_value(uint64_t metric_id, FfiStr storage_name);
 
+void glean_url_set(uint64_t metric_id, FfiStr value);
+
+int32_t glean_url_test_get_num_recorded_errors(uint64_t metric_id,
+                                               int32_t error_type,
+                                               FfiStr storage_name);
+
+char *glean_url_test_get_value(uint64_t metric_id, FfiStr storage_name);
+
+uint8_t glean_url_test_has_value(uint64_t metric_id, FfiStr storage_name);
+
 void glean_uuid_set(uint64_t metric_id, FfiStr value);
 
 char *glean_uuid_test_get_value(uint64_t metric_id, FfiStr storage_name);
@@ -8,7 +8,12 @@ use glean_core::{metrics::*, CommonMetricData, Lifetime};
 
 use crate::boolean::BOOLEAN_METRICS;
 use crate::counter::COUNTER_METRICS;
+use crate::custom_distribution::CUSTOM_DISTRIBUTION_METRICS;
+use crate::memory_distribution::MEMORY_DISTRIBUTION_METRICS;
+use crate::quantity::QUANTITY_METRICS;
 use crate::string::STRING_METRICS;
+use crate::timespan::TIMESPAN_METRICS;
+use crate::timing_distribution::TIMING_DISTRIBUTION_METRICS;
 use crate::*;
 
 /// Generate FFI functions for labeled metrics.
@@ -17,18 +22,20 @@ use crate::*;
 /// `LabeledMetric::new` and LabeledMetric.get`.
 /// The constructor function takes the general common meta data.
 ///
-/// If any additional data needs to be passed in, this macro cannot be used.
+/// Additional (non-common) constructor arguments are listed after the constructor name,
+/// as in `define_metric!`, and converted using `TryFrom::try_from`.
 ///
 /// Arguments:
 ///
 /// * `metric` - The metric type, e.g. `CounterMetric`.
 /// * `global` - The name of the newly constructed global to hold instances of the labeled metric.
 /// * `metric_global` - The name of the map to hold instances of the underlying metric type.
-/// * `new_name` - Function name to create a new labeled metric of this type.
+/// * `new_name(...)` - Function name to create a new labeled metric of this type,
+///   optionally followed by all additional arguments.
 /// * `destroy_name` - Function name to destroy the labeled metric.
 /// * `get_name` - Function name to get a new instance of the underlying metric.
 macro_rules! impl_labeled_metric {
-    ($metric:ty, $global:ident, $metric_global:ident, $new_name:ident, $destroy_name:ident, $get_name:ident, $test_get_num_recorded_errors:ident) => {
+    ($metric:ty, $global:ident, $metric_global:ident, $new_name:ident $(($($new_argname:ident: $new_argtyp:ty),* $(,)*))?, $destroy_name:ident, $get_name:ident, $test_get_num_recorded_errors:ident) => {
         static $global: once_cell::sync::Lazy<ConcurrentHandleMap<LabeledMetric<$metric>>> =
             once_cell::sync::Lazy::new(ConcurrentHandleMap::new);
         $crate::define_infallible_handle_map_deleter!($global, $destroy_name);
@@ -42,6 +49,7 @@ macro_rules! impl_labeled_metric {
             send_in_pings_len: i32,
             lifetime: i32,
             disabled: u8,
+            $($($new_argname: $new_argtyp,)*)?
             labels: RawStringArray,
             label_count: i32,
         ) -> u64 {
@@ -57,15 +65,22 @@ macro_rules! impl_labeled_metric {
                 };
                 let lifetime = Lifetime::try_from(lifetime)?;
 
+                $($(
+                    let $new_argname = TryFrom::try_from($new_argname)?;
+                )*)?
+
                 Ok(LabeledMetric::new(
-                    <$metric>::new(CommonMetricData {
-                        name,
-                        category,
-                        send_in_pings,
-                        lifetime,
-                        disabled: disabled != 0,
-                        ..Default::default()
-                    }),
+                    <$metric>::new(
+                        CommonMetricData {
+                            name,
+                            category,
+                            send_in_pings,
+                            lifetime,
+                            disabled: disabled != 0,
+                            ..Default::default()
+                        },
+                        $($($new_argname),*)?
+                    ),
                     labels,
                 ))
             })
@@ -136,3 +151,63 @@ impl_labeled_metric!(
     glean_labeled_string_metric_get,
     glean_labeled_string_test_get_num_recorded_errors
 );
+
+// Create the required FFI functions for LabeledMetric<QuantityMetric>
+impl_labeled_metric!(
+    QuantityMetric,
+    LABELED_QUANTITY,
+    QUANTITY_METRICS,
+    glean_new_labeled_quantity_metric,
+    glean_destroy_labeled_quantity_metric,
+    glean_labeled_quantity_metric_get,
+    glean_labeled_quantity_test_get_num_recorded_errors
+);
+
+// Create the required FFI functions for LabeledMetric<TimespanMetric>
+impl_labeled_metric!(
+    TimespanMetric,
+    LABELED_TIMESPAN,
+    TIMESPAN_METRICS,
+    glean_new_labeled_timespan_metric(time_unit: i32),
+    glean_destroy_labeled_timespan_metric,
+    glean_labeled_timespan_metric_get,
+    glean_labeled_timespan_test_get_num_recorded_errors
+);
+
+// Create the required FFI functions for LabeledMetric<TimingDistributionMetric>
+impl_labeled_metric!(
+    TimingDistributionMetric,
+    LABELED_TIMING_DISTRIBUTION,
+    TIMING_DISTRIBUTION_METRICS,
+    glean_new_labeled_timing_distribution_metric(time_unit: i32),
+    glean_destroy_labeled_timing_distribution_metric,
+    glean_labeled_timing_distribution_metric_get,
+    glean_labeled_timing_distribution_test_get_num_recorded_errors
+);
+
+// Create the required FFI functions for LabeledMetric<MemoryDistributionMetric>
+impl_labeled_metric!(
+    MemoryDistributionMetric,
+    LABELED_MEMORY_DISTRIBUTION,
+    MEMORY_DISTRIBUTION_METRICS,
+    glean_new_labeled_memory_distribution_metric(memory_unit: i32),
+    glean_destroy_labeled_memory_distribution_metric,
+    glean_labeled_memory_distribution_metric_get,
+    glean_labeled_memory_distribution_test_get_num_recorded_errors
+);
+
+// Create the required FFI functions for LabeledMetric<CustomDistributionMetric>
+impl_labeled_metric!(
+    CustomDistributionMetric,
+    LABELED_CUSTOM_DISTRIBUTION,
+    CUSTOM_DISTRIBUTION_METRICS,
+    glean_new_labeled_custom_distribution_metric(
+        range_min: u64,
+        range_max: u64,
+        bucket_count: u64,
+        histogram_type: i32
+    ),
+    glean_destroy_labeled_custom_distribution_metric,
+    glean_labeled_custom_distribution_metric_get,
+    glean_labeled_custom_distribution_test_get_num_recorded_errors
+);
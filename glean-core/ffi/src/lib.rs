@@ -22,15 +22,18 @@ mod event;
 mod ffi_string_ext;
 mod from_raw;
 mod handlemap_ext;
+mod jwe;
 mod labeled;
 mod memory_distribution;
 pub mod ping_type;
 mod quantity;
+mod rate;
 mod string;
 mod string_list;
 mod timespan;
 mod timing_distribution;
 pub mod upload;
+mod url;
 mod uuid;
 
 use ffi_string_ext::FallibleToString;
@@ -205,6 +208,9 @@ impl TryFrom<&FfiConfiguration<'_>> for glean_core::Configuration {
             application_id,
             max_events,
             delay_ping_lifetime_io,
+            rate_limit: None,
+            storage_quota: None,
+            database_backend: None,
         })
     }
 }
@@ -327,6 +333,16 @@ pub extern "C" fn glean_is_dirty_flag_set() -> u8 {
     with_glean_value(|glean| glean.is_dirty_flag_set())
 }
 
+#[no_mangle]
+pub extern "C" fn glean_handle_client_active() {
+    with_glean_value(|glean| glean.handle_client_active());
+}
+
+#[no_mangle]
+pub extern "C" fn glean_handle_client_inactive() {
+    with_glean_value(|glean| glean.handle_client_inactive());
+}
+
 #[no_mangle]
 pub extern "C" fn glean_test_clear_all_stores() {
     with_glean_value(|glean| glean.test_clear_all_stores())
@@ -342,6 +358,27 @@ pub extern "C" fn glean_is_first_run() -> u8 {
     with_glean_value(|glean| glean.is_first_run())
 }
 
+#[no_mangle]
+pub extern "C" fn glean_set_debug_view_tag(tag: FfiStr) -> u8 {
+    with_glean_mut(|glean| {
+        let tag = tag.to_string_fallible()?;
+        Ok(glean.set_debug_view_tag(&tag))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_set_log_pings(value: u8) {
+    with_glean_value_mut(|glean| glean.set_log_pings(value != 0));
+}
+
+#[no_mangle]
+pub extern "C" fn glean_set_source_tags(raw_tags: RawStringArray, tags_count: i32) -> u8 {
+    with_glean_mut(|glean| {
+        let tags = from_raw_string_array(raw_tags, tags_count)?;
+        Ok(glean.set_source_tags(tags))
+    })
+}
+
 // Unfortunately, the way we use CFFI in Python ("out-of-line", "ABI mode") does not
 // allow return values to be `union`s, so we need to use an output parameter instead of
 // a return value to get the task. The output data will be consumed and freed by the
@@ -0,0 +1,170 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::convert::TryFrom;
+
+use ffi_support::FfiStr;
+
+use glean_core::{metrics::*, CommonMetricData, Lifetime};
+
+use crate::*;
+
+define_metric!(RateMetric => RATE_METRICS {
+    new           -> glean_new_rate_metric(),
+    test_get_num_recorded_errors -> glean_rate_test_get_num_recorded_errors,
+    destroy       -> glean_destroy_rate_metric,
+
+    add_to_numerator -> glean_rate_add_to_numerator(amount: i32),
+    add_to_denominator -> glean_rate_add_to_denominator(amount: i32),
+});
+
+#[no_mangle]
+pub extern "C" fn glean_rate_test_has_value(metric_id: u64, storage_name: FfiStr) -> u8 {
+    with_glean_value(|glean| {
+        RATE_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .is_some()
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_rate_test_get_numerator(metric_id: u64, storage_name: FfiStr) -> i32 {
+    with_glean_value(|glean| {
+        RATE_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .unwrap()
+                .numerator
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_rate_test_get_denominator(metric_id: u64, storage_name: FfiStr) -> i32 {
+    with_glean_value(|glean| {
+        RATE_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .unwrap()
+                .denominator
+        })
+    })
+}
+
+define_metric!(NumeratorMetric => NUMERATOR_METRICS {
+    new           -> glean_new_numerator_metric(),
+    test_get_num_recorded_errors -> glean_numerator_test_get_num_recorded_errors,
+    destroy       -> glean_destroy_numerator_metric,
+
+    add_to_numerator -> glean_numerator_add_to_numerator(amount: i32),
+});
+
+#[no_mangle]
+pub extern "C" fn glean_numerator_test_has_value(metric_id: u64, storage_name: FfiStr) -> u8 {
+    with_glean_value(|glean| {
+        NUMERATOR_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .is_some()
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_numerator_test_get_numerator(metric_id: u64, storage_name: FfiStr) -> i32 {
+    with_glean_value(|glean| {
+        NUMERATOR_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .unwrap()
+                .numerator
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_numerator_test_get_denominator(
+    metric_id: u64,
+    storage_name: FfiStr,
+) -> i32 {
+    with_glean_value(|glean| {
+        NUMERATOR_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .unwrap()
+                .denominator
+        })
+    })
+}
+
+define_metric!(DenominatorMetric => DENOMINATOR_METRICS {
+    test_get_num_recorded_errors -> glean_denominator_test_get_num_recorded_errors,
+    destroy       -> glean_destroy_denominator_metric,
+
+    add -> glean_denominator_add(amount: i32),
+});
+
+/// Create a new denominator metric.
+///
+/// The numerators are given as handles of previously created numerator metrics.
+#[no_mangle]
+pub extern "C" fn glean_new_denominator_metric(
+    category: FfiStr,
+    name: FfiStr,
+    send_in_pings: RawStringArray,
+    send_in_pings_len: i32,
+    lifetime: i32,
+    disabled: u8,
+    numerators: RawInt64Array,
+    numerators_len: i32,
+) -> u64 {
+    DENOMINATOR_METRICS.insert_with_log(|| {
+        let name = name.to_string_fallible()?;
+        let category = category.to_string_fallible()?;
+        let send_in_pings = from_raw_string_array(send_in_pings, send_in_pings_len)?;
+        let lifetime = Lifetime::try_from(lifetime)?;
+        let numerators = from_raw_int64_array(numerators, numerators_len)
+            .into_iter()
+            .map(|handle| {
+                NUMERATOR_METRICS.get_u64(handle as u64, |numerator| {
+                    Ok::<_, glean_core::Error>(numerator.clone())
+                })
+            })
+            .collect::<glean_core::Result<Vec<_>>>()?;
+
+        Ok(DenominatorMetric::new(
+            CommonMetricData {
+                name,
+                category,
+                send_in_pings,
+                lifetime,
+                disabled: disabled != 0,
+                ..Default::default()
+            },
+            numerators,
+        ))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_denominator_test_has_value(metric_id: u64, storage_name: FfiStr) -> u8 {
+    with_glean_value(|glean| {
+        DENOMINATOR_METRICS.call_infallible(metric_id, |metric| {
+            metric
+                .test_get_value(glean, storage_name.as_str())
+                .is_some()
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn glean_denominator_test_get_value(metric_id: u64, storage_name: FfiStr) -> i32 {
+    with_glean_value(|glean| {
+        DENOMINATOR_METRICS.call_infallible(metric_id, |metric| {
+            metric.test_get_value(glean, storage_name.as_str()).unwrap()
+        })
+    })
+}
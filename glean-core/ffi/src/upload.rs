@@ -71,9 +71,15 @@ pub mod upload_result {
 ///   char *headers;
 /// } FfiPingUploadTask_Upload_Body;
 ///
+/// typedef struct {
+///   FfiPingUploadTask_Tag tag;
+///   uint64_t time;
+/// } FfiPingUploadTask_Wait_Body;
+///
 /// typedef union {
 ///   FfiPingUploadTask_Tag tag;
 ///   FfiPingUploadTask_Upload_Body upload;
+///   FfiPingUploadTask_Wait_Body wait;
 /// } FfiPingUploadTask;
 ///
 /// ```
@@ -104,7 +110,9 @@ pub enum FfiPingUploadTask {
         body: ByteBuffer,
         headers: *mut c_char,
     },
-    Wait,
+    Wait {
+        time: u64,
+    },
     Done,
 }
 
@@ -126,7 +134,7 @@ impl From<PingUploadTask> for FfiPingUploadTask {
                     headers: headers.into_raw(),
                 }
             }
-            PingUploadTask::Wait => FfiPingUploadTask::Wait,
+            PingUploadTask::Wait(time) => FfiPingUploadTask::Wait { time },
             PingUploadTask::Done => FfiPingUploadTask::Done,
         }
     }
@@ -3,6 +3,7 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::env;
+use std::time::Duration;
 
 use once_cell::sync::Lazy;
 use tempfile::Builder;
@@ -33,6 +34,8 @@ fn main() -> Result<(), Error> {
         max_events: None,
         delay_ping_lifetime_io: false,
         channel: None,
+        server_endpoint: None,
+        uploader: None,
     };
 
     let client_info = ClientInfoMetrics {
@@ -43,11 +46,11 @@ fn main() -> Result<(), Error> {
     glean::initialize(cfg, client_info)?;
     glean::register_ping_type(&PrototypePing);
 
-    if glean::submit_ping_by_name("prototype", None) {
-        log::info!("Successfully collected a prototype ping");
-    } else {
-        log::info!("Prototype ping failed");
-    }
+    glean::submit_ping_by_name("prototype", None);
+    log::info!("Submitted a prototype ping");
+
+    // Make sure the ping is stored before exiting, it is uploaded on the next run.
+    glean::shutdown(Duration::from_secs(5));
 
     Ok(())
 }
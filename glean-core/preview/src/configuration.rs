@@ -2,6 +2,10 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::sync::Arc;
+
+use crate::net::PingUploader;
+
 /// The Glean configuration.
 ///
 /// Optional values will be filled in with default values.
@@ -19,4 +23,13 @@ pub struct Configuration {
     pub delay_ping_lifetime_io: bool,
     /// The release channel the application is on, if known.
     pub channel: Option<String>,
+    /// The server pings are sent to.
+    /// Defaults to [`DEFAULT_TELEMETRY_ENDPOINT`](net/constant.DEFAULT_TELEMETRY_ENDPOINT.html).
+    pub server_endpoint: Option<String>,
+    /// The uploader used to send pings.
+    ///
+    /// If none is given and the `upload` feature is enabled, the built-in
+    /// [`HttpUploader`](net/struct.HttpUploader.html) is used.
+    /// Otherwise pings are only queued on disk.
+    pub uploader: Option<Arc<dyn PingUploader>>,
 }
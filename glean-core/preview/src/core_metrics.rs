@@ -2,7 +2,10 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use glean_core::{metrics::StringMetric, CommonMetricData, Lifetime};
+use glean_core::{
+    metrics::{CounterMetric, StringMetric},
+    registry::{MetricRegistry, GLEAN_METRICS},
+};
 
 /// Metrics included in every ping as `client_info`.
 #[derive(Debug)]
@@ -32,67 +35,35 @@ pub struct InternalMetrics {
     pub architecture: StringMetric,
     pub device_manufacturer: StringMetric,
     pub device_model: StringMetric,
+    pub preinit_tasks_overflow: CounterMetric,
 }
 
 impl InternalMetrics {
     pub fn new() -> Self {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(GLEAN_METRICS)
+            .expect("Glean's metrics.yaml should be valid");
+        let meta = |identifier: &str| {
+            registry
+                .metric(identifier)
+                .expect("The metric should be defined in Glean's metrics.yaml")
+                .common_metric_data()
+        };
+
         Self {
-            app_build: StringMetric::new(CommonMetricData {
-                name: "app_build".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            app_display_version: StringMetric::new(CommonMetricData {
-                name: "app_display_version".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            app_channel: StringMetric::new(CommonMetricData {
-                name: "app_channel".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            os_version: StringMetric::new(CommonMetricData {
-                name: "os_version".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            architecture: StringMetric::new(CommonMetricData {
-                name: "architecture".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            device_manufacturer: StringMetric::new(CommonMetricData {
-                name: "device_manufacturer".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
-            device_model: StringMetric::new(CommonMetricData {
-                name: "device_model".into(),
-                category: "".into(),
-                send_in_pings: vec!["glean_client_info".into()],
-                lifetime: Lifetime::Application,
-                disabled: false,
-                dynamic_label: None,
-            }),
+            app_build: StringMetric::new(meta("glean.internal.metrics.app_build")),
+            app_display_version: StringMetric::new(meta(
+                "glean.internal.metrics.app_display_version",
+            )),
+            app_channel: StringMetric::new(meta("glean.internal.metrics.app_channel")),
+            os_version: StringMetric::new(meta("glean.internal.metrics.os_version")),
+            architecture: StringMetric::new(meta("glean.internal.metrics.architecture")),
+            device_manufacturer: StringMetric::new(meta(
+                "glean.internal.metrics.device_manufacturer",
+            )),
+            device_model: StringMetric::new(meta("glean.internal.metrics.device_model")),
+            preinit_tasks_overflow: CounterMetric::new(meta("glean.error.preinit_tasks_overflow")),
         }
     }
 }
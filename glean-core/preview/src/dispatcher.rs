@@ -0,0 +1,235 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A global dispatcher for API calls.
+//!
+//! Before Glean is initialized, tasks are kept in a bounded queue.
+//! They are replayed in order on a worker thread once `flush_init` is called.
+//! From then on, every launched task is directly handed to the worker thread.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+/// The maximum number of tasks queued before initialization.
+pub const GLOBAL_DISPATCHER_LIMIT: usize = 100;
+
+type Task = Box<dyn FnOnce() + Send>;
+
+static GLOBAL_DISPATCHER: Lazy<Dispatcher> = Lazy::new(|| Dispatcher::new(GLOBAL_DISPATCHER_LIMIT));
+
+/// A dispatcher running tasks on a single worker thread.
+struct Dispatcher {
+    /// Tasks launched before `flush_init`, or `None` once they were flushed.
+    preinit_queue: Mutex<Option<Vec<Task>>>,
+
+    /// The maximum number of tasks in `preinit_queue`.
+    max_queue_size: usize,
+
+    /// The number of tasks dropped because `preinit_queue` was full.
+    overflow_count: AtomicUsize,
+
+    /// The sending side of the worker thread's queue.
+    sender: Mutex<Sender<Task>>,
+}
+
+impl Dispatcher {
+    /// Create a new dispatcher and start its worker thread.
+    fn new(max_queue_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+
+        thread::Builder::new()
+            .name("glean.dispatcher".into())
+            .spawn(move || {
+                for task in receiver {
+                    // A panicking task must not take down the thread, and all later tasks with it.
+                    if panic::catch_unwind(AssertUnwindSafe(task)).is_err() {
+                        log::error!("A task panicked on the dispatcher thread");
+                    }
+                }
+            })
+            .expect("Failed to spawn Glean's dispatcher thread");
+
+        Self {
+            preinit_queue: Mutex::new(Some(Vec::new())),
+            max_queue_size,
+            overflow_count: AtomicUsize::new(0),
+            sender: Mutex::new(sender),
+        }
+    }
+
+    fn send(&self, task: Task) {
+        if self.sender.lock().unwrap().send(task).is_err() {
+            log::error!("Failed to launch a task: the dispatcher thread is gone");
+        }
+    }
+
+    fn launch(&self, task: Task) {
+        // Holding the lock while sending keeps launched tasks ordered
+        // with the tasks replayed by `flush_init`.
+        let mut preinit_queue = self.preinit_queue.lock().unwrap();
+        match preinit_queue.as_mut() {
+            Some(queue) if queue.len() >= self.max_queue_size => {
+                log::warn!("Exceeded the maximum of queued tasks, dropping the task");
+                self.overflow_count.fetch_add(1, Ordering::SeqCst);
+            }
+            Some(queue) => queue.push(task),
+            None => self.send(task),
+        }
+    }
+
+    fn flush_init(&self) -> usize {
+        let mut preinit_queue = self.preinit_queue.lock().unwrap();
+        if let Some(queue) = preinit_queue.take() {
+            for task in queue {
+                self.send(task);
+            }
+        }
+        self.overflow_count.swap(0, Ordering::SeqCst)
+    }
+
+    /// Block until all tasks launched so far have run, or the optional timeout has passed.
+    ///
+    /// Before `flush_init` this returns false right away:
+    /// the queued tasks don't run until then, which might never happen
+    /// if this is called on the thread initializing Glean.
+    fn wait(&self, timeout: Option<Duration>) -> bool {
+        let (sender, receiver) = mpsc::channel();
+        {
+            // Holding the lock keeps `flush_init` from running in between.
+            let preinit_queue = self.preinit_queue.lock().unwrap();
+            if preinit_queue.is_some() {
+                log::warn!("Not waiting for the dispatcher queue before initialization");
+                return false;
+            }
+            self.send(Box::new(move || {
+                // The receiver might have stopped waiting already.
+                let _ = sender.send(());
+            }));
+        }
+
+        let result = match timeout {
+            Some(timeout) => receiver.recv_timeout(timeout).map_err(|e| e.to_string()),
+            None => receiver.recv().map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Failed to wait for the dispatcher queue: {}", e);
+                false
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn reset(&self) {
+        let mut preinit_queue = self.preinit_queue.lock().unwrap();
+        *preinit_queue = Some(Vec::new());
+        self.overflow_count.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Launch a new task on the global dispatcher.
+///
+/// Before initialization the task is queued, or dropped if the queue is full.
+/// Afterwards it is run on the dispatcher's worker thread.
+pub fn launch(task: impl FnOnce() + Send + 'static) {
+    GLOBAL_DISPATCHER.launch(Box::new(task))
+}
+
+/// Start running the tasks queued before initialization, in order.
+///
+/// All tasks launched afterwards run directly on the worker thread.
+///
+/// ## Return value
+///
+/// Returns the number of tasks dropped because the queue was full.
+pub fn flush_init() -> usize {
+    GLOBAL_DISPATCHER.flush_init()
+}
+
+/// Block until all tasks launched so far have run.
+///
+/// If called before `flush_init`, this returns right away.
+pub fn block_on_queue() {
+    GLOBAL_DISPATCHER.wait(None);
+}
+
+/// Block until all tasks launched so far have run, or the timeout has passed.
+///
+/// If called before `flush_init`, this returns right away.
+///
+/// ## Return value
+///
+/// Returns true if all tasks have run before the timeout, false otherwise.
+pub fn block_on_queue_timeout(timeout: Duration) -> bool {
+    GLOBAL_DISPATCHER.wait(Some(timeout))
+}
+
+/// Queue tasks again, as before initialization.
+///
+/// This is only needed in tests, where Glean is initialized several times.
+#[cfg(test)]
+pub fn reset() {
+    GLOBAL_DISPATCHER.reset()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn tasks_are_queued_until_flushed() {
+        let dispatcher = Dispatcher::new(2);
+        let result = Arc::new(Mutex::new(vec![]));
+
+        for i in 0..3 {
+            let result = Arc::clone(&result);
+            dispatcher.launch(Box::new(move || result.lock().unwrap().push(i)));
+        }
+        assert!(result.lock().unwrap().is_empty());
+
+        assert_eq!(1, dispatcher.flush_init());
+        let result_after = Arc::clone(&result);
+        dispatcher.launch(Box::new(move || result_after.lock().unwrap().push(3)));
+
+        let (sender, receiver) = mpsc::channel();
+        dispatcher.launch(Box::new(move || sender.send(()).unwrap()));
+        receiver.recv().unwrap();
+
+        assert_eq!(vec![0, 1, 3], *result.lock().unwrap());
+    }
+
+    #[test]
+    fn tasks_run_after_a_task_panicked() {
+        let dispatcher = Dispatcher::new(2);
+        dispatcher.flush_init();
+
+        dispatcher.launch(Box::new(|| panic!("This task panics")));
+        let (sender, receiver) = mpsc::channel();
+        dispatcher.launch(Box::new(move || sender.send(()).unwrap()));
+
+        receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("The task after the panicking one should run");
+    }
+
+    #[test]
+    fn waiting_before_flush_returns_right_away() {
+        let dispatcher = Dispatcher::new(1);
+
+        // Fill up the queue, so the waiting task itself would be dropped.
+        dispatcher.launch(Box::new(|| {}));
+        assert!(!dispatcher.wait(None));
+
+        dispatcher.flush_init();
+        assert!(dispatcher.wait(None));
+    }
+}
@@ -26,6 +26,8 @@
 //!     max_events: None,
 //!     delay_ping_lifetime_io: false,
 //!     channel: None,
+//!     server_endpoint: None,
+//!     uploader: None,
 //! };
 //! glean_preview::initialize(cfg, ClientInfoMetrics::unknown())?;
 //!
@@ -38,8 +40,11 @@
 //! # }
 //! ```
 
+use glean_core::scheduler::MetricsPingScheduler;
 use once_cell::sync::OnceCell;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub use configuration::Configuration;
 pub use core_metrics::ClientInfoMetrics;
@@ -47,7 +52,9 @@ pub use glean_core::{global_glean, setup_glean, CommonMetricData, Error, Glean,
 
 mod configuration;
 mod core_metrics;
+mod dispatcher;
 pub mod metrics;
+pub mod net;
 mod system;
 
 /// Application state to keep track of.
@@ -58,6 +65,12 @@ struct AppState {
 
     /// Client info metrics set by the application.
     client_info: ClientInfoMetrics,
+
+    /// The upload manager, if an uploader is available.
+    upload_manager: Option<net::UploadManager>,
+
+    /// The scheduler submitting the `metrics` ping once a day.
+    metrics_ping_scheduler: Arc<MetricsPingScheduler>,
 }
 
 /// A global singleton storing additional state for Glean.
@@ -78,31 +91,83 @@ fn setup_state(state: AppState) {
         STATE.set(Mutex::new(state)).unwrap();
     } else {
         let mut lock = STATE.get().unwrap().lock().unwrap();
+        // The previous scheduler would otherwise keep submitting `metrics` pings.
+        lock.metrics_ping_scheduler.cancel();
         *lock = state;
     }
 }
 
 fn with_glean<F, R>(f: F) -> R
 where
-    F: Fn(&Glean) -> R,
+    F: FnOnce(&Glean) -> R,
 {
     let glean = global_glean().expect("Global Glean object not initialized");
     let lock = glean.lock().unwrap();
     f(&lock)
 }
 
+/// Like `with_glean`, but gives up if the global Glean object is still locked at the deadline.
+///
+/// ## Return value
+///
+/// Returns `None` if the lock wasn't acquired before the deadline.
+fn with_glean_until<F, R>(deadline: Instant, f: F) -> Option<R>
+where
+    F: FnOnce(&Glean) -> R,
+{
+    let glean = global_glean().expect("Global Glean object not initialized");
+    loop {
+        match glean.try_lock() {
+            Ok(lock) => return Some(f(&lock)),
+            Err(TryLockError::Poisoned(e)) => panic!("{}", e),
+            Err(TryLockError::WouldBlock) => {}
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
 fn with_glean_mut<F, R>(f: F) -> R
 where
-    F: Fn(&mut Glean) -> R,
+    F: FnOnce(&mut Glean) -> R,
 {
     let glean = global_glean().expect("Global Glean object not initialized");
     let mut lock = glean.lock().unwrap();
     f(&mut lock)
 }
 
+/// Launch a task using the global Glean object on the dispatcher.
+///
+/// Before `initialize` the task is queued, afterwards it runs on the dispatcher's thread.
+fn launch_with_glean(callback: impl FnOnce(&Glean) + Send + 'static) {
+    dispatcher::launch(|| with_glean(callback))
+}
+
+/// Launch a task using the global Glean object mutably on the dispatcher.
+///
+/// Before `initialize` the task is queued, afterwards it runs on the dispatcher's thread.
+fn launch_with_glean_mut(callback: impl FnOnce(&mut Glean) + Send + 'static) {
+    dispatcher::launch(|| with_glean_mut(callback))
+}
+
+/// Block until all tasks launched so far have run.
+///
+/// Used by the test-only APIs to see the effects of previous calls.
+/// Returns right away if called before `initialize`.
+fn block_on_dispatcher() {
+    dispatcher::block_on_queue()
+}
+
 /// Create and initialize a new Glean object.
 ///
 /// See `glean_core::Glean::new`.
+///
+/// API calls made before initialization are queued, up to a maximum of
+/// `GLOBAL_DISPATCHER_LIMIT` calls, and replayed in order once Glean is initialized.
+/// Any calls beyond that are dropped. If that happens, the total number of calls made
+/// before initialization is recorded in the `glean.error.preinit_tasks_overflow` metric.
 pub fn initialize(cfg: Configuration, client_info: ClientInfoMetrics) -> Result<()> {
     let core_cfg = glean_core::Configuration {
         upload_enabled: cfg.upload_enabled,
@@ -110,22 +175,82 @@ pub fn initialize(cfg: Configuration, client_info: ClientInfoMetrics) -> Result<
         application_id: cfg.application_id.clone(),
         max_events: cfg.max_events,
         delay_ping_lifetime_io: cfg.delay_ping_lifetime_io,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
     let glean = Glean::new(core_cfg)?;
 
     // First initialize core metrics
     initialize_core_metrics(&glean, &client_info, cfg.channel.clone());
 
+    let server_endpoint = cfg
+        .server_endpoint
+        .unwrap_or_else(|| net::DEFAULT_TELEMETRY_ENDPOINT.to_string());
+    let upload_manager = cfg
+        .uploader
+        .or_else(default_uploader)
+        .map(|uploader| net::UploadManager::new(server_endpoint, uploader));
+
+    let metrics_ping_scheduler = MetricsPingScheduler::new(|task| {
+        launch_with_glean(move |glean| {
+            task(glean);
+            trigger_upload();
+        })
+    });
+    let scheduler = Arc::clone(&metrics_ping_scheduler);
+    let app_version = client_info.app_display_version.clone();
+
     // Now make this the global object available to others.
     setup_state(AppState {
         channel: cfg.channel,
         client_info,
+        upload_manager,
+        metrics_ping_scheduler,
     });
     glean_core::setup_glean(glean)?;
 
+    // Now that Glean is available, run the calls made before initialization.
+    let overflow_count = dispatcher::flush_init();
+    if overflow_count > 0 {
+        // As on the other platforms, this counts all the tasks made before initialization.
+        let task_count = dispatcher::GLOBAL_DISPATCHER_LIMIT + overflow_count;
+        launch_with_glean(move |glean| {
+            core_metrics::InternalMetrics::new()
+                .preinit_tasks_overflow
+                .add(glean, task_count as i32);
+        });
+    }
+
+    // The metrics ping might be overdue, which submits it right away.
+    // This runs after the calls made before initialization, without counting against their limit.
+    launch_with_glean(move |glean| scheduler.schedule(glean, &app_version));
+
+    // There might be pings left over from a previous run.
+    trigger_upload();
+
     Ok(())
 }
 
+/// The uploader to use if none was configured.
+#[cfg(feature = "upload")]
+fn default_uploader() -> Option<Arc<dyn net::PingUploader>> {
+    Some(Arc::new(net::HttpUploader::new()))
+}
+
+/// The uploader to use if none was configured.
+#[cfg(not(feature = "upload"))]
+fn default_uploader() -> Option<Arc<dyn net::PingUploader>> {
+    None
+}
+
+/// Signal the upload manager, if any, that there are pings to upload.
+fn trigger_upload() {
+    if let Some(upload_manager) = global_state().lock().unwrap().upload_manager.as_ref() {
+        upload_manager.trigger_upload();
+    }
+}
+
 fn initialize_core_metrics(
     glean: &Glean,
     client_info: &ClientInfoMetrics,
@@ -152,36 +277,93 @@ fn initialize_core_metrics(
     core_metrics.device_model.set(glean, "unknown".to_string());
 }
 
+/// Shut down Glean in an orderly fashion.
+///
+/// This waits for all previous API calls to be applied, persists data kept in memory,
+/// syncs the event files to disk and gives an upload in progress a chance to finish.
+/// The `metrics` ping is no longer scheduled and no further pings are uploaded afterwards.
+///
+/// ## Arguments
+///
+/// * `timeout` - The maximum time to wait for all of this to finish.
+///   Whatever is left to do after the timeout is abandoned.
+pub fn shutdown(timeout: Duration) {
+    if global_glean().is_none() {
+        log::warn!("Shutdown called before Glean was initialized, nothing to do");
+        return;
+    }
+
+    let deadline = Instant::now() + timeout;
+    if !dispatcher::block_on_queue_timeout(timeout) {
+        log::warn!("Timed out waiting for pending API calls on shutdown");
+    }
+
+    // A task stuck on the dispatcher might still hold the Glean object.
+    let shut_down = with_glean_until(deadline, |glean| {
+        glean.shutdown(deadline.saturating_duration_since(Instant::now()));
+    });
+    if shut_down.is_none() {
+        log::warn!("Timed out waiting for the Glean object on shutdown");
+    }
+
+    let state = global_state().lock().unwrap();
+    state.metrics_ping_scheduler.cancel();
+    if let Some(upload_manager) = state.upload_manager.as_ref() {
+        if !upload_manager.shutdown(deadline) {
+            log::warn!("Timed out waiting for the ping upload on shutdown");
+        }
+    }
+}
+
 /// Set whether upload is enabled or not.
 ///
 /// See `glean_core::Glean.set_upload_enabled`.
+///
+/// The change is applied on the dispatcher's thread.
 pub fn set_upload_enabled(enabled: bool) -> bool {
-    with_glean_mut(|glean| {
-        let state = global_state().lock().unwrap();
-        let old_enabled = glean.is_upload_enabled();
-        glean.set_upload_enabled(enabled);
-
-        if !old_enabled && enabled {
-            // If uploading is being re-enabled, we have to restore the
-            // application-lifetime metrics.
-            initialize_core_metrics(&glean, &state.client_info, state.channel.clone());
+    launch_with_glean_mut(move |glean| {
+        let changed = {
+            let state = global_state().lock().unwrap();
+            let old_enabled = glean.is_upload_enabled();
+            glean.set_upload_enabled(enabled);
+
+            if !old_enabled && enabled {
+                // If uploading is being re-enabled, we have to restore the
+                // application-lifetime metrics.
+                initialize_core_metrics(glean, &state.client_info, state.channel.clone());
+            }
+
+            old_enabled != enabled
+        };
+
+        // Disabling upload queues a deletion-request ping.
+        if changed && !enabled {
+            trigger_upload();
         }
+    });
 
-        enabled
-    })
+    enabled
 }
 
 /// Determine whether upload is enabled.
 ///
 /// See `glean_core::Glean.is_upload_enabled`.
+///
+/// This waits for previous API calls to be applied.
+/// Returns false if called before `initialize`.
 pub fn is_upload_enabled() -> bool {
-    with_glean(|glean| glean.is_upload_enabled())
+    block_on_dispatcher();
+    match global_glean() {
+        Some(glean) => glean.lock().unwrap().is_upload_enabled(),
+        None => false,
+    }
 }
 
 /// Register a new [`PingType`](metrics/struct.PingType.html).
 pub fn register_ping_type(ping: &metrics::PingType) {
-    with_glean_mut(|glean| {
-        glean.register_ping_type(&ping.ping_type);
+    let ping_type = ping.ping_type.clone();
+    launch_with_glean_mut(move |glean| {
+        glean.register_ping_type(&ping_type);
     })
 }
 
@@ -189,10 +371,8 @@ pub fn register_ping_type(ping: &metrics::PingType) {
 ///
 /// See `glean_core::Glean.submit_ping`.
 ///
-/// ## Return value
-///
-/// Returns true if a ping was assembled and queued, false otherwise.
-pub fn submit_ping(ping: &metrics::PingType, reason: Option<&str>) -> bool {
+/// The ping is assembled and queued for upload on the dispatcher's thread.
+pub fn submit_ping(ping: &metrics::PingType, reason: Option<&str>) {
     submit_ping_by_name(&ping.name, reason)
 }
 
@@ -200,11 +380,42 @@ pub fn submit_ping(ping: &metrics::PingType, reason: Option<&str>) -> bool {
 ///
 /// See `glean_core::Glean.submit_ping_by_name`.
 ///
-/// ## Return value
+/// The ping is assembled and queued for upload on the dispatcher's thread.
+pub fn submit_ping_by_name(ping: &str, reason: Option<&str>) {
+    let ping = ping.to_string();
+    let reason = reason.map(|reason| reason.to_string());
+    launch_with_glean(move |glean| {
+        let submitted = glean
+            .submit_ping_by_name(&ping, reason.as_deref())
+            .unwrap_or(false);
+        if submitted {
+            trigger_upload();
+        }
+    })
+}
+
+/// Handle the client becoming active, e.g. the application moving to the foreground.
+///
+/// See `glean_core::Glean.handle_client_active`.
 ///
-/// Returns true if a ping was assembled and queued, false otherwise.
-pub fn submit_ping_by_name(ping: &str, reason: Option<&str>) -> bool {
-    with_glean(|glean| glean.submit_ping_by_name(ping, reason).unwrap_or(false))
+/// The `baseline` pings are submitted on the dispatcher's thread.
+pub fn handle_client_active() {
+    launch_with_glean(|glean| {
+        glean.handle_client_active();
+        trigger_upload();
+    })
+}
+
+/// Handle the client becoming inactive, e.g. the application moving to the background.
+///
+/// See `glean_core::Glean.handle_client_inactive`.
+///
+/// The `baseline` ping is submitted on the dispatcher's thread.
+pub fn handle_client_inactive() {
+    launch_with_glean(|glean| {
+        glean.handle_client_inactive();
+        trigger_upload();
+    })
 }
 
 #[cfg(test)]
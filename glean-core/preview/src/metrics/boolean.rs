@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A boolean metric.
+///
+/// Records a simple flag.
+#[derive(Clone, Debug)]
+pub struct BooleanMetric(pub(crate) glean_core::metrics::BooleanMetric);
+
+impl BooleanMetric {
+    /// Create a new boolean metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::BooleanMetric::new(meta))
+    }
+
+    /// Set to the specified boolean value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - the value to set.
+    pub fn set(&self, value: bool) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as a boolean.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<bool> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
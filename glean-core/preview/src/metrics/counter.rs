@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A counter metric.
+///
+/// Used to count things.
+/// The value can only be incremented, not decremented.
+#[derive(Clone, Debug)]
+pub struct CounterMetric(pub(crate) glean_core::metrics::CounterMetric);
+
+impl CounterMetric {
+    /// Create a new counter metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::CounterMetric::new(meta))
+    }
+
+    /// Increase the counter by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount` - The amount to increase by. Should be positive.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is 0 or negative.
+    pub fn add(&self, amount: i32) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add(glean, amount))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as an integer.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<i32> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
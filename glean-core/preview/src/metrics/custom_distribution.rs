@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::metrics::{DistributionData, HistogramType};
+use glean_core::CommonMetricData;
+
+/// A custom distribution metric.
+///
+/// Custom distributions are used to accumulate samples into buckets
+/// with a custom range and bucketing.
+#[derive(Debug)]
+pub struct CustomDistributionMetric(pub(crate) glean_core::metrics::CustomDistributionMetric);
+
+impl CustomDistributionMetric {
+    /// Create a new custom distribution metric.
+    pub fn new(
+        meta: CommonMetricData,
+        range_min: u64,
+        range_max: u64,
+        bucket_count: u64,
+        histogram_type: HistogramType,
+    ) -> Self {
+        Self(glean_core::metrics::CustomDistributionMetric::new(
+            meta,
+            range_min,
+            range_max,
+            bucket_count,
+            histogram_type,
+        ))
+    }
+
+    /// Accumulates the provided signed samples in the metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `samples` - The vector holding the samples to be recorded by the metric.
+    ///
+    /// ## Notes
+    ///
+    /// Discards any negative value in `samples` and report an `ErrorType::InvalidValue`
+    /// for each of them.
+    pub fn accumulate_samples_signed(&self, samples: Vec<i64>) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.accumulate_samples_signed(glean, samples))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<DistributionData> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
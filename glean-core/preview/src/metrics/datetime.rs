@@ -0,0 +1,46 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, FixedOffset, Local};
+use glean_core::{metrics::TimeUnit, CommonMetricData};
+
+/// A datetime metric.
+///
+/// Used to record an absolute date and time, such as the time the user first ran
+/// the application.
+#[derive(Debug)]
+pub struct DatetimeMetric(pub(crate) glean_core::metrics::DatetimeMetric);
+
+impl DatetimeMetric {
+    /// Create a new datetime metric.
+    pub fn new(meta: CommonMetricData, time_unit: TimeUnit) -> Self {
+        Self(glean_core::metrics::DatetimeMetric::new(meta, time_unit))
+    }
+
+    /// Set the metric to a date/time which including the timezone offset.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - Some date/time value, with offset, to set the metric to.
+    ///   If none, the current local time is used.
+    pub fn set(&self, value: Option<DateTime<FixedOffset>>) {
+        let value = value.unwrap_or_else(|| {
+            let now = Local::now();
+            now.with_timezone(now.offset())
+        });
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, Some(value)))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as a String.
+    /// The precision of this value is truncated to the `time_unit` precision.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value_as_string(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value_as_string(glean, storage_name))
+    }
+}
@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+use super::NumeratorMetric;
+
+/// A denominator metric.
+///
+/// A counter that is also the denominator of a set of
+/// [`NumeratorMetric`](struct.NumeratorMetric.html)s.
+/// It is sent as a counter, and every increase is also added to the denominator of the numerators.
+#[derive(Clone, Debug)]
+pub struct DenominatorMetric(pub(crate) glean_core::metrics::DenominatorMetric);
+
+impl DenominatorMetric {
+    /// Create a new denominator metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `meta` - The metadata of the denominator.
+    /// * `numerators` - The numerators this is the denominator of.
+    pub fn new(meta: CommonMetricData, numerators: Vec<NumeratorMetric>) -> Self {
+        let numerators = numerators
+            .into_iter()
+            .map(|numerator| numerator.0)
+            .collect();
+        Self(glean_core::metrics::DenominatorMetric::new(
+            meta, numerators,
+        ))
+    }
+
+    /// Increase the denominator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount` - The amount to increase by. Should be positive.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is 0 or negative.
+    pub fn add(&self, amount: i32) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add(glean, amount))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as an integer.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<i32> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use glean_core::metrics::{ExtraKeys, ExtraValue, RecordedEvent};
+use glean_core::CommonMetricData;
+
+/// An event metric.
+///
+/// Events allow recording of e.g. individual occurences of user actions, say
+/// every time a view was open and from where. Each time you record an event, it
+/// records a timestamp, the event's name and a set of custom values.
+#[derive(Clone, Debug)]
+pub struct EventMetric(pub(crate) glean_core::metrics::EventMetric);
+
+impl EventMetric {
+    /// Create a new event metric.
+    pub fn new(meta: CommonMetricData, allowed_extra_keys: Vec<String>) -> Self {
+        Self(glean_core::metrics::EventMetric::new(
+            meta,
+            allowed_extra_keys,
+        ))
+    }
+
+    /// Record an event, with extra keys given by their index.
+    ///
+    /// The timestamp is taken from a monotonic clock at the time of the call.
+    ///
+    /// ## Arguments
+    ///
+    /// * `extra` - A HashMap of (key, value) pairs. The key is an index into
+    ///   the metric's `allowed_extra_keys` vector where the key's string is
+    ///   looked up. If any key index is out of range, an error is reported and
+    ///   no event is recorded.
+    pub fn record<M: Into<Option<HashMap<i32, String>>>>(&self, extra: M) {
+        let timestamp = time::precise_time_ns() / 1_000_000;
+        let extra = extra.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.record(glean, timestamp, extra))
+    }
+
+    /// Record an event, with extra keys given by their name.
+    ///
+    /// The timestamp is taken from a monotonic clock at the time of the call.
+    ///
+    /// ## Arguments
+    ///
+    /// * `extra` - A HashMap of (key, value) pairs, with string, integer or
+    ///   boolean values. If any key is not in the metric's `allowed_extra_keys`,
+    ///   an error is reported and no event is recorded.
+    pub fn record_with_names<M: Into<Option<HashMap<String, ExtraValue>>>>(&self, extra: M) {
+        let timestamp = time::precise_time_ns() / 1_000_000;
+        let extra = extra.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.record_with_names(glean, timestamp, extra))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Test whether there are currently stored events for this event metric.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_has_value(&self, storage_name: &str) -> bool {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_has_value(glean, storage_name))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the vector of currently stored events for this event metric.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<Vec<RecordedEvent>> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
+
+/// An event metric with typed extra keys.
+///
+/// Only the extra keys of type `K` can be recorded, which is checked at compile time.
+#[derive(Debug)]
+pub struct TypedEventMetric<K> {
+    inner: EventMetric,
+    extra_keys: PhantomData<fn(K)>,
+}
+
+impl<K: ExtraKeys> TypedEventMetric<K> {
+    /// Create a new event metric, allowing the extra keys of `K`.
+    pub fn new(meta: CommonMetricData) -> Self {
+        let allowed_extra_keys = K::ALLOWED_KEYS.iter().map(|key| key.to_string()).collect();
+        Self {
+            inner: EventMetric::new(meta, allowed_extra_keys),
+            extra_keys: PhantomData,
+        }
+    }
+
+    /// Record an event.
+    ///
+    /// The timestamp is taken from a monotonic clock at the time of the call.
+    ///
+    /// ## Arguments
+    ///
+    /// * `extra` - A HashMap of (key, value) pairs, with string, integer or
+    ///   boolean values.
+    pub fn record<M: Into<Option<HashMap<K, ExtraValue>>>>(&self, extra: M) {
+        let timestamp = time::precise_time_ns() / 1_000_000;
+        // Keys are turned into their names here, so that they can be sent to the dispatcher.
+        let extra = extra.into().map(|extra| {
+            extra
+                .into_iter()
+                .map(|(k, v)| (k.as_str().to_string(), v))
+                .collect::<HashMap<_, _>>()
+        });
+        let metric = self.inner.0.clone();
+        crate::launch_with_glean(move |glean| metric.record_with_names(glean, timestamp, extra))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Test whether there are currently stored events for this event metric.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_has_value(&self, storage_name: &str) -> bool {
+        self.inner.test_has_value(storage_name)
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the vector of currently stored events for this event metric.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<Vec<RecordedEvent>> {
+        self.inner.test_get_value(storage_name)
+    }
+}
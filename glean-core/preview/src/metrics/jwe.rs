@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A JWE metric.
+///
+/// Record a JSON Web Encryption value, in compact serialization.
+#[derive(Clone, Debug)]
+pub struct JweMetric(pub(crate) glean_core::metrics::JweMetric);
+
+impl JweMetric {
+    /// Create a new JWE metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::JweMetric::new(meta))
+    }
+
+    /// Set to the specified JWE value, in compact serialization.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The compact serialization of the JWE value.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error and doesn't set the value if it doesn't have five valid components.
+    pub fn set_with_compact_representation<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set_with_compact_representation(glean, value))
+    }
+
+    /// Build a JWE value from its components and set to it.
+    ///
+    /// ## Arguments
+    ///
+    /// * `header` - The BASE64URL encoded JWE protected header.
+    /// * `key` - The BASE64URL encoded encrypted key, which may be empty.
+    /// * `init_vector` - The BASE64URL encoded initialization vector, which may be empty.
+    /// * `cipher_text` - The BASE64URL encoded ciphertext.
+    /// * `auth_tag` - The BASE64URL encoded authentication tag, which may be empty.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error and doesn't set the value if a component is invalid.
+    pub fn set(
+        &self,
+        header: &str,
+        key: &str,
+        init_vector: &str,
+        cipher_text: &str,
+        auth_tag: &str,
+    ) {
+        let header = header.to_string();
+        let key = key.to_string();
+        let init_vector = init_vector.to_string();
+        let cipher_text = cipher_text.to_string();
+        let auth_tag = auth_tag.to_string();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| {
+            metric.set(glean, &header, &key, &init_vector, &cipher_text, &auth_tag)
+        })
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value, in compact serialization.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::sync::Mutex;
+
+use glean_core::CommonMetricData;
+
+use super::{
+    BooleanMetric, CounterMetric, CustomDistributionMetric, MemoryDistributionMetric,
+    QuantityMetric, StringMetric, TimespanMetric, TimingDistributionMetric,
+};
+
+/// Sealed traits protect against downstream implementations.
+///
+/// We wrap it in a private module that is inaccessible outside of this module.
+mod private {
+    use std::sync::{Arc, Mutex};
+
+    use glean_core::{metrics::MetricType, CommonMetricData};
+
+    /// The sealed labeled trait.
+    ///
+    /// This also allows us to hide methods, that are only used internally
+    /// and should not be visible to users of the object implementing the
+    /// `Labeled<T>` trait.
+    pub trait Sealed {
+        /// The `glean_core` metric type representing the labeled metric.
+        type Inner: MetricType + Clone + std::fmt::Debug;
+
+        /// Get the `glean_core` metric from the `glean_preview` metric.
+        fn into_inner(self) -> Self::Inner;
+
+        /// Create a new `glean_preview` metric from the inner type.
+        fn from_inner(metric: Self::Inner) -> Self;
+    }
+
+    /// Labeled metrics whose sub-metric only needs the metric metadata.
+    pub trait FromMeta: Sealed {
+        /// Create a new `glean_core` metric from the metadata.
+        fn new_inner(meta: CommonMetricData) -> Self::Inner;
+    }
+
+    /// Implement the sealed traits for a metric wrapping the `glean_core` metric directly.
+    macro_rules! impl_sealed {
+        ($ty:ident) => {
+            impl Sealed for super::$ty {
+                type Inner = glean_core::metrics::$ty;
+
+                fn into_inner(self) -> Self::Inner {
+                    self.0
+                }
+
+                fn from_inner(metric: Self::Inner) -> Self {
+                    super::$ty(metric)
+                }
+            }
+        };
+        ($ty:ident, from_meta) => {
+            impl_sealed!($ty);
+
+            impl FromMeta for super::$ty {
+                fn new_inner(meta: CommonMetricData) -> Self::Inner {
+                    glean_core::metrics::$ty::new(meta)
+                }
+            }
+        };
+    }
+
+    /// Implement the sealed trait for a metric keeping the `glean_core` metric behind a lock.
+    macro_rules! impl_sealed_locked {
+        ($ty:ident) => {
+            impl Sealed for super::$ty {
+                type Inner = glean_core::metrics::$ty;
+
+                fn into_inner(self) -> Self::Inner {
+                    self.0.lock().unwrap().clone()
+                }
+
+                fn from_inner(metric: Self::Inner) -> Self {
+                    super::$ty(Arc::new(Mutex::new(metric)))
+                }
+            }
+        };
+    }
+
+    impl_sealed!(CounterMetric, from_meta);
+    impl_sealed!(BooleanMetric, from_meta);
+    impl_sealed!(StringMetric, from_meta);
+    impl_sealed!(QuantityMetric, from_meta);
+    impl_sealed!(MemoryDistributionMetric);
+    impl_sealed!(CustomDistributionMetric);
+    impl_sealed_locked!(TimespanMetric);
+    impl_sealed_locked!(TimingDistributionMetric);
+}
+
+/// Marker trait for metrics that can be nested inside a labeled metric.
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait AllowLabeled: private::Sealed {}
+
+impl AllowLabeled for CounterMetric {}
+impl AllowLabeled for BooleanMetric {}
+impl AllowLabeled for StringMetric {}
+impl AllowLabeled for QuantityMetric {}
+impl AllowLabeled for TimespanMetric {}
+impl AllowLabeled for TimingDistributionMetric {}
+impl AllowLabeled for MemoryDistributionMetric {}
+impl AllowLabeled for CustomDistributionMetric {}
+
+/// A labeled metric.
+///
+/// Labeled metrics allow to record multiple sub-metrics of the same type under different string labels.
+#[derive(Debug)]
+pub struct LabeledMetric<T: AllowLabeled>(
+    pub(crate) Mutex<glean_core::metrics::LabeledMetric<T::Inner>>,
+);
+
+impl<T> LabeledMetric<T>
+where
+    T: AllowLabeled + private::FromMeta,
+{
+    /// Create a new labeled metric from the given metric metadata and optional list of labels.
+    ///
+    /// See [`get`](#method.get) for information on how static or dynamic labels are handled.
+    pub fn new(meta: CommonMetricData, labels: Option<Vec<String>>) -> LabeledMetric<T> {
+        let submetric = T::new_inner(meta);
+        LabeledMetric(Mutex::new(glean_core::metrics::LabeledMetric::new(
+            submetric, labels,
+        )))
+    }
+}
+
+impl<T> LabeledMetric<T>
+where
+    T: AllowLabeled,
+{
+    /// Create a new labeled metric from the given sub-metric and optional list of labels.
+    ///
+    /// Used for metric types that need more than the metadata to be created,
+    /// such as timespans and distributions.
+    /// The sub-metric itself is never recorded to.
+    ///
+    /// See [`get`](#method.get) for information on how static or dynamic labels are handled.
+    pub fn with_submetric(submetric: T, labels: Option<Vec<String>>) -> LabeledMetric<T> {
+        LabeledMetric(Mutex::new(glean_core::metrics::LabeledMetric::new(
+            submetric.into_inner(),
+            labels,
+        )))
+    }
+
+    /// Get a specific metric for a given label.
+    ///
+    /// If a set of acceptable labels were specified in the `metrics.yaml` file,
+    /// and the given label is not in the set, it will be recorded under the special `__other__` label.
+    ///
+    /// If a set of acceptable labels was not specified in the `metrics.yaml` file,
+    /// only the first 16 unique labels will be used.
+    /// After that, any additional labels will be recorded under the special `__other__` label.
+    ///
+    /// Labels must be `snake_case` and less than 30 characters.
+    /// If an invalid label is used, the metric will be recorded in the special `__other__` label.
+    pub fn get(&self, label: &str) -> T {
+        let inner = self.0.lock().unwrap().get(label);
+        T::from_inner(inner)
+    }
+}
@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::metrics::{DistributionData, MemoryUnit};
+use glean_core::CommonMetricData;
+
+/// A memory distribution metric.
+///
+/// Memory distributions are used to accumulate and store memory sizes.
+#[derive(Debug)]
+pub struct MemoryDistributionMetric(pub(crate) glean_core::metrics::MemoryDistributionMetric);
+
+impl MemoryDistributionMetric {
+    /// Create a new memory distribution metric.
+    pub fn new(meta: CommonMetricData, memory_unit: MemoryUnit) -> Self {
+        Self(glean_core::metrics::MemoryDistributionMetric::new(
+            meta,
+            memory_unit,
+        ))
+    }
+
+    /// Accumulates the provided sample in the metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `sample` - The sample to be recorded by the metric. The sample is assumed to be in the
+    ///   configured memory unit of the metric.
+    ///
+    /// ## Notes
+    ///
+    /// Values bigger than 1 Terabyte (2<sup>40</sup> bytes) are truncated
+    /// and an `ErrorType::InvalidValue` error is recorded.
+    pub fn accumulate(&self, sample: u64) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.accumulate(glean, sample))
+    }
+
+    /// Accumulates the provided signed samples in the metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `samples` - The vector holding the samples to be recorded by the metric.
+    ///
+    /// ## Notes
+    ///
+    /// Discards any negative value in `samples` and report an `ErrorType::InvalidValue`
+    /// for each of them.
+    pub fn accumulate_samples_signed(&self, samples: Vec<i64>) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.accumulate_samples_signed(glean, samples))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<DistributionData> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
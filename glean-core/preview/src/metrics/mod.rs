@@ -4,6 +4,51 @@
 
 //! The different metric types supported by the Glean SDK to handle data.
 
+mod boolean;
+mod counter;
+mod custom_distribution;
+mod datetime;
+mod denominator;
+mod event;
+mod jwe;
+mod labeled;
+mod memory_distribution;
+mod numerator;
+mod object;
 mod ping;
+mod quantity;
+mod rate;
+mod string;
+mod string_list;
+mod text;
+mod timespan;
+mod timing_distribution;
+mod url;
+mod uuid;
 
-pub use ping::PingType;
+pub use glean_core::metrics::{
+    DistributionData, ExtraKeys, ExtraValue, HistogramType, MemoryUnit, NoExtraKeys, ObjectSchema,
+    Rate, RecordedEvent, TimeUnit, TimerId,
+};
+
+pub use self::boolean::BooleanMetric;
+pub use self::counter::CounterMetric;
+pub use self::custom_distribution::CustomDistributionMetric;
+pub use self::datetime::DatetimeMetric;
+pub use self::denominator::DenominatorMetric;
+pub use self::event::{EventMetric, TypedEventMetric};
+pub use self::jwe::JweMetric;
+pub use self::labeled::{AllowLabeled, LabeledMetric};
+pub use self::memory_distribution::MemoryDistributionMetric;
+pub use self::numerator::NumeratorMetric;
+pub use self::object::ObjectMetric;
+pub use self::ping::{NoReasonCodes, PingType, ReasonCodes, TypedPingType};
+pub use self::quantity::QuantityMetric;
+pub use self::rate::RateMetric;
+pub use self::string::StringMetric;
+pub use self::string_list::StringListMetric;
+pub use self::text::TextMetric;
+pub use self::timespan::TimespanMetric;
+pub use self::timing_distribution::TimingDistributionMetric;
+pub use self::url::UrlMetric;
+pub use self::uuid::UuidMetric;
@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::metrics::Rate;
+use glean_core::CommonMetricData;
+
+/// A numerator metric.
+///
+/// A rate metric whose denominator is shared with other numerators,
+/// through a [`DenominatorMetric`](struct.DenominatorMetric.html).
+/// Only the numerator is increased directly.
+#[derive(Clone, Debug)]
+pub struct NumeratorMetric(pub(crate) glean_core::metrics::NumeratorMetric);
+
+impl NumeratorMetric {
+    /// Create a new numerator metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::NumeratorMetric::new(meta))
+    }
+
+    /// Increase the numerator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_numerator(&self, amount: i32) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add_to_numerator(glean, amount))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored numerator and denominator.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<Rate> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::metrics::ObjectSchema;
+use glean_core::CommonMetricData;
+use serde_json::Value as JsonValue;
+
+/// An object metric.
+///
+/// Record structured data as a JSON object or array.
+/// Values that don't match the metric's `ObjectSchema` are rejected.
+#[derive(Clone, Debug)]
+pub struct ObjectMetric(pub(crate) glean_core::metrics::ObjectMetric);
+
+impl ObjectMetric {
+    /// Create a new object metric.
+    ///
+    /// The root of the `schema` should be an `ObjectSchema::Object` or an `ObjectSchema::Array`.
+    pub fn new(meta: CommonMetricData, schema: ObjectSchema) -> Self {
+        Self(glean_core::metrics::ObjectMetric::new(meta, schema))
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The JSON object or array to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error and doesn't set the value if it doesn't match the metric's schema.
+    pub fn set(&self, value: JsonValue) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// Set to the specified JSON-encoded value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The JSON-encoded object or array to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error and doesn't set the value
+    /// if it isn't valid JSON or doesn't match the metric's schema.
+    pub fn set_string<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set_string(glean, &value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<JsonValue> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
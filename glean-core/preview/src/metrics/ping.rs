@@ -2,6 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::marker::PhantomData;
+
 /// Stores information about a ping.
 ///
 /// This is required so that given metric data queued on disk we can send
@@ -38,12 +40,91 @@ impl PingType {
         Self { name, ping_type }
     }
 
-    /// Submit the ping.
+    /// Set whether the ping includes text metrics.
     ///
-    /// ## Return value
+    /// Pings don't include text metrics unless they opt in.
+    pub fn with_include_text(mut self, include_text: bool) -> Self {
+        self.ping_type.include_text = include_text;
+        self
+    }
+
+    /// Submit the ping.
     ///
-    /// Returns true if a ping was assembled and queued, false otherwise.
-    pub fn submit(&self, reason: Option<&str>) -> bool {
+    /// The ping is assembled and queued for upload on the dispatcher's thread.
+    pub fn submit(&self, reason: Option<&str>) {
         crate::submit_ping(self, reason)
     }
 }
+
+/// The reasons a ping can be submitted for.
+///
+/// This is usually implemented by an enum generated from the `pings.yaml` file,
+/// with one variant per reason.
+pub trait ReasonCodes: Copy {
+    /// The names of all reasons.
+    const REASON_CODES: &'static [&'static str];
+
+    /// The name of the reason.
+    fn as_str(self) -> &'static str;
+}
+
+/// The reasons of pings without reasons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoReasonCodes {}
+
+impl ReasonCodes for NoReasonCodes {
+    const REASON_CODES: &'static [&'static str] = &[];
+
+    fn as_str(self) -> &'static str {
+        match self {}
+    }
+}
+
+/// A ping type with typed reasons.
+///
+/// Only the reasons of type `R` can be submitted, which is checked at compile time.
+#[derive(Debug)]
+pub struct TypedPingType<R> {
+    inner: PingType,
+    reason_codes: PhantomData<fn(R)>,
+}
+
+impl<R: ReasonCodes> TypedPingType<R> {
+    /// Create a new ping type for the given name, allowing the reasons of `R`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `name` - The name of the ping.
+    /// * `include_client_id` - Whether to include the client ID in the assembled ping when.
+    /// * `send_if_empty` - Whether the ping should be sent empty or not.
+    pub fn new<A: Into<String>>(name: A, include_client_id: bool, send_if_empty: bool) -> Self {
+        let reason_codes = R::REASON_CODES
+            .iter()
+            .map(|code| code.to_string())
+            .collect();
+        Self {
+            inner: PingType::new(name, include_client_id, send_if_empty, reason_codes),
+            reason_codes: PhantomData,
+        }
+    }
+
+    /// Set whether the ping includes text metrics.
+    ///
+    /// Pings don't include text metrics unless they opt in.
+    pub fn with_include_text(mut self, include_text: bool) -> Self {
+        self.inner = self.inner.with_include_text(include_text);
+        self
+    }
+
+    /// The untyped ping type, e.g. to register it.
+    pub fn ping_type(&self) -> &PingType {
+        &self.inner
+    }
+
+    /// Submit the ping.
+    ///
+    /// The ping is assembled and queued for upload on the dispatcher's thread.
+    pub fn submit(&self, reason: Option<R>) {
+        self.inner.submit(reason.map(R::as_str))
+    }
+}
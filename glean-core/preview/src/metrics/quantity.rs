@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A quantity metric.
+///
+/// Used to store explicit non-negative integers.
+#[derive(Clone, Debug)]
+pub struct QuantityMetric(pub(crate) glean_core::metrics::QuantityMetric);
+
+impl QuantityMetric {
+    /// Create a new quantity metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::QuantityMetric::new(meta))
+    }
+
+    /// Set the value. Must be non-negative.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The value. Must be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `value` is negative.
+    pub fn set(&self, value: i64) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as an integer.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<i64> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::metrics::Rate;
+use glean_core::CommonMetricData;
+
+/// A rate metric.
+///
+/// Used to determine the proportion of things.
+/// Both the numerator and the denominator are stored together,
+/// so they are always sent in the same ping.
+#[derive(Clone, Debug)]
+pub struct RateMetric(pub(crate) glean_core::metrics::RateMetric);
+
+impl RateMetric {
+    /// Create a new rate metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::RateMetric::new(meta))
+    }
+
+    /// Increase the numerator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_numerator(&self, amount: i32) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add_to_numerator(glean, amount))
+    }
+
+    /// Increase the denominator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_denominator(&self, amount: i32) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add_to_denominator(glean, amount))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored numerator and denominator.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<Rate> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A string metric.
+///
+/// Record an Unicode string value with arbitrary content.
+/// Strings are length-limited to `MAX_LENGTH_VALUE` bytes.
+#[derive(Clone, Debug)]
+pub struct StringMetric(pub(crate) glean_core::metrics::StringMetric);
+
+impl StringMetric {
+    /// Create a new string metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::StringMetric::new(meta))
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The string to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Truncates the value if it is longer than `MAX_LENGTH_VALUE` bytes and logs an error.
+    pub fn set<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as a string.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
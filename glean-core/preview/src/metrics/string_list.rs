@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A string list metric.
+///
+/// This allows appending a string value with arbitrary content to a list.
+#[derive(Clone, Debug)]
+pub struct StringListMetric(pub(crate) glean_core::metrics::StringListMetric);
+
+impl StringListMetric {
+    /// Create a new string list metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::StringListMetric::new(meta))
+    }
+
+    /// Add a new string to the list.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The string to add.
+    ///
+    /// ## Notes
+    ///
+    /// Truncates the value if it is longer than `MAX_STRING_LENGTH` bytes and logs an error.
+    pub fn add<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.add(glean, value))
+    }
+
+    /// Set to a specific list of strings.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The list of string to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// If passed an empty list, records an error and returns.
+    /// Truncates the list if it is longer than `MAX_LIST_LENGTH` and logs an error.
+    /// Truncates any value in the list if it is longer than `MAX_STRING_LENGTH` and logs an error.
+    pub fn set(&self, value: Vec<String>) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently-stored values.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<Vec<String>> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,46 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A text metric.
+///
+/// Record long-form Unicode text.
+/// Text metrics are only included in pings that opt in,
+/// see [`PingType::with_include_text`](struct.PingType.html#method.with_include_text).
+#[derive(Clone, Debug)]
+pub struct TextMetric(pub(crate) glean_core::metrics::TextMetric);
+
+impl TextMetric {
+    /// Create a new text metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::TextMetric::new(meta))
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The text to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Truncates the value if it is too long and logs an error.
+    /// The value is not recorded for pings that don't include text, which is logged as an error.
+    pub fn set<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as a string.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
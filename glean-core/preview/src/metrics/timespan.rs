@@ -0,0 +1,76 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use glean_core::{metrics::TimeUnit, CommonMetricData};
+
+/// A timespan metric.
+///
+/// Timespans are used to make a measurement of how much time is spent in a particular task.
+///
+/// The underlying metric keeps track of the running timer,
+/// so it is kept behind a lock to allow starting and stopping from a shared reference.
+#[derive(Debug)]
+pub struct TimespanMetric(pub(crate) Arc<Mutex<glean_core::metrics::TimespanMetric>>);
+
+impl TimespanMetric {
+    /// Create a new timespan metric.
+    pub fn new(meta: CommonMetricData, time_unit: TimeUnit) -> Self {
+        Self(Arc::new(Mutex::new(
+            glean_core::metrics::TimespanMetric::new(meta, time_unit),
+        )))
+    }
+
+    /// Start tracking time for the provided metric.
+    ///
+    /// This records an error if it's already tracking time (i.e. start was already
+    /// called with no corresponding `stop`): in that case the original
+    /// start time will be preserved.
+    pub fn start(&self) {
+        let start_time = time::precise_time_ns();
+        let metric = Arc::clone(&self.0);
+        crate::launch_with_glean(move |glean| metric.lock().unwrap().set_start(glean, start_time))
+    }
+
+    /// Stop tracking time for the provided metric. Sets the metric to the elapsed time.
+    ///
+    /// This will record an error if no `start` was called.
+    pub fn stop(&self) {
+        let stop_time = time::precise_time_ns();
+        let metric = Arc::clone(&self.0);
+        crate::launch_with_glean(move |glean| metric.lock().unwrap().set_stop(glean, stop_time))
+    }
+
+    /// Abort a previous `start` call. No error is recorded if no `start` was called.
+    pub fn cancel(&self) {
+        let metric = Arc::clone(&self.0);
+        crate::dispatcher::launch(move || metric.lock().unwrap().cancel())
+    }
+
+    /// Explicitly set the timespan value.
+    ///
+    /// This API should only be used if your library or application requires recording
+    /// times in a way that can not make use of `start`/`stop`/`cancel`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `elapsed` - The elapsed time to record.
+    pub fn set_raw(&self, elapsed: Duration) {
+        let metric = Arc::clone(&self.0);
+        crate::launch_with_glean(move |glean| metric.lock().unwrap().set_raw(glean, elapsed, false))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as an integer, in the metric's time unit.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<u64> {
+        crate::block_on_dispatcher();
+        let metric = self.0.lock().unwrap();
+        crate::with_glean(|glean| metric.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::sync::{Arc, Mutex};
+
+use glean_core::metrics::{DistributionData, TimeUnit, TimerId};
+use glean_core::CommonMetricData;
+
+/// A timing distribution metric.
+///
+/// Timing distributions are used to accumulate and store time measurement, for analyzing distributions of the timing data.
+///
+/// The underlying metric keeps track of all running timers,
+/// so it is kept behind a lock to allow starting and stopping from a shared reference.
+#[derive(Debug)]
+pub struct TimingDistributionMetric(
+    pub(crate) Arc<Mutex<glean_core::metrics::TimingDistributionMetric>>,
+);
+
+impl TimingDistributionMetric {
+    /// Create a new timing distribution metric.
+    pub fn new(meta: CommonMetricData, time_unit: TimeUnit) -> Self {
+        Self(Arc::new(Mutex::new(
+            glean_core::metrics::TimingDistributionMetric::new(meta, time_unit),
+        )))
+    }
+
+    /// Start tracking time for the provided metric.
+    ///
+    /// ## Return value
+    ///
+    /// Returns a unique `TimerId` for the new timer.
+    pub fn start(&self) -> TimerId {
+        let start_time = time::precise_time_ns();
+        self.0.lock().unwrap().set_start(start_time)
+    }
+
+    /// Stop tracking time for the provided metric and associated timer id.
+    ///
+    /// Add a count to the corresponding bucket in the timing distribution.
+    /// This will record an error if no `start` was called.
+    ///
+    /// ## Arguments
+    ///
+    /// * `id` - The `TimerId` to associate with this timing. This allows
+    ///   for concurrent timing of events associated with different ids to the
+    ///   same timing distribution metric.
+    pub fn stop_and_accumulate(&self, id: TimerId) {
+        let stop_time = time::precise_time_ns();
+        let metric = Arc::clone(&self.0);
+        crate::launch_with_glean(move |glean| {
+            metric
+                .lock()
+                .unwrap()
+                .set_stop_and_accumulate(glean, id, stop_time)
+        })
+    }
+
+    /// Abort a previous `start` call. No error is recorded if no `start` was called.
+    ///
+    /// ## Arguments
+    ///
+    /// * `id` - The `TimerId` to associate with this timing.
+    pub fn cancel(&self, id: TimerId) {
+        let metric = Arc::clone(&self.0);
+        crate::dispatcher::launch(move || metric.lock().unwrap().cancel(id))
+    }
+
+    /// Accumulates the provided signed samples in the metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `samples` - The vector holding the samples to be recorded by the metric,
+    ///   in the metric's time unit.
+    ///
+    /// ## Notes
+    ///
+    /// Discards any negative value in `samples` and report an `ErrorType::InvalidValue`
+    /// for each of them.
+    pub fn accumulate_samples_signed(&self, samples: Vec<i64>) {
+        let metric = Arc::clone(&self.0);
+        crate::launch_with_glean(move |glean| {
+            metric
+                .lock()
+                .unwrap()
+                .accumulate_samples_signed(glean, samples)
+        })
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<DistributionData> {
+        crate::block_on_dispatcher();
+        let metric = self.0.lock().unwrap();
+        crate::with_glean(|glean| metric.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,46 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+
+/// A URL metric.
+///
+/// Record a URL value. The URL must start with a valid scheme,
+/// `data:` URLs are not supported.
+/// URLs are length-limited to `MAX_URL_LENGTH` bytes.
+#[derive(Clone, Debug)]
+pub struct UrlMetric(pub(crate) glean_core::metrics::UrlMetric);
+
+impl UrlMetric {
+    /// Create a new URL metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::UrlMetric::new(meta))
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The URL to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error and doesn't set the value if the URL has no valid scheme or is a `data:` URL.
+    /// Truncates the value if it is longer than `MAX_URL_LENGTH` bytes and logs an error.
+    pub fn set<S: Into<String>>(&self, value: S) {
+        let value = value.into();
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the currently stored value as a string.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use glean_core::CommonMetricData;
+use uuid::Uuid;
+
+/// An UUID metric.
+///
+/// Stores UUID v4 (randomly generated) values.
+#[derive(Clone, Debug)]
+pub struct UuidMetric(pub(crate) glean_core::metrics::UuidMetric);
+
+impl UuidMetric {
+    /// Create a new UUID metric
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(glean_core::metrics::UuidMetric::new(meta))
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The UUID to set the metric to.
+    pub fn set(&self, value: Uuid) {
+        let metric = self.0.clone();
+        crate::launch_with_glean(move |glean| metric.set(glean, value))
+    }
+
+    /// Generate a new random UUID and set the metric to it.
+    ///
+    /// ## Return value
+    ///
+    /// Returns the stored UUID value.
+    pub fn generate_and_set(&self) -> Uuid {
+        let uuid = Uuid::new_v4();
+        self.set(uuid);
+        uuid
+    }
+
+    /// **Test-only API.**
+    ///
+    /// Get the stored UUID value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, storage_name: &str) -> Option<String> {
+        crate::block_on_dispatcher();
+        crate::with_glean(|glean| self.0.test_get_value(glean, storage_name))
+    }
+}
@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io::Read;
+
+use crate::net::{PingUploader, UploadResult};
+
+/// How long to wait for the connection to be established, in milliseconds.
+const CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// How long to wait for the server's response, in milliseconds.
+const READ_TIMEOUT_MS: u64 = 30_000;
+
+/// A simple HTTP/1.1 uploader, sending the ping to the server as a `POST` request.
+#[derive(Debug, Default)]
+pub struct HttpUploader;
+
+impl HttpUploader {
+    /// Create a new HTTP uploader.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PingUploader for HttpUploader {
+    /// Uploads a ping to a server.
+    ///
+    /// ## Arguments
+    ///
+    /// * `url` - the URL path to upload the data to.
+    /// * `body` - the serialized text data to send.
+    /// * `headers` - a vector of tuples containing the headers to send with
+    ///   the request, i.e. (Name, Value).
+    ///
+    /// ## Return value
+    ///
+    /// * `HttpStatus` if the server responded, no matter the status code.
+    /// * `UnrecoverableFailure` if the URL can't be used to send requests.
+    /// * `RecoverableFailure` for any other error, e.g. the connection failed.
+    fn upload(&self, url: String, body: Vec<u8>, headers: Vec<(String, String)>) -> UploadResult {
+        log::debug!("Uploading ping to {}", url);
+
+        let mut request = ureq::post(&url);
+        request
+            .timeout_connect(CONNECT_TIMEOUT_MS)
+            .timeout_read(READ_TIMEOUT_MS);
+        for (name, value) in &headers {
+            request.set(name, value);
+        }
+
+        let response = request.send_bytes(&body);
+        if let Some(err) = response.synthetic_error() {
+            return match err {
+                ureq::Error::BadUrl(_) | ureq::Error::UnknownScheme(_) => {
+                    log::error!("Unable to upload to {}: {}", url, err);
+                    UploadResult::UnrecoverableFailure
+                }
+                _ => {
+                    log::warn!("Failed to upload to {}: {}", url, err);
+                    UploadResult::RecoverableFailure
+                }
+            };
+        }
+
+        let status = response.status();
+        // Read the body to the end so the connection can be reused by the agent.
+        let _ = response.into_reader().read_to_end(&mut Vec::new());
+        UploadResult::HttpStatus(u32::from(status))
+    }
+}
@@ -0,0 +1,195 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Handling the Glean upload logic.
+//!
+//! This doesn't perform the actual upload but rather handles
+//! retries, upload limitations and error tracking.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use glean_core::upload::PingUploadTask;
+pub use glean_core::upload::{PingRequest, UploadResult};
+
+#[cfg(feature = "upload")]
+pub use http_uploader::HttpUploader;
+
+#[cfg(feature = "upload")]
+mod http_uploader;
+
+/// The default server pings are sent to.
+pub const DEFAULT_TELEMETRY_ENDPOINT: &str = "https://incoming.telemetry.mozilla.org";
+
+/// The longest time the upload thread sleeps without checking
+/// whether it should stop waiting.
+const WAIT_SLICE: Duration = Duration::from_millis(100);
+
+/// A description of a component used to upload pings.
+pub trait PingUploader: std::fmt::Debug + Send + Sync {
+    /// Uploads a ping to a server.
+    ///
+    /// ## Arguments
+    ///
+    /// * `url` - the URL path to upload the data to.
+    /// * `body` - the serialized text data to send.
+    /// * `headers` - a vector of tuples containing the headers to send with
+    ///   the request, i.e. (Name, Value).
+    ///
+    /// ## Return value
+    ///
+    /// The result of the upload attempt, which will be reported back to Glean.
+    fn upload(&self, url: String, body: Vec<u8>, headers: Vec<(String, String)>) -> UploadResult;
+}
+
+/// The logic for uploading pings: this leaves the actual upload
+/// to the configured `PingUploader` and coordinates with glean-core
+/// on a dedicated thread.
+#[derive(Debug)]
+pub(crate) struct UploadManager {
+    server_endpoint: String,
+    uploader: Arc<dyn PingUploader>,
+    is_uploading: Arc<AtomicBool>,
+    upload_requested: Arc<AtomicBool>,
+    is_shut_down: Arc<AtomicBool>,
+}
+
+impl UploadManager {
+    /// Create a new instance of the upload manager.
+    ///
+    /// ## Arguments
+    ///
+    /// * `server_endpoint` - the server pings are sent to.
+    /// * `uploader` - the uploader implementation to use.
+    pub(crate) fn new(server_endpoint: String, uploader: Arc<dyn PingUploader>) -> Self {
+        Self {
+            server_endpoint,
+            uploader,
+            is_uploading: Arc::new(AtomicBool::new(false)),
+            upload_requested: Arc::new(AtomicBool::new(false)),
+            is_shut_down: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Signals Glean to upload pings at the next best opportunity.
+    ///
+    /// If an upload thread is already running this is a no-op,
+    /// as that thread will pick up any newly enqueued ping.
+    pub(crate) fn trigger_upload(&self) {
+        if self.is_shut_down.load(Ordering::SeqCst) {
+            log::info!("Not uploading pings, the upload manager is shut down");
+            return;
+        }
+
+        // Set before checking for a running thread, so that a thread
+        // which is about to stop sees it and carries on.
+        self.upload_requested.store(true, Ordering::SeqCst);
+        if self
+            .is_uploading
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return;
+        }
+
+        let server = self.server_endpoint.clone();
+        let uploader = Arc::clone(&self.uploader);
+        let is_uploading = Arc::clone(&self.is_uploading);
+        let upload_requested = Arc::clone(&self.upload_requested);
+        let is_shut_down = Arc::clone(&self.is_shut_down);
+        let spawned = thread::Builder::new()
+            .name("glean.upload".into())
+            .spawn(move || {
+                log::trace!("Started glean.upload thread");
+                loop {
+                    upload_requested.store(false, Ordering::SeqCst);
+                    // Pings left in the queue on shutdown are uploaded on the next run.
+                    while !is_shut_down.load(Ordering::SeqCst) {
+                        let task = crate::with_glean(|glean| glean.get_upload_task());
+                        match task {
+                            PingUploadTask::Upload(request) => {
+                                let document_id = request.document_id.clone();
+                                let url = format!("{}{}", server, request.path);
+                                let headers = request
+                                    .headers
+                                    .into_iter()
+                                    .map(|(name, value)| (name.to_string(), value))
+                                    .collect();
+                                let result = uploader.upload(url, request.body, headers);
+                                crate::with_glean(|glean| {
+                                    glean.process_ping_upload_response(&document_id, result)
+                                });
+                            }
+                            PingUploadTask::Wait(time) => {
+                                wait(time, &is_shut_down, &upload_requested)
+                            }
+                            PingUploadTask::Done => break,
+                        }
+                    }
+
+                    // Clear the flag so that the next submitted ping starts a new thread.
+                    is_uploading.store(false, Ordering::SeqCst);
+
+                    // A ping submitted after the queue was found empty,
+                    // but before the flag was cleared, didn't start a new thread.
+                    // Pick it up here, unless another thread got to it first.
+                    if is_shut_down.load(Ordering::SeqCst)
+                        || !upload_requested.load(Ordering::SeqCst)
+                        || is_uploading
+                            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                            .is_err()
+                    {
+                        break;
+                    }
+                }
+
+                log::trace!("Stopped glean.upload thread");
+            });
+
+        if let Err(e) = spawned {
+            log::error!("Unable to spawn the upload thread: {}", e);
+            self.is_uploading.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Stop uploading pings.
+    ///
+    /// An upload in progress is given until the deadline to finish,
+    /// no further uploads are started.
+    ///
+    /// ## Return value
+    ///
+    /// Returns true if no upload was in progress by the deadline, false otherwise.
+    pub(crate) fn shutdown(&self, deadline: Instant) -> bool {
+        self.is_shut_down.store(true, Ordering::SeqCst);
+
+        while self.is_uploading.load(Ordering::SeqCst) {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+}
+
+/// Wait for `time` milliseconds, or until the upload manager is shut down or an upload is requested.
+///
+/// Backoff waits can take up to an hour. Newly submitted pings, which can be uploaded right away,
+/// and a shutdown shouldn't have to wait for them.
+fn wait(time: u64, is_shut_down: &AtomicBool, upload_requested: &AtomicBool) {
+    let deadline = Instant::now() + Duration::from_millis(time);
+    loop {
+        if is_shut_down.load(Ordering::SeqCst) || upload_requested.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        thread::sleep(WAIT_SLICE.min(deadline - now));
+    }
+}
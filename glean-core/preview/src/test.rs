@@ -25,6 +25,8 @@ fn new_glean() -> tempfile::TempDir {
         max_events: None,
         delay_ping_lifetime_io: false,
         channel: Some("testing".into()),
+        server_endpoint: None,
+        uploader: None,
     };
 
     initialize(cfg, ClientInfoMetrics::unknown()).unwrap();
@@ -73,15 +75,17 @@ fn client_info_reset_after_toggle() {
 
     // Disabling upload clears everything.
     crate::set_upload_enabled(false);
+    block_on_dispatcher();
     with_glean(|glean| {
-        assert!(!core_metrics
+        assert!(core_metrics
             .architecture
             .test_get_value(glean, "glean_client_info")
-            .is_some());
+            .is_none());
     });
 
     // Re-enabling upload should reset the values.
     crate::set_upload_enabled(true);
+    block_on_dispatcher();
     with_glean(|glean| {
         assert!(core_metrics
             .architecture
@@ -89,3 +93,399 @@ fn client_info_reset_after_toggle() {
             .is_some());
     });
 }
+
+#[test]
+fn records_through_the_global_singleton() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let _t = new_glean();
+
+    let counter = metrics::CounterMetric::new(CommonMetricData {
+        name: "counter".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        ..Default::default()
+    });
+    counter.add(1);
+    counter.add(2);
+    assert_eq!(Some(3), counter.test_get_value("store1"));
+
+    let string = metrics::StringMetric::new(CommonMetricData {
+        name: "string".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        ..Default::default()
+    });
+    string.set("value");
+    assert_eq!(Some("value".to_string()), string.test_get_value("store1"));
+
+    let url = metrics::UrlMetric::new(CommonMetricData {
+        name: "url".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        ..Default::default()
+    });
+    url.set("https://example.com/");
+    url.set("data:text/plain,ignored");
+    assert_eq!(
+        Some("https://example.com/".to_string()),
+        url.test_get_value("store1")
+    );
+
+    let timespan = metrics::TimespanMetric::new(
+        CommonMetricData {
+            name: "timespan".into(),
+            category: "local".into(),
+            send_in_pings: vec!["store1".into()],
+            ..Default::default()
+        },
+        metrics::TimeUnit::Nanosecond,
+    );
+    timespan.start();
+    timespan.stop();
+    assert!(timespan.test_get_value("store1").is_some());
+
+    let event = metrics::EventMetric::new(
+        CommonMetricData {
+            name: "event".into(),
+            category: "local".into(),
+            send_in_pings: vec!["store1".into()],
+            ..Default::default()
+        },
+        vec!["key".into()],
+    );
+    let mut extra = std::collections::HashMap::new();
+    extra.insert(0, "extra value".to_string());
+    event.record(extra);
+    let events = event.test_get_value("store1").unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(
+        Some("extra value"),
+        events[0].extra.as_ref().unwrap()["key"].as_str()
+    );
+}
+
+#[test]
+fn labeled_metrics_record_per_label() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let _t = new_glean();
+
+    let labeled: metrics::LabeledMetric<metrics::CounterMetric> = metrics::LabeledMetric::new(
+        CommonMetricData {
+            name: "labeled_counter".into(),
+            category: "local".into(),
+            send_in_pings: vec!["store1".into()],
+            ..Default::default()
+        },
+        Some(vec!["label1".into()]),
+    );
+
+    labeled.get("label1").add(1);
+    labeled.get("label1").add(1);
+    labeled.get("unknown").add(5);
+
+    assert_eq!(Some(2), labeled.get("label1").test_get_value("store1"));
+    assert_eq!(Some(5), labeled.get("__other__").test_get_value("store1"));
+}
+
+#[test]
+fn labeled_metrics_can_be_created_from_a_submetric() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let _t = new_glean();
+
+    let labeled = metrics::LabeledMetric::with_submetric(
+        metrics::TimespanMetric::new(
+            CommonMetricData {
+                name: "labeled_timespan".into(),
+                category: "local".into(),
+                send_in_pings: vec!["store1".into()],
+                ..Default::default()
+            },
+            metrics::TimeUnit::Millisecond,
+        ),
+        None,
+    );
+
+    labeled.get("label1").set_raw(Duration::from_millis(10));
+    labeled.get("label2").set_raw(Duration::from_millis(20));
+
+    assert_eq!(Some(10), labeled.get("label1").test_get_value("store1"));
+    assert_eq!(Some(20), labeled.get("label2").test_get_value("store1"));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ClickKeys {
+    ObjectId,
+    Other,
+}
+
+impl metrics::ExtraKeys for ClickKeys {
+    const ALLOWED_KEYS: &'static [&'static str] = &["object_id", "other"];
+
+    fn as_str(self) -> &'static str {
+        Self::ALLOWED_KEYS[self as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TestReasonCodes {
+    Manual,
+}
+
+impl metrics::ReasonCodes for TestReasonCodes {
+    const REASON_CODES: &'static [&'static str] = &["manual"];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            TestReasonCodes::Manual => "manual",
+        }
+    }
+}
+
+#[test]
+fn typed_events_and_pings_record_by_key() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let _t = new_glean();
+
+    let click: metrics::TypedEventMetric<ClickKeys> =
+        metrics::TypedEventMetric::new(CommonMetricData {
+            name: "click".into(),
+            category: "ui".into(),
+            send_in_pings: vec!["typed".into()],
+            ..Default::default()
+        });
+    let mut extra = std::collections::HashMap::new();
+    extra.insert(ClickKeys::ObjectId, "button".into());
+    extra.insert(ClickKeys::Other, 3.into());
+    click.record(extra);
+
+    let events = click.test_get_value("typed").unwrap();
+    assert_eq!(1, events.len());
+    let extra = events[0].extra.as_ref().unwrap();
+    assert_eq!(Some("button"), extra["object_id"].as_str());
+    assert_eq!(metrics::ExtraValue::Integer(3), extra["other"]);
+
+    let ping: metrics::TypedPingType<TestReasonCodes> =
+        metrics::TypedPingType::new("typed", true, false);
+    register_ping_type(ping.ping_type());
+    ping.submit(Some(TestReasonCodes::Manual));
+    assert!(!click.test_has_value("typed"));
+}
+
+#[test]
+fn calls_before_initialize_are_queued() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    dispatcher::reset();
+
+    let counter = metrics::CounterMetric::new(CommonMetricData {
+        name: "preinit_counter".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        ..Default::default()
+    });
+    let string = metrics::StringMetric::new(CommonMetricData {
+        name: "preinit_string".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        ..Default::default()
+    });
+    string.set("first");
+    for _ in 0..dispatcher::GLOBAL_DISPATCHER_LIMIT + 5 {
+        counter.add(1);
+    }
+    // Dropped, the queue is full.
+    string.set("second");
+
+    let _t = new_glean();
+
+    assert_eq!(Some("first".to_string()), string.test_get_value("store1"));
+    assert_eq!(
+        Some(dispatcher::GLOBAL_DISPATCHER_LIMIT as i32 - 1),
+        counter.test_get_value("store1")
+    );
+
+    // On the first run the `metrics` ping is submitted right after initialization.
+    // 6 counter increments and the second string value were dropped,
+    // out of all the calls made before initialization.
+    let pings_dir = _t.path().join("pending_pings");
+    let metrics_ping = pings_dir
+        .read_dir()
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .find(|contents| contents.contains("/metrics/"))
+        .expect("Expected a metrics ping to be submitted");
+    let payload: serde_json::Value =
+        serde_json::from_str(metrics_ping.lines().nth(1).unwrap()).unwrap();
+    assert_eq!(
+        dispatcher::GLOBAL_DISPATCHER_LIMIT as u64 + 7,
+        payload["metrics"]["counter"]["glean.error.preinit_tasks_overflow"]
+            .as_u64()
+            .unwrap()
+    );
+}
+
+#[test]
+fn shutdown_persists_delayed_data() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: true,
+        channel: Some("testing".into()),
+        server_endpoint: None,
+        uploader: None,
+    };
+    let string = metrics::StringMetric::new(CommonMetricData {
+        name: "delayed".into(),
+        category: "local".into(),
+        send_in_pings: vec!["store1".into()],
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    });
+
+    initialize(cfg.clone(), ClientInfoMetrics::unknown()).unwrap();
+    string.set("value");
+    shutdown(std::time::Duration::from_secs(5));
+
+    // The value was kept in memory, it survives because it was persisted on shutdown.
+    initialize(cfg, ClientInfoMetrics::unknown()).unwrap();
+    assert_eq!(Some("value".to_string()), string.test_get_value("store1"));
+}
+
+#[test]
+fn shutdown_gives_up_on_a_stuck_task_at_the_timeout() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let _t = new_glean();
+
+    // This task keeps the Glean object locked for longer than the shutdown timeout.
+    launch_with_glean(|_| thread::sleep(Duration::from_secs(3)));
+
+    let start = Instant::now();
+    shutdown(Duration::from_millis(500));
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // Let the stuck task finish before the next test initializes Glean again.
+    dispatcher::block_on_queue();
+}
+
+#[derive(Debug)]
+struct FakeUploader {
+    sender: Mutex<std::sync::mpsc::Sender<String>>,
+    // Uploads of this ping fail with a recoverable error.
+    failing_ping: Option<&'static str>,
+}
+
+impl net::PingUploader for FakeUploader {
+    fn upload(
+        &self,
+        url: String,
+        _body: Vec<u8>,
+        _headers: Vec<(String, String)>,
+    ) -> net::UploadResult {
+        let fails = self
+            .failing_ping
+            .map_or(false, |ping| url.contains(&format!("/{}/", ping)));
+        self.sender.lock().unwrap().send(url).unwrap();
+        if fails {
+            net::UploadResult::RecoverableFailure
+        } else {
+            net::UploadResult::HttpStatus(200)
+        }
+    }
+}
+
+#[test]
+fn submitted_pings_are_uploaded() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: false,
+        channel: Some("testing".into()),
+        server_endpoint: Some("http://localhost".into()),
+        uploader: Some(Arc::new(FakeUploader {
+            sender: Mutex::new(sender),
+            failing_ping: None,
+        })),
+    };
+    initialize(cfg, ClientInfoMetrics::unknown()).unwrap();
+
+    let ping = metrics::PingType::new("test-upload", true, true, vec![]);
+    register_ping_type(&ping);
+    ping.submit(None);
+
+    let url = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .unwrap();
+    assert!(url.starts_with("http://localhost/submit/org-mozilla-fogotype-test/test-upload/1/"));
+}
+
+#[test]
+fn pings_submitted_during_a_backoff_wait_are_uploaded() {
+    let _lock = GLOBAL_LOCK.lock().unwrap();
+    env_logger::try_init().ok();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: false,
+        channel: Some("testing".into()),
+        server_endpoint: Some("http://localhost".into()),
+        uploader: Some(Arc::new(FakeUploader {
+            sender: Mutex::new(sender),
+            failing_ping: Some("failing"),
+        })),
+    };
+    initialize(cfg, ClientInfoMetrics::unknown()).unwrap();
+
+    // The failed upload makes the upload thread wait for the ping's backoff time.
+    let failing = metrics::PingType::new("failing", true, true, vec![]);
+    register_ping_type(&failing);
+    failing.submit(None);
+    let url = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .unwrap();
+    assert!(url.contains("/failing/"));
+    // Give the upload thread time to process the failure and start waiting.
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    // The backoff time is much longer than this timeout.
+    let ping = metrics::PingType::new("test-upload", true, true, vec![]);
+    register_ping_type(&ping);
+    ping.submit(None);
+    let url = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .unwrap();
+    assert!(url.contains("/test-upload/"));
+
+    // Shutting down doesn't wait for the backoff time either.
+    let start = std::time::Instant::now();
+    shutdown(std::time::Duration::from_secs(10));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
@@ -2,14 +2,17 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::Path;
+use std::time::Duration;
 
 use jsonschema_valid::{self, schemas::Draft6};
 use serde_json::Value;
 
 use glean::{metrics::PingType, ClientInfoMetrics, Configuration};
+use glean_core::{metrics::*, CommonMetricData, Lifetime};
 use glean_preview as glean;
 
 const SCHEMA_JSON: &str = include_str!("../../../glean.1.schema.json");
@@ -33,6 +36,8 @@ fn new_glean() -> tempfile::TempDir {
         max_events: None,
         delay_ping_lifetime_io: false,
         channel: None,
+        server_endpoint: None,
+        uploader: None,
     };
 
     let client_info = ClientInfoMetrics {
@@ -55,11 +60,140 @@ fn validate_against_schema() {
     let ping_type = PingType::new("test", true, /* send_if_empty */ true, vec![]);
     glean::register_ping_type(&ping_type);
     ping_type.submit(None);
+    // The ping is submitted on the dispatcher, this waits for it.
+    assert!(glean::is_upload_enabled());
 
     // Read the ping from disk.
     // We know where it should be placed.
-    let pings_dir = dir.path().join("pending_pings");
-    let pending_pings: Vec<PathBuf> = pings_dir
+    let pending_pings = read_pending_pings(dir.path());
+
+    // There should only be one: our custom ping.
+    assert_eq!(1, pending_pings.len());
+
+    assert_valid(&schema, &pending_pings[0].1);
+}
+
+#[test]
+fn validate_metrics_against_schema() {
+    let schema = load_schema();
+
+    // A separate, non-global Glean object, so that the metric types
+    // not exposed by `glean_preview` can be recorded as well.
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = glean_core::Configuration {
+        upload_enabled: true,
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        max_events: None,
+        delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
+    };
+    let mut glean = glean_core::Glean::new(cfg).unwrap();
+
+    // The client info the language bindings would otherwise set.
+    for name in &[
+        "app_build",
+        "app_display_version",
+        "architecture",
+        "os_version",
+    ] {
+        let metric = StringMetric::new(CommonMetricData {
+            name: name.to_string(),
+            category: "".into(),
+            send_in_pings: vec!["glean_client_info".into()],
+            lifetime: Lifetime::Application,
+            ..Default::default()
+        });
+        metric.set(&glean, "unknown");
+    }
+
+    let mut ping_type = glean_core::metrics::PingType::new("store1", true, false, vec![]);
+    ping_type.include_text = true;
+    glean.register_ping_type(&ping_type);
+
+    let meta = |name: &str| CommonMetricData {
+        name: name.into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    };
+
+    TextMetric::new(meta("text")).set(&glean, "a long\nmultiline text");
+    UrlMetric::new(meta("url")).set(&glean, "https://example.com/path?query=1");
+    JweMetric::new(meta("jwe"))
+        .set_with_compact_representation(&glean, "eyJhbGciOiJkaXIifQ..aXY.Y2lwaGVy.dGFn");
+
+    let rate = RateMetric::new(meta("rate"));
+    rate.add_to_numerator(&glean, 1);
+    rate.add_to_denominator(&glean, 10);
+    let mut labeled_rate = LabeledMetric::new(RateMetric::new(meta("labeled_rate")), None);
+    labeled_rate.get("label").add_to_numerator(&glean, 2);
+    labeled_rate.get("label").add_to_denominator(&glean, 20);
+
+    let mut labeled_quantity =
+        LabeledMetric::new(QuantityMetric::new(meta("labeled_quantity")), None);
+    labeled_quantity.get("label").set(&glean, 5);
+    let mut labeled_timespan = LabeledMetric::new(
+        TimespanMetric::new(meta("labeled_timespan"), TimeUnit::Millisecond),
+        None,
+    );
+    labeled_timespan
+        .get("label")
+        .set_raw(&glean, Duration::from_millis(10), false);
+    let mut labeled_memory = LabeledMetric::new(
+        MemoryDistributionMetric::new(meta("labeled_memory"), MemoryUnit::Kilobyte),
+        None,
+    );
+    labeled_memory.get("label").accumulate(&glean, 1024);
+    let mut labeled_custom = LabeledMetric::new(
+        CustomDistributionMetric::new(meta("labeled_custom"), 1, 100, 10, HistogramType::Linear),
+        None,
+    );
+    labeled_custom
+        .get("label")
+        .accumulate_samples_signed(&glean, vec![5, 50]);
+
+    let mut item = HashMap::new();
+    item.insert("name".to_string(), ObjectSchema::String);
+    item.insert("size".to_string(), ObjectSchema::Number);
+    let object = ObjectMetric::new(
+        meta("object"),
+        ObjectSchema::Array {
+            items: Box::new(ObjectSchema::Object(item)),
+            max_length: 2,
+        },
+    );
+    object.set(&glean, serde_json::json!([{"name": "a", "size": 1}]));
+
+    let event = EventMetric::new(
+        meta("event"),
+        vec!["count".into(), "enabled".into(), "name".into()],
+    );
+    let mut extra = HashMap::new();
+    extra.insert("count".to_string(), ExtraValue::Integer(5));
+    extra.insert("enabled".to_string(), ExtraValue::Boolean(true));
+    extra.insert("name".to_string(), ExtraValue::String("value".into()));
+    event.record_with_names(&glean, 1000, extra);
+
+    assert!(glean.submit_ping(&ping_type, None).unwrap());
+
+    let pending_pings = read_pending_pings(dir.path());
+    assert_eq!(1, pending_pings.len());
+    assert!(pending_pings[0].0.contains("/store1/"));
+
+    assert_valid(&schema, &pending_pings[0].1);
+}
+
+/// Read all the pings pending upload in the given data directory.
+///
+/// Returns the URL and the JSON body of each ping.
+fn read_pending_pings(data_path: &Path) -> Vec<(String, Value)> {
+    let pings_dir = data_path.join("pending_pings");
+    pings_dir
         .read_dir()
         .unwrap()
         .filter_map(|entry| entry.ok())
@@ -67,27 +201,27 @@ fn validate_against_schema() {
             Ok(f) if f.is_file() => Some(entry.path()),
             _ => None,
         })
-        .collect();
-
-    // There should only be one: our custom ping.
-    assert_eq!(1, pending_pings.len());
+        .map(|path| {
+            // 1. line: the URL
+            // 2. line: the JSON body
+            let file = File::open(&path).unwrap();
+            let mut lines = BufReader::new(file).lines();
+            let url = lines.next().unwrap().unwrap();
+            let body = lines.next().unwrap().unwrap();
+            (url, serde_json::from_str(&body).unwrap())
+        })
+        .collect()
+}
 
-    // 1. line: the URL
-    // 2. line: the JSON body
-    let file = File::open(&pending_pings[0]).unwrap();
-    let mut lines = BufReader::new(file).lines();
-    let _url = lines.next().unwrap().unwrap();
-    let body = lines.next().unwrap().unwrap();
-
-    // Now validate against the vendored schema
-    let data = serde_json::from_str(&body).unwrap();
-    let cfg = jsonschema_valid::Config::from_schema(&schema, Some(&Draft6)).unwrap();
-    let validation = cfg.validate(&data);
+/// Validate the ping payload against the vendored schema.
+fn assert_valid(schema: &Value, data: &Value) {
+    let cfg = jsonschema_valid::Config::from_schema(schema, Some(&Draft6)).unwrap();
+    let validation = cfg.validate(data);
     match validation {
         Ok(()) => {}
         Err(e) => {
             let errors = e.map(|e| format!("{}", e)).collect::<Vec<_>>();
-            assert!(false, "Data: {:#?}\nErrors: {:#?}", data, errors);
+            panic!("Data: {:#?}\nErrors: {:#?}", data, errors);
         }
     }
 }
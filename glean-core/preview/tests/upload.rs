@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "upload")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+use glean_preview::net::{HttpUploader, PingUploader, UploadResult};
+
+/// A request as seen by the mock server.
+struct ReceivedRequest {
+    request_line: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// Start a mock server answering a single request with the given status code.
+///
+/// Returns the server's address and a receiver for the request it got.
+fn mock_server(status: u32) -> (String, mpsc::Receiver<ReceivedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next().unwrap().trim().to_string();
+            let value = parts.next().unwrap_or("").trim().to_string();
+            headers.push((name, value));
+        }
+
+        let content_length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .map(|(_, value)| value.parse::<usize>().unwrap())
+            .unwrap_or(0);
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            status
+        )
+        .unwrap();
+
+        sender
+            .send(ReceivedRequest {
+                request_line: request_line.trim_end().to_string(),
+                headers,
+                body,
+            })
+            .unwrap();
+    });
+
+    (address, receiver)
+}
+
+#[test]
+fn posts_body_and_headers_to_the_server() {
+    let (address, receiver) = mock_server(200);
+
+    let url = format!("{}/submit/app/ping/1/doc-id", address);
+    let body = b"{\"ping\": true}".to_vec();
+    let headers = vec![
+        ("Content-Type".to_string(), "application/json".to_string()),
+        ("X-Client-Type".to_string(), "Glean".to_string()),
+    ];
+
+    let result = HttpUploader::new().upload(url, body.clone(), headers);
+    match result {
+        UploadResult::HttpStatus(200) => {}
+        other => panic!("Unexpected upload result {:?}", other),
+    }
+
+    let request = receiver.recv().unwrap();
+    assert_eq!(
+        "POST /submit/app/ping/1/doc-id HTTP/1.1",
+        request.request_line
+    );
+    assert!(request
+        .headers
+        .contains(&("X-Client-Type".to_string(), "Glean".to_string())));
+    assert_eq!(body, request.body);
+}
+
+#[test]
+fn reports_server_errors_as_http_status() {
+    let (address, _receiver) = mock_server(500);
+
+    let url = format!("{}/submit/app/ping/1/doc-id", address);
+    match HttpUploader::new().upload(url, vec![], vec![]) {
+        UploadResult::HttpStatus(500) => {}
+        other => panic!("Unexpected upload result {:?}", other),
+    }
+}
+
+#[test]
+fn connection_failures_are_recoverable() {
+    // Bind and immediately drop a listener, so nothing listens on that port.
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    let url = format!("http://{}/submit/app/ping/1/doc-id", address);
+    match HttpUploader::new().upload(url, vec![], vec![]) {
+        UploadResult::RecoverableFailure => {}
+        other => panic!("Unexpected upload result {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_schemes_are_unrecoverable() {
+    let url = "gopher://localhost/submit/app/ping/1/doc-id".to_string();
+    match HttpUploader::new().upload(url, vec![], vec![]) {
+        UploadResult::UnrecoverableFailure => {}
+        other => panic!("Unexpected upload result {:?}", other),
+    }
+}
@@ -19,6 +19,9 @@ pub enum Lifetime {
     Application,
     /// The metric is reset with each user profile
     User,
+    /// The metric is reset at the end of each session,
+    /// when the client becomes inactive
+    Session,
 }
 
 impl Default for Lifetime {
@@ -34,6 +37,7 @@ impl Lifetime {
             Lifetime::Ping => "ping",
             Lifetime::Application => "app",
             Lifetime::User => "user",
+            Lifetime::Session => "session",
         }
     }
 }
@@ -46,13 +50,14 @@ impl TryFrom<i32> for Lifetime {
             0 => Ok(Lifetime::Ping),
             1 => Ok(Lifetime::Application),
             2 => Ok(Lifetime::User),
+            3 => Ok(Lifetime::Session),
             e => Err(ErrorKind::Lifetime(e).into()),
         }
     }
 }
 
 /// The common set of data shared across all different metric types.
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommonMetricData {
     /// The metric's name.
     pub name: String,
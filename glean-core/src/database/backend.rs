@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The interface between the database and the storage it persists data to.
+
+use std::fmt::Debug;
+use std::panic::RefUnwindSafe;
+
+use crate::Lifetime;
+use crate::Result;
+
+/// A key-value storage for metric data, with one separate store per lifetime.
+///
+/// Keys are the storage keys built by the `Database`,
+/// in the form `{storage_name}#{metric_id}`.
+/// Values are opaque byte blobs, the storage doesn't interpret them.
+///
+/// Backends need to be `RefUnwindSafe`, as the FFI catches panics around calls into Glean.
+pub trait StorageBackend: Debug + Send + Sync + RefUnwindSafe {
+    /// Get the value stored under a key.
+    ///
+    /// ## Return value
+    ///
+    /// The value, or `None` if nothing is stored under this key.
+    fn get(&self, lifetime: Lifetime, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store a value under a key, replacing any previous value.
+    fn put(&self, lifetime: Lifetime, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Store multiple values at once.
+    ///
+    /// Backends that support transactions should store all of them in a single transaction.
+    fn put_many(&self, lifetime: Lifetime, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        for (key, value) in entries {
+            self.put(lifetime, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the value stored under a key with a new value computed from it.
+    ///
+    /// The transformation function gets the current value, if any.
+    /// Backends that support transactions should read and write in a single transaction.
+    fn update(
+        &self,
+        lifetime: Lifetime,
+        key: &str,
+        transform: &mut dyn FnMut(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<()> {
+        let old_value = self.get(lifetime, key)?;
+        let new_value = transform(old_value.as_deref());
+        self.put(lifetime, key, &new_value)
+    }
+
+    /// Delete the value stored under a key.
+    ///
+    /// Deleting a key that has no value is not an error.
+    fn delete(&self, lifetime: Lifetime, key: &str) -> Result<()>;
+
+    /// Iterate over all entries whose key starts with the given prefix, in key order.
+    ///
+    /// Entries whose key or value isn't of the expected type are skipped.
+    /// Fails if the underlying storage can't be read, e.g. because it is corrupted.
+    fn iter_prefix(
+        &self,
+        lifetime: Lifetime,
+        prefix: &str,
+        callback: &mut dyn FnMut(&str, &[u8]),
+    ) -> Result<()>;
+
+    /// Delete all entries of the given lifetime.
+    fn clear(&self, lifetime: Lifetime) -> Result<()>;
+}
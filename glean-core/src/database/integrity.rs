@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Integrity checks of the metric data, and recovery from corrupted databases.
+
+use std::fs;
+use std::path::Path;
+
+use super::rkv_backend::RKV_DIRECTORY;
+use super::StorageBackend;
+use crate::metrics::Metric;
+use crate::Lifetime;
+use crate::Result;
+
+/// The name of the directory, in the data path, a corrupted rkv database is moved to.
+const QUARANTINE_DIRECTORY: &str = "db-quarantine";
+
+/// The keys that are carried over from a quarantined database to the new one, if readable.
+///
+/// These identify the client, all other data can be dropped.
+const RECOVERABLE_KEYS: [&str; 2] = [
+    "glean_client_info#client_id",
+    "glean_client_info#first_run_date",
+];
+
+/// All the lifetimes with persisted data.
+const LIFETIMES: [Lifetime; 4] = [
+    Lifetime::User,
+    Lifetime::Ping,
+    Lifetime::Application,
+    Lifetime::Session,
+];
+
+/// The outcome of the integrity check run when opening the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// The number of entries that couldn't be decoded and were removed.
+    pub undecodable_entries: u32,
+    /// Whether the database was corrupted and moved aside.
+    pub quarantined: bool,
+}
+
+/// Read all entries of all lifetimes, to find out whether the storage is readable.
+///
+/// ## Return value
+///
+/// The error of the first read that failed, if any.
+pub(super) fn check_readable(backend: &dyn StorageBackend) -> Result<()> {
+    for lifetime in LIFETIMES.iter() {
+        backend.iter_prefix(*lifetime, "", &mut |_, _| {})?;
+    }
+    Ok(())
+}
+
+/// Delete all entries that can't be decoded into a metric.
+///
+/// ## Return value
+///
+/// The number of deleted entries.
+pub(super) fn remove_undecodable(backend: &dyn StorageBackend) -> u32 {
+    let mut removed = 0;
+    for lifetime in LIFETIMES.iter() {
+        let mut undecodable = Vec::new();
+        let read = backend.iter_prefix(*lifetime, "", &mut |key, blob| {
+            if bincode::deserialize::<Metric>(blob).is_err() {
+                undecodable.push(key.to_string());
+            }
+        });
+        if let Err(e) = read {
+            log::error!("Failed to check the {:?} store. {:?}", lifetime, e);
+        }
+
+        for key in undecodable {
+            log::warn!(
+                "Removing undecodable entry {} from the {:?} store",
+                key,
+                lifetime
+            );
+            match backend.delete(*lifetime, &key) {
+                Ok(()) => removed += 1,
+                Err(e) => log::error!("Failed to remove entry {}. {:?}", key, e),
+            }
+        }
+    }
+    removed
+}
+
+/// Read the entries identifying the client from a storage.
+///
+/// Entries that can't be read or decoded are left out.
+pub(super) fn recover_client_info(backend: &dyn StorageBackend) -> Vec<(String, Vec<u8>)> {
+    RECOVERABLE_KEYS
+        .iter()
+        .filter_map(|key| match backend.get(Lifetime::User, key) {
+            Ok(Some(blob)) if bincode::deserialize::<Metric>(&blob).is_ok() => {
+                Some((key.to_string(), blob))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Move the rkv database out of the way, replacing any previously quarantined one.
+///
+/// The quarantined copy is kept for inspection only, it is never read again.
+pub(super) fn quarantine(data_path: &str) -> Result<()> {
+    let data_path = Path::new(data_path);
+    let quarantine_path = data_path.join(QUARANTINE_DIRECTORY);
+    if quarantine_path.exists() {
+        fs::remove_dir_all(&quarantine_path)?;
+    }
+    fs::rename(data_path.join(RKV_DIRECTORY), &quarantine_path)?;
+    log::warn!(
+        "Moved the corrupted database to {}",
+        quarantine_path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A storage backend keeping all data in memory.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use super::StorageBackend;
+use crate::Lifetime;
+use crate::Result;
+
+/// A storage backend keeping all data in memory.
+///
+/// Nothing is persisted: all data is lost when the backend is dropped.
+/// This is meant for tests, short-lived tools
+/// and processes that are not allowed to memory map files.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    /// The stores, keyed by the name of their lifetime.
+    stores: RwLock<HashMap<&'static str, BTreeMap<String, Vec<u8>>>>,
+}
+
+impl MemoryBackend {
+    /// Create a new, empty, in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn get(&self, lifetime: Lifetime, key: &str) -> Result<Option<Vec<u8>>> {
+        let stores = self.stores.read().expect("Can't read the in-memory stores");
+        Ok(stores
+            .get(lifetime.as_str())
+            .and_then(|store| store.get(key))
+            .cloned())
+    }
+
+    fn put(&self, lifetime: Lifetime, key: &str, value: &[u8]) -> Result<()> {
+        let mut stores = self
+            .stores
+            .write()
+            .expect("Can't write to the in-memory stores");
+        stores
+            .entry(lifetime.as_str())
+            .or_default()
+            .insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        lifetime: Lifetime,
+        key: &str,
+        transform: &mut dyn FnMut(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<()> {
+        let mut stores = self
+            .stores
+            .write()
+            .expect("Can't write to the in-memory stores");
+        let store = stores.entry(lifetime.as_str()).or_default();
+        let new_value = transform(store.get(key).map(|value| value.as_slice()));
+        store.insert(key.to_string(), new_value);
+        Ok(())
+    }
+
+    fn delete(&self, lifetime: Lifetime, key: &str) -> Result<()> {
+        let mut stores = self
+            .stores
+            .write()
+            .expect("Can't write to the in-memory stores");
+        if let Some(store) = stores.get_mut(lifetime.as_str()) {
+            store.remove(key);
+        }
+        Ok(())
+    }
+
+    fn iter_prefix(
+        &self,
+        lifetime: Lifetime,
+        prefix: &str,
+        callback: &mut dyn FnMut(&str, &[u8]),
+    ) -> Result<()> {
+        let stores = self.stores.read().expect("Can't read the in-memory stores");
+        if let Some(store) = stores.get(lifetime.as_str()) {
+            for (key, value) in store.range(prefix.to_string()..) {
+                if !key.starts_with(prefix) {
+                    break;
+                }
+                callback(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&self, lifetime: Lifetime) -> Result<()> {
+        let mut stores = self
+            .stores
+            .write()
+            .expect("Can't write to the in-memory stores");
+        stores.remove(lifetime.as_str());
+        Ok(())
+    }
+}
@@ -4,46 +4,61 @@
 
 use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
-use std::fs;
-use std::str;
 use std::sync::RwLock;
 
-use rkv::{Rkv, SingleStore, StoreOptions};
-
 use crate::metrics::Metric;
 use crate::CommonMetricData;
 use crate::Glean;
 use crate::Lifetime;
 use crate::Result;
 
-pub struct Database {
-    /// Handle to the database environment.
-    rkv: Rkv,
-
-    /// Handles to the "lifetime" stores.
+mod backend;
+mod integrity;
+mod memory;
+mod rkv_backend;
+mod safe_mode;
+
+pub use backend::StorageBackend;
+pub use integrity::IntegrityReport;
+pub use memory::MemoryBackend;
+pub use rkv_backend::RkvBackend;
+pub use safe_mode::SafeModeBackend;
+
+/// The storage backend the metric data is persisted to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatabaseBackend {
+    /// Persist data in an rkv (LMDB) environment in the data path. This is the default.
+    Rkv,
+    /// Keep all data in memory, nothing is persisted.
+    ///
+    /// This is meant for tests, short-lived tools
+    /// and processes that are not allowed to memory map files.
+    InMemory,
+    /// Persist data in plain files in the data path, without memory-mapped files.
     ///
-    /// A "store" is a handle to the underlying database.
-    /// We keep them open for fast and frequent access.
-    user_store: SingleStore,
-    ping_store: SingleStore,
-    application_store: SingleStore,
+    /// This is slower than rkv, but works on file systems where rkv can't be opened.
+    /// It is also used automatically if opening rkv fails.
+    SafeMode,
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self {
+        DatabaseBackend::Rkv
+    }
+}
+
+#[derive(Debug)]
+pub struct Database {
+    /// The storage the metric data is persisted to.
+    backend: Box<dyn StorageBackend>,
 
     /// If the `delay_ping_lifetime_io` Glean config option is `true`,
     /// we will save metrics with 'ping' lifetime data in a map temporarily
-    /// so as to persist them to disk using rkv in bulk on demand.
+    /// so as to persist them to the backend in bulk on demand.
     ping_lifetime_data: Option<RwLock<BTreeMap<String, Metric>>>,
-}
 
-impl std::fmt::Debug for Database {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        fmt.debug_struct("Database")
-            .field("rkv", &self.rkv)
-            .field("user_store", &"SingleStore")
-            .field("ping_store", &"SingleStore")
-            .field("application_store", &"SingleStore")
-            .field("ping_lifetime_data", &self.ping_lifetime_data)
-            .finish()
-    }
+    /// What the integrity check found when the database was opened.
+    integrity: IntegrityReport,
 }
 
 impl Database {
@@ -51,51 +66,108 @@ impl Database {
     ///
     /// This opens the underlying rkv store and creates
     /// the underlying directory structure.
+    /// If the rkv store is corrupted, it is quarantined and replaced by a new one,
+    /// which keeps the client id and the first run date if they can be read.
     ///
     /// It also loads any Lifetime::Ping data that might be
     /// persisted, in case `delay_ping_lifetime_io` is set.
     pub fn new(data_path: &str, delay_ping_lifetime_io: bool) -> Result<Self> {
-        let rkv = Self::open_rkv(data_path)?;
-        let user_store = rkv.open_single(Lifetime::User.as_str(), StoreOptions::create())?;
-        let ping_store = rkv.open_single(Lifetime::Ping.as_str(), StoreOptions::create())?;
-        let application_store =
-            rkv.open_single(Lifetime::Application.as_str(), StoreOptions::create())?;
+        let (backend, quarantined) = Self::open_rkv(data_path)?;
+        let mut db = Self::with_backend(Box::new(backend), delay_ping_lifetime_io);
+        db.integrity.quarantined = quarantined;
+        Ok(db)
+    }
+
+    /// Initialize the data store with the requested kind of storage backend.
+    ///
+    /// If the rkv backend is requested but fails to open,
+    /// the safe-mode backend is used instead.
+    ///
+    /// ## Arguments
+    ///
+    /// * `data_path` - the path to store data in.
+    /// * `kind` - the kind of storage backend to use.
+    /// * `delay_ping_lifetime_io` - whether to keep ping-lifetime data in memory.
+    pub fn open(
+        data_path: &str,
+        kind: DatabaseBackend,
+        delay_ping_lifetime_io: bool,
+    ) -> Result<Self> {
+        let backend: Box<dyn StorageBackend> = match kind {
+            DatabaseBackend::Rkv => match Self::new(data_path, delay_ping_lifetime_io) {
+                Ok(db) => return Ok(db),
+                Err(e) => {
+                    log::error!(
+                        "Failed to open the rkv database, falling back to safe mode. {:?}",
+                        e
+                    );
+                    Box::new(SafeModeBackend::new(data_path)?)
+                }
+            },
+            DatabaseBackend::InMemory => Box::new(MemoryBackend::new()),
+            DatabaseBackend::SafeMode => Box::new(SafeModeBackend::new(data_path)?),
+        };
+        Ok(Self::with_backend(backend, delay_ping_lifetime_io))
+    }
+
+    /// Open the rkv backend, quarantining the rkv store if it is corrupted.
+    ///
+    /// ## Return value
+    ///
+    /// The backend and whether a corrupted store was quarantined.
+    fn open_rkv(data_path: &str) -> Result<(RkvBackend, bool)> {
+        let recovered = match RkvBackend::new(data_path) {
+            Ok(backend) => match integrity::check_readable(&backend) {
+                Ok(()) => return Ok((backend, false)),
+                Err(e) if e.is_database_corruption() => {
+                    log::error!("The database is corrupted. {:?}", e);
+                    integrity::recover_client_info(&backend)
+                }
+                Err(e) => return Err(e),
+            },
+            Err(e) if e.is_database_corruption() => {
+                log::error!("Failed to open the corrupted database. {:?}", e);
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        integrity::quarantine(data_path)?;
+        let backend = RkvBackend::new(data_path)?;
+        backend.put_many(Lifetime::User, &recovered)?;
+        Ok((backend, true))
+    }
+
+    /// Initialize the data store on top of the given storage backend.
+    ///
+    /// It also loads any Lifetime::Ping data that might be
+    /// persisted, in case `delay_ping_lifetime_io` is set.
+    pub fn with_backend(backend: Box<dyn StorageBackend>, delay_ping_lifetime_io: bool) -> Self {
         let ping_lifetime_data = if delay_ping_lifetime_io {
             Some(RwLock::new(BTreeMap::new()))
         } else {
             None
         };
 
+        let integrity = IntegrityReport {
+            undecodable_entries: integrity::remove_undecodable(&*backend),
+            quarantined: false,
+        };
+
         let db = Self {
-            rkv,
-            user_store,
-            ping_store,
-            application_store,
+            backend,
             ping_lifetime_data,
+            integrity,
         };
 
         db.load_ping_lifetime_data();
 
-        Ok(db)
+        db
     }
 
-    fn get_store(&self, lifetime: Lifetime) -> &SingleStore {
-        match lifetime {
-            Lifetime::User => &self.user_store,
-            Lifetime::Ping => &self.ping_store,
-            Lifetime::Application => &self.application_store,
-        }
-    }
-
-    /// Creates the storage directories and inits rkv.
-    fn open_rkv(path: &str) -> Result<Rkv> {
-        let path = std::path::Path::new(path).join("db");
-        log::debug!("Database path: {:?}", path.display());
-        fs::create_dir_all(&path)?;
-
-        let rkv = Rkv::new(&path)?;
-        log::info!("Database initialized");
-        Ok(rkv)
+    /// What the integrity check found when the database was opened.
+    pub fn integrity_report(&self) -> IntegrityReport {
+        self.integrity
     }
 
     /// Build the key of the final location of the data in the database.
@@ -118,7 +190,7 @@ impl Database {
         }
     }
 
-    /// Loads Lifetime::Ping data from rkv to memory,
+    /// Loads Lifetime::Ping data from the backend to memory,
     /// if `delay_ping_lifetime_io` is set to true.
     ///
     /// Does nothing if it isn't or if there is not data to load.
@@ -128,22 +200,12 @@ impl Database {
                 .write()
                 .expect("Can't read ping lifetime data");
 
-            let reader = unwrap_or!(self.rkv.read(), return);
-            let store = self.get_store(Lifetime::Ping);
-            let mut iter = unwrap_or!(store.iter_start(&reader), return);
-
-            while let Some(Ok((metric_id, value))) = iter.next() {
-                let metric_id = match str::from_utf8(metric_id) {
-                    Ok(metric_id) => metric_id.to_string(),
-                    _ => continue,
-                };
-                let metric: Metric = match value.expect("Value missing in iteration") {
-                    rkv::Value::Blob(blob) => unwrap_or!(bincode::deserialize(blob), continue),
-                    _ => continue,
-                };
-
-                data.insert(metric_id, metric);
-            }
+            let _ = self
+                .backend
+                .iter_prefix(Lifetime::Ping, "", &mut |metric_id, blob| {
+                    let metric: Metric = unwrap_or!(bincode::deserialize(blob), return);
+                    data.insert(metric_id.to_string(), metric);
+                });
         }
     }
 
@@ -198,24 +260,13 @@ impl Database {
             }
         }
 
-        let reader = unwrap_or!(self.rkv.read(), return);
-        let mut iter = unwrap_or!(
-            self.get_store(lifetime).iter_from(&reader, &iter_start),
-            return
-        );
-
-        while let Some(Ok((metric_id, value))) = iter.next() {
-            if !metric_id.starts_with(iter_start.as_bytes()) {
-                break;
-            }
-
-            let metric_id = &metric_id[len..];
-            let metric: Metric = match value.expect("Value missing in iteration") {
-                rkv::Value::Blob(blob) => unwrap_or!(bincode::deserialize(blob), continue),
-                _ => continue,
-            };
-            transaction_fn(metric_id, &metric);
-        }
+        let _ = self
+            .backend
+            .iter_prefix(lifetime, &iter_start, &mut |metric_id, blob| {
+                let metric_id = &metric_id[len..];
+                let metric: Metric = unwrap_or!(bincode::deserialize(blob), return);
+                transaction_fn(metric_id.as_bytes(), &metric);
+            });
     }
 
     /// Determine if the storage has the given metric.
@@ -250,27 +301,7 @@ impl Database {
             }
         }
 
-        let reader = unwrap_or!(self.rkv.read(), return false);
-        self.get_store(lifetime)
-            .get(&reader, &key)
-            .unwrap_or(None)
-            .is_some()
-    }
-
-    /// Write to the specified storage with the provided transaction function.
-    ///
-    /// If the storage is unavailable, it will return an error.
-    ///
-    /// ## Panics
-    ///
-    /// * This function will **not** panic on database errors.
-    pub fn write_with_store<F>(&self, store_name: Lifetime, mut transaction_fn: F) -> Result<()>
-    where
-        F: FnMut(rkv::Writer, &SingleStore) -> Result<()>,
-    {
-        let writer = self.rkv.write().unwrap();
-        let store = self.get_store(store_name);
-        transaction_fn(writer, store)
+        self.backend.get(lifetime, &key).unwrap_or(None).is_some()
     }
 
     /// Records a metric in the underlying storage system.
@@ -317,13 +348,7 @@ impl Database {
         }
 
         let encoded = bincode::serialize(&metric).expect("IMPOSSIBLE: Serializing metric failed");
-        let value = rkv::Value::Blob(&encoded);
-
-        let mut writer = self.rkv.write()?;
-        self.get_store(lifetime)
-            .put(&mut writer, final_key, &value)?;
-        writer.commit()?;
-        Ok(())
+        self.backend.put(lifetime, &final_key, &encoded)
     }
 
     /// Records the provided value, with the given lifetime, after
@@ -387,26 +412,11 @@ impl Database {
             }
         }
 
-        let mut writer = self.rkv.write()?;
-        let store = self.get_store(lifetime);
-        let new_value: Metric = {
-            let old_value = store.get(&writer, &final_key)?;
-
-            match old_value {
-                Some(rkv::Value::Blob(blob)) => {
-                    let old_value = bincode::deserialize(blob).ok();
-                    transform(old_value)
-                }
-                _ => transform(None),
-            }
-        };
-
-        let encoded =
-            bincode::serialize(&new_value).expect("IMPOSSIBLE: Serializing metric failed");
-        let value = rkv::Value::Blob(&encoded);
-        store.put(&mut writer, final_key, &value)?;
-        writer.commit()?;
-        Ok(())
+        self.backend.update(lifetime, &final_key, &mut |old_value| {
+            let old_value = old_value.and_then(|blob| bincode::deserialize(blob).ok());
+            let new_value = transform(old_value);
+            bincode::serialize(&new_value).expect("IMPOSSIBLE: Serializing metric failed")
+        })
     }
 
     /// Clears a storage (only Ping Lifetime).
@@ -432,31 +442,20 @@ impl Database {
                 .clear();
         }
 
-        self.write_with_store(Lifetime::Ping, |mut writer, store| {
-            let mut metrics = Vec::new();
-            {
-                let mut iter = store.iter_from(&writer, &storage_name)?;
-                while let Some(Ok((metric_id, _))) = iter.next() {
-                    if let Ok(metric_id) = std::str::from_utf8(metric_id) {
-                        if !metric_id.starts_with(&storage_name) {
-                            break;
-                        }
-                        metrics.push(metric_id.to_owned());
-                    }
-                }
-            }
+        let mut metrics = Vec::new();
+        self.backend
+            .iter_prefix(Lifetime::Ping, storage_name, &mut |metric_id, _| {
+                metrics.push(metric_id.to_owned());
+            })?;
 
-            let mut res = Ok(());
-            for to_delete in metrics {
-                if let Err(e) = store.delete(&mut writer, to_delete) {
-                    log::error!("Can't delete from store: {:?}", e);
-                    res = Err(e);
-                }
+        let mut res = Ok(());
+        for to_delete in metrics {
+            if let Err(e) = self.backend.delete(Lifetime::Ping, &to_delete) {
+                log::error!("Can't delete from store: {:?}", e);
+                res = Err(e);
             }
-
-            writer.commit()?;
-            Ok(res?)
-        })
+        }
+        res
     }
 
     /// Removes a single metric from the storage.
@@ -496,18 +495,7 @@ impl Database {
             }
         }
 
-        self.write_with_store(lifetime, |mut writer, store| {
-            if let Err(e) = store.delete(&mut writer, final_key.clone()) {
-                if self.ping_lifetime_data.is_some() {
-                    // If ping_lifetime_data exists, it might be
-                    // that data is in memory, but not yet in rkv.
-                    return Ok(());
-                }
-                return Err(e.into());
-            }
-            writer.commit()?;
-            Ok(())
-        })
+        self.backend.delete(lifetime, &final_key)
     }
 
     /// Clears all the metrics in the database, for the provided lifetime.
@@ -518,12 +506,7 @@ impl Database {
     ///
     /// * This function will **not** panic on database errors.
     pub fn clear_lifetime(&self, lifetime: Lifetime) {
-        let res = self.write_with_store(lifetime, |mut writer, store| {
-            store.clear(&mut writer)?;
-            writer.commit()?;
-            Ok(())
-        });
-        if let Err(e) = res {
+        if let Err(e) = self.backend.clear(lifetime) {
             log::error!("Could not clear store for lifetime {:?}: {:?}", lifetime, e);
         }
     }
@@ -543,12 +526,19 @@ impl Database {
                 .clear();
         }
 
-        for lifetime in [Lifetime::User, Lifetime::Ping, Lifetime::Application].iter() {
+        for lifetime in [
+            Lifetime::User,
+            Lifetime::Ping,
+            Lifetime::Application,
+            Lifetime::Session,
+        ]
+        .iter()
+        {
             self.clear_lifetime(*lifetime);
         }
     }
 
-    /// Persist ping_lifetime_data to disk.
+    /// Persist ping_lifetime_data to the backend.
     ///
     /// Does nothing in case there is nothing to persist.
     ///
@@ -561,18 +551,18 @@ impl Database {
                 .read()
                 .expect("Can't read ping lifetime data");
 
-            self.write_with_store(Lifetime::Ping, |mut writer, store| {
-                for (key, value) in data.iter() {
+            // There is no need for `get_storage_key` here because
+            // the key is already formatted from when it was saved
+            // to ping_lifetime_data.
+            let entries: Vec<_> = data
+                .iter()
+                .map(|(key, value)| {
                     let encoded =
                         bincode::serialize(&value).expect("IMPOSSIBLE: Serializing metric failed");
-                    // There is no need for `get_storage_key` here because
-                    // the key is already formatted from when it was saved
-                    // to ping_lifetime_data.
-                    store.put(&mut writer, &key, &rkv::Value::Blob(&encoded))?;
-                }
-                writer.commit()?;
-                Ok(())
-            })?;
+                    (key.clone(), encoded)
+                })
+                .collect();
+            self.backend.put_many(Lifetime::Ping, &entries)?;
         }
         Ok(())
     }
@@ -796,7 +786,12 @@ mod test {
         let metric_id_pattern = "telemetry_test.single_metric";
 
         // Write sample metrics to the database.
-        let lifetimes = vec![Lifetime::User, Lifetime::Ping, Lifetime::Application];
+        let lifetimes = vec![
+            Lifetime::User,
+            Lifetime::Ping,
+            Lifetime::Application,
+            Lifetime::Session,
+        ];
 
         for lifetime in lifetimes.iter() {
             for value in &["retain", "delete"] {
@@ -881,20 +876,23 @@ mod test {
             // At this stage we expect `test_value1` to be persisted and in memory,
             // since it was recorded before calling `persist_ping_lifetime_data`,
             // and `test_value2` to be only in memory, since it was recorded after.
-            let store: SingleStore = db
-                .rkv
-                .open_single(Lifetime::Ping.as_str(), StoreOptions::create())
-                .unwrap();
-            let reader = db.rkv.read().unwrap();
 
-            // Verify that test_value1 is in rkv.
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id1))
+            // Verify that test_value1 is in the backend.
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id1)
+                )
                 .unwrap_or(None)
                 .is_some());
-            // Verifiy that test_value2 is **not** in rkv.
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id2))
+            // Verifiy that test_value2 is **not** in the backend.
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id2)
+                )
                 .unwrap_or(None)
                 .is_none());
 
@@ -920,20 +918,23 @@ mod test {
             // At this stage we expect `test_value1` and `test_value2` to
             // be persisted, since both were created before a call to
             // `persist_ping_lifetime_data`.
-            let store: SingleStore = db
-                .rkv
-                .open_single(Lifetime::Ping.as_str(), StoreOptions::create())
-                .unwrap();
-            let reader = db.rkv.read().unwrap();
 
-            // Verify that test_value1 is in rkv.
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id1))
+            // Verify that test_value1 is in the backend.
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id1)
+                )
                 .unwrap_or(None)
                 .is_some());
-            // Verifiy that test_value2 is also in rkv.
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id2))
+            // Verifiy that test_value2 is also in the backend.
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id2)
+                )
                 .unwrap_or(None)
                 .is_some());
 
@@ -989,13 +990,12 @@ mod test {
             db.persist_ping_lifetime_data().unwrap();
 
             // Verify that test_value is now in rkv.
-            let store: SingleStore = db
-                .rkv
-                .open_single(Lifetime::Ping.as_str(), StoreOptions::create())
-                .unwrap();
-            let reader = db.rkv.read().unwrap();
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id))
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id)
+                )
                 .unwrap_or(None)
                 .is_some());
         }
@@ -1016,15 +1016,331 @@ mod test {
                 .is_some());
 
             // Verify that test_value is also in rkv.
-            let store: SingleStore = db
-                .rkv
-                .open_single(Lifetime::Ping.as_str(), StoreOptions::create())
-                .unwrap();
-            let reader = db.rkv.read().unwrap();
-            assert!(store
-                .get(&reader, format!("{}#{}", test_storage, test_metric_id))
+            assert!(db
+                .backend
+                .get(
+                    Lifetime::Ping,
+                    &format!("{}#{}", test_storage, test_metric_id)
+                )
                 .unwrap_or(None)
                 .is_some());
         }
     }
+
+    /// Exercise the `StorageBackend` interface on the given backend.
+    fn check_storage_backend(backend: &dyn StorageBackend) {
+        backend.put(Lifetime::Ping, "store1#a", b"a").unwrap();
+        backend.put(Lifetime::Ping, "store1#b", b"b").unwrap();
+        backend.put(Lifetime::Ping, "store2#c", b"c").unwrap();
+        backend.put(Lifetime::User, "store1#a", b"user").unwrap();
+        backend
+            .put(Lifetime::Session, "store1#a", b"session")
+            .unwrap();
+
+        assert_eq!(
+            Some(b"a".to_vec()),
+            backend.get(Lifetime::Ping, "store1#a").unwrap()
+        );
+        assert_eq!(
+            Some(b"user".to_vec()),
+            backend.get(Lifetime::User, "store1#a").unwrap()
+        );
+        assert_eq!(
+            Some(b"session".to_vec()),
+            backend.get(Lifetime::Session, "store1#a").unwrap()
+        );
+        assert_eq!(
+            None,
+            backend.get(Lifetime::Application, "store1#a").unwrap()
+        );
+
+        // Iterating is limited to the prefix and the lifetime, in key order.
+        let mut found = Vec::new();
+        backend
+            .iter_prefix(Lifetime::Ping, "store1#", &mut |key, value| {
+                found.push((key.to_string(), value.to_vec()))
+            })
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("store1#a".to_string(), b"a".to_vec()),
+                ("store1#b".to_string(), b"b".to_vec())
+            ],
+            found
+        );
+
+        let mut count = 0;
+        backend
+            .iter_prefix(Lifetime::Ping, "", &mut |_, _| count += 1)
+            .unwrap();
+        assert_eq!(3, count);
+
+        // Updating sees the previous value, if any.
+        backend
+            .update(Lifetime::Ping, "store1#a", &mut |old| {
+                assert_eq!(Some(&b"a"[..]), old);
+                b"updated".to_vec()
+            })
+            .unwrap();
+        backend
+            .update(Lifetime::Ping, "store1#new", &mut |old| {
+                assert_eq!(None, old);
+                b"new".to_vec()
+            })
+            .unwrap();
+        assert_eq!(
+            Some(b"updated".to_vec()),
+            backend.get(Lifetime::Ping, "store1#a").unwrap()
+        );
+        assert_eq!(
+            Some(b"new".to_vec()),
+            backend.get(Lifetime::Ping, "store1#new").unwrap()
+        );
+
+        backend
+            .put_many(
+                Lifetime::Application,
+                &[
+                    ("store1#x".to_string(), b"x".to_vec()),
+                    ("store1#y".to_string(), b"y".to_vec()),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            Some(b"y".to_vec()),
+            backend.get(Lifetime::Application, "store1#y").unwrap()
+        );
+
+        // Deleting works for existing and missing keys.
+        backend.delete(Lifetime::Ping, "store1#b").unwrap();
+        backend.delete(Lifetime::Ping, "store1#missing").unwrap();
+        assert_eq!(None, backend.get(Lifetime::Ping, "store1#b").unwrap());
+
+        // Clearing only affects a single lifetime.
+        backend.clear(Lifetime::Ping).unwrap();
+        assert_eq!(None, backend.get(Lifetime::Ping, "store2#c").unwrap());
+        assert_eq!(
+            Some(b"user".to_vec()),
+            backend.get(Lifetime::User, "store1#a").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rkv_backend() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+        check_storage_backend(&RkvBackend::new(&str_dir).unwrap());
+    }
+
+    #[test]
+    fn test_safe_mode_backend() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+        check_storage_backend(&SafeModeBackend::new(&str_dir).unwrap());
+    }
+
+    #[test]
+    fn test_safe_mode_backend_persists_data() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+
+        {
+            let db = Database::open(&str_dir, DatabaseBackend::SafeMode, false).unwrap();
+            db.record_per_lifetime(
+                Lifetime::User,
+                "store1",
+                "telemetry_test.string",
+                &Metric::String("test-value".to_string()),
+            )
+            .unwrap();
+        }
+
+        let db = Database::open(&str_dir, DatabaseBackend::SafeMode, false).unwrap();
+        assert!(db.has_metric(Lifetime::User, "store1", "telemetry_test.string"));
+        assert!(dir.path().join("safe-db").exists());
+        assert!(!dir.path().join("db").exists());
+    }
+
+    #[test]
+    fn test_falls_back_to_safe_mode_if_rkv_fails() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+        // A file where the rkv directory should be makes opening rkv fail.
+        std::fs::write(dir.path().join("db"), b"not a directory").unwrap();
+
+        let db = Database::open(&str_dir, DatabaseBackend::Rkv, false).unwrap();
+        db.record_per_lifetime(
+            Lifetime::User,
+            "store1",
+            "telemetry_test.string",
+            &Metric::String("test-value".to_string()),
+        )
+        .unwrap();
+
+        assert!(db.has_metric(Lifetime::User, "store1", "telemetry_test.string"));
+        assert!(dir.path().join("safe-db").join("user.kv").exists());
+    }
+
+    #[test]
+    fn test_undecodable_entries_are_removed() {
+        let backend = MemoryBackend::new();
+        let valid = bincode::serialize(&Metric::Counter(1)).unwrap();
+        backend.put(Lifetime::User, "store1#valid", &valid).unwrap();
+        backend
+            .put(Lifetime::User, "store1#invalid", b"\xff")
+            .unwrap();
+        backend.put(Lifetime::Ping, "store1#invalid", b"").unwrap();
+
+        let db = Database::with_backend(Box::new(backend), true);
+        assert_eq!(
+            IntegrityReport {
+                undecodable_entries: 2,
+                quarantined: false,
+            },
+            db.integrity_report()
+        );
+        assert!(db.has_metric(Lifetime::User, "store1", "valid"));
+        assert!(!db.has_metric(Lifetime::User, "store1", "invalid"));
+        assert!(!db.has_metric(Lifetime::Ping, "store1", "invalid"));
+    }
+
+    #[test]
+    fn test_corrupted_database_is_quarantined() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+        let db_path = dir.path().join("db");
+        std::fs::create_dir_all(&db_path).unwrap();
+        std::fs::write(db_path.join("data.mdb"), vec![0xab; 8192]).unwrap();
+
+        let db = Database::new(&str_dir, false).unwrap();
+        assert!(db.integrity_report().quarantined);
+        assert!(dir.path().join("db-quarantine").join("data.mdb").exists());
+
+        // The new database works.
+        db.record_per_lifetime(
+            Lifetime::User,
+            "store1",
+            "telemetry_test.string",
+            &Metric::String("test-value".to_string()),
+        )
+        .unwrap();
+        assert!(db.has_metric(Lifetime::User, "store1", "telemetry_test.string"));
+        drop(db);
+
+        // It isn't quarantined again on the next start.
+        let db = Database::new(&str_dir, false).unwrap();
+        assert!(!db.integrity_report().quarantined);
+        assert!(db.has_metric(Lifetime::User, "store1", "telemetry_test.string"));
+    }
+
+    #[test]
+    fn test_database_is_kept_on_errors_other_than_corruption() {
+        let dir = tempdir().unwrap();
+        let str_dir = dir.path().display().to_string();
+        let db = Database::new(&str_dir, false).unwrap();
+        db.record_per_lifetime(
+            Lifetime::User,
+            "store1",
+            "telemetry_test.string",
+            &Metric::String("test-value".to_string()),
+        )
+        .unwrap();
+        drop(db);
+
+        // A lock file that can't be opened makes LMDB fail with an OS error.
+        let lock_path = dir.path().join("db").join("lock.mdb");
+        std::fs::remove_file(&lock_path).unwrap();
+        std::fs::create_dir(&lock_path).unwrap();
+
+        assert!(Database::new(&str_dir, false).is_err());
+        assert!(!dir.path().join("db-quarantine").exists());
+
+        // The data is still there once the database can be opened again.
+        std::fs::remove_dir(&lock_path).unwrap();
+        let db = Database::new(&str_dir, false).unwrap();
+        assert!(!db.integrity_report().quarantined);
+        assert!(db.has_metric(Lifetime::User, "store1", "telemetry_test.string"));
+    }
+
+    #[test]
+    fn test_client_info_is_recovered() {
+        let backend = MemoryBackend::new();
+        let client_id = bincode::serialize(&Metric::String("client".to_string())).unwrap();
+        backend
+            .put(Lifetime::User, "glean_client_info#client_id", &client_id)
+            .unwrap();
+        backend
+            .put(Lifetime::User, "glean_client_info#first_run_date", b"\xff")
+            .unwrap();
+        backend
+            .put(Lifetime::User, "store1#other", &client_id)
+            .unwrap();
+
+        assert_eq!(
+            vec![("glean_client_info#client_id".to_string(), client_id)],
+            integrity::recover_client_info(&backend)
+        );
+    }
+
+    #[test]
+    fn test_memory_backend() {
+        check_storage_backend(&MemoryBackend::new());
+    }
+
+    #[test]
+    fn test_database_with_memory_backend() {
+        let db = Database::with_backend(Box::new(MemoryBackend::new()), false);
+
+        let test_storage = "test-storage";
+        db.record_per_lifetime(
+            Lifetime::User,
+            test_storage,
+            "telemetry_test.string",
+            &Metric::String("test-value".to_string()),
+        )
+        .unwrap();
+        db.record_per_lifetime_with(
+            Lifetime::Ping,
+            test_storage,
+            "telemetry_test.counter",
+            |old| match old {
+                Some(Metric::Counter(old)) => Metric::Counter(old + 1),
+                _ => Metric::Counter(1),
+            },
+        )
+        .unwrap();
+        db.record_per_lifetime_with(
+            Lifetime::Ping,
+            test_storage,
+            "telemetry_test.counter",
+            |old| match old {
+                Some(Metric::Counter(old)) => Metric::Counter(old + 1),
+                _ => Metric::Counter(1),
+            },
+        )
+        .unwrap();
+
+        let mut found = HashMap::new();
+        for lifetime in &[Lifetime::User, Lifetime::Ping] {
+            db.iter_store_from(*lifetime, test_storage, None, |metric_id, metric| {
+                found.insert(
+                    String::from_utf8_lossy(metric_id).into_owned(),
+                    metric.clone(),
+                );
+            });
+        }
+        assert_eq!(2, found.len());
+        assert_eq!(
+            Some(&Metric::String("test-value".to_string())),
+            found.get("telemetry_test.string")
+        );
+        assert_eq!(
+            Some(&Metric::Counter(2)),
+            found.get("telemetry_test.counter")
+        );
+
+        db.clear_ping_lifetime_storage(test_storage).unwrap();
+        assert!(!db.has_metric(Lifetime::Ping, test_storage, "telemetry_test.counter"));
+        assert!(db.has_metric(Lifetime::User, test_storage, "telemetry_test.string"));
+    }
 }
@@ -0,0 +1,183 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A storage backend persisting data in an rkv (LMDB) environment.
+
+use std::fs;
+use std::path::Path;
+use std::str;
+
+use rkv::{Rkv, SingleStore, StoreOptions};
+
+use super::StorageBackend;
+use crate::Lifetime;
+use crate::Result;
+
+/// The name of the directory, in the data path, the rkv environment is stored in.
+pub(super) const RKV_DIRECTORY: &str = "db";
+
+/// A storage backend persisting data in an rkv environment,
+/// with one `SingleStore` per lifetime.
+pub struct RkvBackend {
+    /// Handle to the database environment.
+    rkv: Rkv,
+
+    /// Handles to the "lifetime" stores.
+    ///
+    /// A "store" is a handle to the underlying database.
+    /// We keep them open for fast and frequent access.
+    user_store: SingleStore,
+    ping_store: SingleStore,
+    application_store: SingleStore,
+    session_store: SingleStore,
+}
+
+impl std::fmt::Debug for RkvBackend {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("RkvBackend")
+            .field("rkv", &self.rkv)
+            .field("user_store", &"SingleStore")
+            .field("ping_store", &"SingleStore")
+            .field("application_store", &"SingleStore")
+            .field("session_store", &"SingleStore")
+            .finish()
+    }
+}
+
+impl RkvBackend {
+    /// Open the rkv environment in the `db` directory of the data path.
+    ///
+    /// This creates the underlying directory structure and the stores if necessary.
+    pub fn new(data_path: &str) -> Result<Self> {
+        let rkv = Self::open_rkv(data_path)?;
+        let user_store = rkv.open_single(Lifetime::User.as_str(), StoreOptions::create())?;
+        let ping_store = rkv.open_single(Lifetime::Ping.as_str(), StoreOptions::create())?;
+        let application_store =
+            rkv.open_single(Lifetime::Application.as_str(), StoreOptions::create())?;
+        let session_store = rkv.open_single(Lifetime::Session.as_str(), StoreOptions::create())?;
+
+        Ok(Self {
+            rkv,
+            user_store,
+            ping_store,
+            application_store,
+            session_store,
+        })
+    }
+
+    /// Creates the storage directories and inits rkv.
+    fn open_rkv(path: &str) -> Result<Rkv> {
+        let path = Path::new(path).join(RKV_DIRECTORY);
+        log::debug!("Database path: {:?}", path.display());
+        fs::create_dir_all(&path)?;
+
+        let rkv = Rkv::new(&path)?;
+        log::info!("Database initialized");
+        Ok(rkv)
+    }
+
+    fn get_store(&self, lifetime: Lifetime) -> &SingleStore {
+        match lifetime {
+            Lifetime::User => &self.user_store,
+            Lifetime::Ping => &self.ping_store,
+            Lifetime::Application => &self.application_store,
+            Lifetime::Session => &self.session_store,
+        }
+    }
+}
+
+impl StorageBackend for RkvBackend {
+    fn get(&self, lifetime: Lifetime, key: &str) -> Result<Option<Vec<u8>>> {
+        let reader = self.rkv.read()?;
+        match self.get_store(lifetime).get(&reader, key)? {
+            Some(rkv::Value::Blob(blob)) => Ok(Some(blob.to_vec())),
+            _ => Ok(None),
+        }
+    }
+
+    fn put(&self, lifetime: Lifetime, key: &str, value: &[u8]) -> Result<()> {
+        let mut writer = self.rkv.write()?;
+        self.get_store(lifetime)
+            .put(&mut writer, key, &rkv::Value::Blob(value))?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    fn put_many(&self, lifetime: Lifetime, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        let mut writer = self.rkv.write()?;
+        let store = self.get_store(lifetime);
+        for (key, value) in entries {
+            store.put(&mut writer, key, &rkv::Value::Blob(value))?;
+        }
+        writer.commit()?;
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        lifetime: Lifetime,
+        key: &str,
+        transform: &mut dyn FnMut(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<()> {
+        let mut writer = self.rkv.write()?;
+        let store = self.get_store(lifetime);
+        let new_value = match store.get(&writer, key)? {
+            Some(rkv::Value::Blob(blob)) => transform(Some(blob)),
+            _ => transform(None),
+        };
+        store.put(&mut writer, key, &rkv::Value::Blob(&new_value))?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    fn delete(&self, lifetime: Lifetime, key: &str) -> Result<()> {
+        let mut writer = self.rkv.write()?;
+        let store = self.get_store(lifetime);
+        // rkv fails to delete keys that don't exist.
+        if store.get(&writer, key)?.is_some() {
+            store.delete(&mut writer, key)?;
+        }
+        writer.commit()?;
+        Ok(())
+    }
+
+    fn iter_prefix(
+        &self,
+        lifetime: Lifetime,
+        prefix: &str,
+        callback: &mut dyn FnMut(&str, &[u8]),
+    ) -> Result<()> {
+        let reader = self.rkv.read()?;
+        let store = self.get_store(lifetime);
+        // LMDB doesn't allow positioning a cursor at an empty key.
+        let iter = if prefix.is_empty() {
+            store.iter_start(&reader)?
+        } else {
+            store.iter_from(&reader, prefix)?
+        };
+
+        for entry in iter {
+            let (key, value) = entry?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+
+            let key = match str::from_utf8(key) {
+                Ok(key) => key,
+                _ => continue,
+            };
+            if let Some(rkv::Value::Blob(blob)) = value {
+                callback(key, blob);
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&self, lifetime: Lifetime) -> Result<()> {
+        let mut writer = self.rkv.write()?;
+        self.get_store(lifetime).clear(&mut writer)?;
+        writer.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A storage backend persisting data in plain files, without memory-mapped files.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::StorageBackend;
+use crate::Lifetime;
+use crate::Result;
+
+/// The name of the directory, in the data path, the files are stored in.
+const SAFE_MODE_DIRECTORY: &str = "safe-db";
+
+/// The data of a single lifetime.
+type Store = BTreeMap<String, Vec<u8>>;
+
+/// A storage backend persisting data in plain files, with one file per lifetime.
+///
+/// The data of all lifetimes is kept in memory.
+/// After every change, the whole file of the changed lifetime is written again,
+/// first to a temporary file which then replaces the previous one.
+///
+/// This is a lot slower than rkv for large amounts of data,
+/// but it works on file systems where LMDB doesn't,
+/// e.g. network file systems that don't support memory-mapped files or lock files.
+#[derive(Debug)]
+pub struct SafeModeBackend {
+    /// Path to the directory the files are stored in.
+    path: PathBuf,
+    /// The stores, in the same order as `LIFETIMES`.
+    stores: RwLock<[Store; 4]>,
+}
+
+/// The lifetimes with a separate store.
+const LIFETIMES: [Lifetime; 4] = [
+    Lifetime::User,
+    Lifetime::Ping,
+    Lifetime::Application,
+    Lifetime::Session,
+];
+
+fn store_index(lifetime: Lifetime) -> usize {
+    match lifetime {
+        Lifetime::User => 0,
+        Lifetime::Ping => 1,
+        Lifetime::Application => 2,
+        Lifetime::Session => 3,
+    }
+}
+
+impl SafeModeBackend {
+    /// Open the files in the `safe-db` directory of the data path and load their data.
+    ///
+    /// This creates the directory if necessary.
+    /// Files that can't be read are treated as empty.
+    pub fn new(data_path: &str) -> Result<Self> {
+        let path = Path::new(data_path).join(SAFE_MODE_DIRECTORY);
+        log::debug!("Safe-mode database path: {:?}", path.display());
+        fs::create_dir_all(&path)?;
+
+        let mut stores: [Store; 4] = Default::default();
+        for lifetime in LIFETIMES.iter() {
+            stores[store_index(*lifetime)] = Self::load(&path, *lifetime);
+        }
+
+        log::info!("Safe-mode database initialized");
+        Ok(Self {
+            path,
+            stores: RwLock::new(stores),
+        })
+    }
+
+    fn file_path(path: &Path, lifetime: Lifetime) -> PathBuf {
+        path.join(format!("{}.kv", lifetime.as_str()))
+    }
+
+    /// Load the data of a single lifetime from its file.
+    fn load(path: &Path, lifetime: Lifetime) -> Store {
+        let file_path = Self::file_path(path, lifetime);
+        let file = match File::open(&file_path) {
+            Ok(file) => file,
+            Err(_) => return Store::new(),
+        };
+
+        match bincode::deserialize_from(BufReader::new(file)) {
+            Ok(store) => store,
+            Err(e) => {
+                log::error!(
+                    "Can't read safe-mode database file {}. Ignoring its data. {:?}",
+                    file_path.display(),
+                    e
+                );
+                Store::new()
+            }
+        }
+    }
+
+    /// Write the data of a single lifetime to its file.
+    fn persist(&self, lifetime: Lifetime, store: &Store) -> Result<()> {
+        let file_path = Self::file_path(&self.path, lifetime);
+        let temp_path = file_path.with_extension("kv.tmp");
+
+        {
+            let mut writer = BufWriter::new(File::create(&temp_path)?);
+            bincode::serialize_into(&mut writer, store)
+                .expect("IMPOSSIBLE: Serializing the store failed");
+            writer.flush()?;
+        }
+        fs::rename(&temp_path, &file_path)?;
+        Ok(())
+    }
+
+    /// Apply a change to the store of a lifetime and persist it.
+    fn modify<F>(&self, lifetime: Lifetime, change: F) -> Result<()>
+    where
+        F: FnOnce(&mut Store),
+    {
+        let mut stores = self
+            .stores
+            .write()
+            .expect("Can't write to the safe-mode stores");
+        let store = &mut stores[store_index(lifetime)];
+        change(store);
+        self.persist(lifetime, store)
+    }
+}
+
+impl StorageBackend for SafeModeBackend {
+    fn get(&self, lifetime: Lifetime, key: &str) -> Result<Option<Vec<u8>>> {
+        let stores = self.stores.read().expect("Can't read the safe-mode stores");
+        Ok(stores[store_index(lifetime)].get(key).cloned())
+    }
+
+    fn put(&self, lifetime: Lifetime, key: &str, value: &[u8]) -> Result<()> {
+        self.modify(lifetime, |store| {
+            store.insert(key.to_string(), value.to_vec());
+        })
+    }
+
+    fn put_many(&self, lifetime: Lifetime, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        self.modify(lifetime, |store| {
+            for (key, value) in entries {
+                store.insert(key.clone(), value.clone());
+            }
+        })
+    }
+
+    fn update(
+        &self,
+        lifetime: Lifetime,
+        key: &str,
+        transform: &mut dyn FnMut(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<()> {
+        self.modify(lifetime, |store| {
+            let new_value = transform(store.get(key).map(|value| value.as_slice()));
+            store.insert(key.to_string(), new_value);
+        })
+    }
+
+    fn delete(&self, lifetime: Lifetime, key: &str) -> Result<()> {
+        self.modify(lifetime, |store| {
+            store.remove(key);
+        })
+    }
+
+    fn iter_prefix(
+        &self,
+        lifetime: Lifetime,
+        prefix: &str,
+        callback: &mut dyn FnMut(&str, &[u8]),
+    ) -> Result<()> {
+        let stores = self.stores.read().expect("Can't read the safe-mode stores");
+        for (key, value) in stores[store_index(lifetime)].range(prefix.to_string()..) {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            callback(key, value);
+        }
+        Ok(())
+    }
+
+    fn clear(&self, lifetime: Lifetime) -> Result<()> {
+        self.modify(lifetime, |store| store.clear())
+    }
+}
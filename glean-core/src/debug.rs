@@ -0,0 +1,118 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Debugging options for pings.
+//!
+//! * The debug view tag is sent in the `X-Debug-ID` header,
+//!   routing pings to the [Debug View](https://debug-ping-preview.firebaseapp.com/).
+//! * The source tags are sent in the `X-Source-Tags` header,
+//!   tagging pings for analysis.
+//! * When logging pings, the contents of every submitted ping are logged.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The maximum number of source tags.
+const MAX_SOURCE_TAGS: usize = 5;
+
+/// The prefix reserved for source tags set by Glean itself.
+const RESERVED_SOURCE_TAG_PREFIX: &str = "glean";
+
+/// The pattern the server accepts for the debug view tag and the source tags.
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new("^[a-zA-Z0-9-]{1,20}$").unwrap());
+
+/// The debugging options applied to pings when they are submitted.
+#[derive(Debug, Default)]
+pub(crate) struct DebugOptions {
+    /// The tag for the `X-Debug-ID` header, if any.
+    pub debug_view_tag: Option<String>,
+    /// The tags for the `X-Source-Tags` header, if any.
+    pub source_tags: Option<Vec<String>>,
+    /// Whether to log the contents of submitted pings.
+    pub log_pings: bool,
+}
+
+/// Validates a debug view tag.
+///
+/// The tag must be 1 to 20 characters long and only contain
+/// alphanumeric characters and hyphens.
+pub(crate) fn validate_debug_view_tag(value: &str) -> bool {
+    if !TAG_REGEX.is_match(value) {
+        log::error!(
+            "Debug view tag '{}' is invalid. It must match {}",
+            value,
+            TAG_REGEX.as_str()
+        );
+        return false;
+    }
+    true
+}
+
+/// Validates a list of source tags.
+///
+/// There must be 1 to 5 tags, each following the same rules as the debug view tag.
+/// Tags starting with `glean` are reserved and can't be set.
+pub(crate) fn validate_source_tags(tags: &[String]) -> bool {
+    if tags.is_empty() || tags.len() > MAX_SOURCE_TAGS {
+        log::error!(
+            "Expected 1 to {} source tags, got {}",
+            MAX_SOURCE_TAGS,
+            tags.len()
+        );
+        return false;
+    }
+
+    for tag in tags {
+        if !TAG_REGEX.is_match(tag) {
+            log::error!(
+                "Source tag '{}' is invalid. It must match {}",
+                tag,
+                TAG_REGEX.as_str()
+            );
+            return false;
+        }
+        if tag.starts_with(RESERVED_SOURCE_TAG_PREFIX) {
+            log::error!(
+                "Source tag '{}' is invalid. Tags starting with '{}' are reserved",
+                tag,
+                RESERVED_SOURCE_TAG_PREFIX
+            );
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn debug_view_tags_are_validated() {
+        assert!(validate_debug_view_tag("valid-tag"));
+        assert!(validate_debug_view_tag("Tag123"));
+
+        assert!(!validate_debug_view_tag(""));
+        assert!(!validate_debug_view_tag("invalid tag"));
+        assert!(!validate_debug_view_tag("invalid_tag"));
+        assert!(!validate_debug_view_tag("a-tag-longer-than-20-characters"));
+    }
+
+    #[test]
+    fn source_tags_are_validated() {
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert!(validate_source_tags(&tags(&["automation"])));
+        assert!(validate_source_tags(&tags(&[
+            "tag1", "tag2", "tag3", "tag4", "tag5"
+        ])));
+
+        assert!(!validate_source_tags(&[]));
+        assert!(!validate_source_tags(&tags(&[
+            "tag1", "tag2", "tag3", "tag4", "tag5", "tag6"
+        ])));
+        assert!(!validate_source_tags(&tags(&["valid", "invalid tag"])));
+        assert!(!validate_source_tags(&tags(&["glean-automation"])));
+    }
+}
@@ -5,6 +5,7 @@ use std::result;
 
 use ffi_support::{handle_map::HandleError, ExternError};
 
+use lmdb::Error as LmdbError;
 use rkv::error::StoreError;
 
 /// A specialized [`Result`] type for this crate's operations.
@@ -57,6 +58,12 @@ pub enum ErrorKind {
     /// Glean not initialized
     NotInitialized,
 
+    /// YAML parsing failed
+    Yaml(String),
+
+    /// A metric or ping definition is invalid
+    InvalidDefinition(String),
+
     #[doc(hidden)]
     __NonExhaustive,
 }
@@ -85,6 +92,19 @@ impl Error {
             kind: ErrorKind::NotInitialized,
         }
     }
+
+    /// Whether this error indicates that the rkv database is corrupted,
+    /// as opposed to e.g. failing to access its files.
+    pub(crate) fn is_database_corruption(&self) -> bool {
+        match self.kind {
+            ErrorKind::Rkv(StoreError::LmdbError(LmdbError::Corrupted))
+            | ErrorKind::Rkv(StoreError::LmdbError(LmdbError::Invalid))
+            | ErrorKind::Rkv(StoreError::LmdbError(LmdbError::PageNotFound))
+            | ErrorKind::Rkv(StoreError::LmdbError(LmdbError::VersionMismatch))
+            | ErrorKind::Rkv(StoreError::DataError(_)) => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::error::Error for Error {}
@@ -104,6 +124,8 @@ impl Display for Error {
             OsString(s) => write!(f, "OsString conversion from {:?} failed", s),
             Utf8Error => write!(f, "Invalid UTF-8 byte sequence in string"),
             NotInitialized => write!(f, "Global Glean object missing"),
+            Yaml(e) => write!(f, "A YAML error occurred: {}", e),
+            InvalidDefinition(e) => write!(f, "Invalid definition: {}", e),
             __NonExhaustive => write!(f, "Unknown error"),
         }
     }
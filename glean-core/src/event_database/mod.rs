@@ -3,6 +3,7 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::BufRead;
@@ -19,6 +20,65 @@ use crate::CommonMetricData;
 use crate::Glean;
 use crate::Result;
 
+/// The value of an event's extra key.
+///
+/// Values are stored and sent as the corresponding JSON type.
+/// Events stored before extra values were typed only contain strings.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ExtraValue {
+    /// A boolean value.
+    Boolean(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A string value.
+    String(String),
+}
+
+impl ExtraValue {
+    /// The string value, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ExtraValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ExtraValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtraValue::Boolean(b) => write!(f, "{}", b),
+            ExtraValue::Integer(i) => write!(f, "{}", i),
+            ExtraValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<bool> for ExtraValue {
+    fn from(value: bool) -> Self {
+        ExtraValue::Boolean(value)
+    }
+}
+
+impl From<i64> for ExtraValue {
+    fn from(value: i64) -> Self {
+        ExtraValue::Integer(value)
+    }
+}
+
+impl From<String> for ExtraValue {
+    fn from(value: String) -> Self {
+        ExtraValue::String(value)
+    }
+}
+
+impl From<&str> for ExtraValue {
+    fn from(value: &str) -> Self {
+        ExtraValue::String(value.to_string())
+    }
+}
+
 /// Represents the recorded data for a single event.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct RecordedEvent {
@@ -41,7 +101,7 @@ pub struct RecordedEvent {
     ///
     /// The set of allowed extra keys is defined by users in the metrics file.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub extra: Option<HashMap<String, String>>,
+    pub extra: Option<HashMap<String, ExtraValue>>,
 }
 
 impl RecordedEvent {
@@ -178,13 +238,13 @@ impl EventDatabase {
     /// * `timestamp` - The timestamp of the event, in milliseconds. Must use a
     ///   monotonically increasing timer (this value is obtained on the
     ///   platform-specific side).
-    /// * `extra` - Extra data values, mapping strings to strings.
+    /// * `extra` - Extra data values, mapping strings to typed values.
     pub fn record(
         &self,
         glean: &Glean,
         meta: &CommonMetricData,
         timestamp: u64,
-        extra: Option<HashMap<String, String>>,
+        extra: Option<HashMap<String, ExtraValue>>,
     ) {
         // Create RecordedEvent object, and its JSON form for serialization
         // on disk.
@@ -295,6 +355,25 @@ impl EventDatabase {
         result
     }
 
+    /// Sync the on-disk event files to the storage device.
+    ///
+    /// Events are written to disk as they are recorded,
+    /// this makes sure they are not lost if the system shuts down right after.
+    pub fn sync_to_disk(&self) -> Result<()> {
+        // safe unwrap, only error case is poisoning
+        let _lock = self.file_lock.write().unwrap();
+        for entry in fs::read_dir(&self.path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                OpenOptions::new()
+                    .append(true)
+                    .open(entry.path())?
+                    .sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
     /// Clear all stored events, both in memory and on-disk.
     pub fn clear_all(&self) -> Result<()> {
         // safe unwrap, only error case is poisoning
@@ -389,7 +468,9 @@ mod test {
         };
 
         let mut data = HashMap::new();
-        data.insert("a key".to_string(), "a value".to_string());
+        data.insert("a key".to_string(), ExtraValue::from("a value"));
+        data.insert("a number".to_string(), ExtraValue::from(-5));
+        data.insert("a flag".to_string(), ExtraValue::from(true));
         let event_data = RecordedEvent {
             timestamp: 2,
             category: "cat".to_string(),
@@ -407,6 +488,30 @@ mod test {
         assert_eq!(event_data, serde_json::from_str(&event_data_json).unwrap());
     }
 
+    #[test]
+    fn typed_extra_values_serialize_as_json_types() {
+        let mut data = HashMap::new();
+        data.insert("string".to_string(), ExtraValue::from("5"));
+        data.insert("number".to_string(), ExtraValue::from(5));
+        data.insert("flag".to_string(), ExtraValue::from(false));
+        let event = RecordedEvent {
+            timestamp: 2,
+            category: "cat".to_string(),
+            name: "name".to_string(),
+            extra: Some(data),
+        };
+
+        assert_eq!(
+            json!({
+                "timestamp": 2,
+                "category": "cat",
+                "name": "name",
+                "extra": {"string": "5", "number": 5, "flag": false}
+            }),
+            json!(event)
+        );
+    }
+
     #[test]
     fn deserialize_existing_data() {
         let event_empty_json = r#"
@@ -436,7 +541,7 @@ mod test {
         };
 
         let mut data = HashMap::new();
-        data.insert("a key".to_string(), "a value".to_string());
+        data.insert("a key".to_string(), ExtraValue::from("a value"));
         let event_data = RecordedEvent {
             timestamp: 2,
             category: "cat".to_string(),
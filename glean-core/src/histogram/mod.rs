@@ -20,7 +20,7 @@ mod functional;
 mod linear;
 
 /// Different kinds of histograms.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum HistogramType {
     /// A histogram with linear distributed buckets.
@@ -46,3 +46,113 @@ impl CoreMetrics {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct BaselineMetrics {
+    pub duration: TimespanMetric,
+}
+
+impl BaselineMetrics {
+    pub fn new() -> BaselineMetrics {
+        BaselineMetrics {
+            duration: TimespanMetric::new(
+                CommonMetricData {
+                    name: "duration".into(),
+                    category: "glean.baseline".into(),
+                    send_in_pings: vec!["baseline".into()],
+                    lifetime: Lifetime::Ping,
+                    disabled: false,
+                    dynamic_label: None,
+                },
+                TimeUnit::Second,
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SessionMetrics {
+    pub session_id: UuidMetric,
+    pub session_count: CounterMetric,
+}
+
+impl SessionMetrics {
+    pub fn new() -> SessionMetrics {
+        SessionMetrics {
+            session_id: UuidMetric::new(CommonMetricData {
+                name: "session_id".into(),
+                category: "".into(),
+                send_in_pings: vec!["glean_internal_info".into()],
+                lifetime: Lifetime::User,
+                disabled: false,
+                dynamic_label: None,
+            }),
+            session_count: CounterMetric::new(CommonMetricData {
+                name: "session_count".into(),
+                category: "".into(),
+                send_in_pings: vec!["glean_internal_info".into()],
+                lifetime: Lifetime::User,
+                disabled: false,
+                dynamic_label: None,
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UploadMetrics {
+    pub retries_exhausted: CounterMetric,
+    pub pending_pings_evicted: CounterMetric,
+}
+
+impl UploadMetrics {
+    pub fn new() -> UploadMetrics {
+        UploadMetrics {
+            retries_exhausted: CounterMetric::new(CommonMetricData {
+                name: "upload_retries_exhausted".into(),
+                category: "glean.error".into(),
+                send_in_pings: vec!["metrics".into()],
+                lifetime: Lifetime::Ping,
+                disabled: false,
+                dynamic_label: None,
+            }),
+            pending_pings_evicted: CounterMetric::new(CommonMetricData {
+                name: "pending_pings_evicted".into(),
+                category: "glean.error".into(),
+                send_in_pings: vec!["metrics".into()],
+                lifetime: Lifetime::Ping,
+                disabled: false,
+                dynamic_label: None,
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DatabaseMetrics {
+    pub undecodable_entries: CounterMetric,
+    pub quarantined: BooleanMetric,
+}
+
+impl DatabaseMetrics {
+    pub fn new() -> DatabaseMetrics {
+        DatabaseMetrics {
+            undecodable_entries: CounterMetric::new(CommonMetricData {
+                name: "undecodable_entries".into(),
+                category: "glean.database".into(),
+                send_in_pings: vec!["metrics".into()],
+                lifetime: Lifetime::Ping,
+                disabled: false,
+                dynamic_label: None,
+            }),
+            quarantined: BooleanMetric::new(CommonMetricData {
+                name: "quarantined".into(),
+                category: "glean.database".into(),
+                send_in_pings: vec!["metrics".into()],
+                lifetime: Lifetime::Ping,
+                disabled: false,
+                dynamic_label: None,
+            }),
+        }
+    }
+}
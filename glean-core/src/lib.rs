@@ -14,10 +14,12 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, FixedOffset};
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use uuid::Uuid;
 
@@ -27,6 +29,7 @@ mod macros;
 
 mod common_metric_data;
 mod database;
+mod debug;
 mod error;
 mod error_recording;
 mod event_database;
@@ -35,6 +38,9 @@ mod internal_metrics;
 mod internal_pings;
 pub mod metrics;
 pub mod ping;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod scheduler;
 pub mod storage;
 mod system;
 pub mod upload;
@@ -42,15 +48,19 @@ mod util;
 
 pub use crate::common_metric_data::{CommonMetricData, Lifetime};
 use crate::database::Database;
+pub use crate::database::DatabaseBackend;
+use crate::debug::DebugOptions;
 pub use crate::error::{Error, Result};
 pub use crate::error_recording::{test_get_num_recorded_errors, ErrorType};
 use crate::event_database::EventDatabase;
-use crate::internal_metrics::CoreMetrics;
+use crate::internal_metrics::{BaselineMetrics, CoreMetrics, DatabaseMetrics, SessionMetrics};
 use crate::internal_pings::InternalPings;
 use crate::metrics::{Metric, MetricType, PingType};
 use crate::ping::PingMaker;
 use crate::storage::StorageManager;
-use crate::upload::{PingUploadManager, PingUploadTask, UploadResult};
+use crate::upload::{
+    PingMetadata, PingRateLimit, PingStorageQuota, PingUploadManager, PingUploadTask, UploadResult,
+};
 use crate::util::{local_now_with_offset, sanitize_application_id};
 
 const GLEAN_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -121,6 +131,15 @@ pub struct Configuration {
     pub max_events: Option<usize>,
     /// Whether Glean should delay persistence of data from metrics with ping lifetime.
     pub delay_ping_lifetime_io: bool,
+    /// The limit on the number of pings uploaded per time interval.
+    /// Defaults to 15 pings per 60 seconds.
+    pub rate_limit: Option<PingRateLimit>,
+    /// The limits on the pending pings kept on disk.
+    /// Defaults to 10 MB, 250 pings and 30 days.
+    pub storage_quota: Option<PingStorageQuota>,
+    /// The storage backend for metric data.
+    /// Defaults to an rkv environment in the data path.
+    pub database_backend: Option<DatabaseBackend>,
 }
 
 /// The object holding meta information about a Glean instance.
@@ -138,6 +157,9 @@ pub struct Configuration {
 ///     upload_enabled: true,
 ///     max_events: None,
 ///     delay_ping_lifetime_io: false,
+///     rate_limit: None,
+///     storage_quota: None,
+///     database_backend: None,
 /// };
 /// let mut glean = Glean::new(cfg).unwrap();
 /// let ping = PingType::new("sample", true, false, vec![]);
@@ -165,6 +187,11 @@ pub struct Glean {
     data_store: Option<Database>,
     event_data_store: EventDatabase,
     core_metrics: CoreMetrics,
+    database_metrics: DatabaseMetrics,
+    baseline_metrics: Mutex<BaselineMetrics>,
+    session_metrics: SessionMetrics,
+    /// Whether the client became active since this object was created.
+    has_been_active: AtomicBool,
     internal_pings: InternalPings,
     data_path: PathBuf,
     application_id: String,
@@ -173,6 +200,7 @@ pub struct Glean {
     max_events: usize,
     is_first_run: bool,
     upload_manager: PingUploadManager,
+    debug: DebugOptions,
 }
 
 impl Glean {
@@ -187,22 +215,38 @@ impl Glean {
 
         // Creating the data store creates the necessary path as well.
         // If that fails we bail out and don't initialize further.
-        let data_store = Some(Database::new(&cfg.data_path, cfg.delay_ping_lifetime_io)?);
+        let data_store = Some(Database::open(
+            &cfg.data_path,
+            cfg.database_backend.unwrap_or_default(),
+            cfg.delay_ping_lifetime_io,
+        )?);
         let event_data_store = EventDatabase::new(&cfg.data_path)?;
+        let upload_manager = PingUploadManager::with_storage_quota(
+            &cfg.data_path,
+            cfg.storage_quota.unwrap_or_default(),
+        );
+        if let Some(rate_limit) = cfg.rate_limit {
+            upload_manager.set_rate_limit(rate_limit);
+        }
 
         let mut glean = Self {
             upload_enabled: cfg.upload_enabled,
             data_store,
             event_data_store,
             core_metrics: CoreMetrics::new(),
+            database_metrics: DatabaseMetrics::new(),
+            baseline_metrics: Mutex::new(BaselineMetrics::new()),
+            session_metrics: SessionMetrics::new(),
+            has_been_active: AtomicBool::new(false),
             internal_pings: InternalPings::new(),
-            upload_manager: PingUploadManager::new(&cfg.data_path),
+            upload_manager,
             data_path: PathBuf::from(cfg.data_path),
             application_id,
             ping_registry: HashMap::new(),
             start_time: local_now_with_offset(),
             max_events: cfg.max_events.unwrap_or(DEFAULT_MAX_EVENTS),
             is_first_run: false,
+            debug: DebugOptions::default(),
         };
 
         // The upload enabled flag may have changed since the last run, for
@@ -236,6 +280,8 @@ impl Glean {
             }
         }
 
+        glean.record_database_integrity();
+
         Ok(glean)
     }
 
@@ -252,6 +298,9 @@ impl Glean {
             upload_enabled,
             max_events: None,
             delay_ping_lifetime_io: false,
+            rate_limit: None,
+            storage_quota: None,
+            database_backend: None,
         };
 
         Self::new(cfg)
@@ -463,7 +512,7 @@ impl Glean {
     ///
     /// This can be one of:
     ///
-    /// * `Wait` - which means the requester should ask again later;
+    /// * `Wait` - which means the requester should ask again after the given number of milliseconds;
     /// * `Upload(PingRequest)` - which means there is a ping to upload. This wraps the actual request object;
     /// * `Done` - which means there are no more pings queued right now.
     ///
@@ -482,7 +531,7 @@ impl Glean {
     /// * `status` - The upload result.
     pub fn process_ping_upload_response(&self, uuid: &str, status: UploadResult) {
         self.upload_manager
-            .process_ping_upload_response(uuid, status);
+            .process_ping_upload_response(self, uuid, status);
     }
 
     /// Take a snapshot for the given store and optionally clear it.
@@ -544,19 +593,36 @@ impl Glean {
                 Ok(false)
             }
             Some(content) => {
+                if self.debug.log_pings {
+                    // Safe unwrap: a `JsonValue` always serializes successfully.
+                    log::info!(
+                        "Submitting ping '{}' with document id {} at {}:\n{}",
+                        ping.name,
+                        doc_id,
+                        url_path,
+                        serde_json::to_string_pretty(&content).unwrap()
+                    );
+                }
+
+                let metadata = PingMetadata {
+                    debug_view_tag: self.debug.debug_view_tag.clone(),
+                    source_tags: self.debug.source_tags.clone(),
+                    ..PingMetadata::new()
+                };
                 if let Err(e) = ping_maker.store_ping(
                     &doc_id,
                     &ping.name,
                     &self.get_data_path(),
                     &url_path,
                     &content,
+                    &metadata,
                 ) {
                     log::warn!("IO error while writing ping to file: {}", e);
                     return Err(e.into());
                 }
 
                 self.upload_manager
-                    .enqueue_ping(&doc_id, &url_path, content);
+                    .enqueue_ping(self, &doc_id, &url_path, content);
 
                 log::info!(
                     "The ping '{}' was submitted and will be sent as soon as possible",
@@ -657,6 +723,49 @@ impl Glean {
         Ok(())
     }
 
+    /// Shut down Glean in an orderly fashion.
+    ///
+    /// Persists Lifetime::Ping data held in memory, syncs the event files to disk
+    /// and waits for the pending pings directory to be processed.
+    /// Failures are logged.
+    ///
+    /// ## Arguments
+    ///
+    /// * `timeout` - The maximum time to wait for the pending pings directory.
+    ///
+    /// ## Return value
+    ///
+    /// Returns true if everything finished before the timeout, false otherwise.
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        if let Err(e) = self.persist_ping_lifetime_data() {
+            log::error!("Failed to persist ping lifetime data on shutdown: {}", e);
+        }
+        if let Err(e) = self.event_data_store.sync_to_disk() {
+            log::error!("Failed to sync the event files on shutdown: {}", e);
+        }
+
+        let finished = self.upload_manager.join_directory_processor(deadline);
+        if !finished {
+            log::warn!("Timed out waiting for the pending pings directory to be processed");
+        }
+        finished
+    }
+
+    /// Report what the integrity check found when opening the database.
+    fn record_database_integrity(&self) {
+        let report = self.storage().integrity_report();
+        if report.undecodable_entries > 0 {
+            self.database_metrics
+                .undecodable_entries
+                .add(self, report.undecodable_entries as i32);
+        }
+        if report.quarantined {
+            self.database_metrics.quarantined.set(self, true);
+        }
+    }
+
     /// Set internally-handled application lifetime metrics.
     fn set_application_lifetime_core_metrics(&self) {
         self.core_metrics.os.set(self, system::OS);
@@ -675,6 +784,64 @@ impl Glean {
         self.set_application_lifetime_core_metrics();
     }
 
+    /// Set the debug view tag, sent in the `X-Debug-ID` header of all subsequently submitted pings.
+    ///
+    /// This routes the pings to the Debug View.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tag. It must be 1 to 20 characters long and only contain
+    ///   alphanumeric characters and hyphens.
+    ///
+    /// # Returns
+    ///
+    /// * Returns true when the tag was valid and was set, false otherwise.
+    pub fn set_debug_view_tag(&mut self, value: &str) -> bool {
+        if !debug::validate_debug_view_tag(value) {
+            return false;
+        }
+
+        self.debug.debug_view_tag = Some(value.to_string());
+        true
+    }
+
+    /// Set the source tags, sent in the `X-Source-Tags` header of all subsequently submitted pings.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tags. There must be 1 to 5 tags, each 1 to 20 characters long
+    ///   and only containing alphanumeric characters and hyphens.
+    ///   Tags starting with `glean` are reserved.
+    ///
+    /// # Returns
+    ///
+    /// * Returns true when the tags were valid and were set, false otherwise.
+    pub fn set_source_tags(&mut self, value: Vec<String>) -> bool {
+        if !debug::validate_source_tags(&value) {
+            return false;
+        }
+
+        self.debug.source_tags = Some(value);
+        true
+    }
+
+    /// Set whether to log the contents of submitted pings.
+    ///
+    /// When enabled, the JSON payload of every submitted ping is logged at info level,
+    /// along with its document id and path.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - When true, log the submitted pings.
+    pub fn set_log_pings(&mut self, value: bool) {
+        self.debug.log_pings = value;
+    }
+
+    /// Return whether the contents of submitted pings are logged.
+    pub fn log_pings(&self) -> bool {
+        self.debug.log_pings
+    }
+
     /// Return whether or not this is the first run on this profile.
     pub fn is_first_run(&self) -> bool {
         self.is_first_run
@@ -724,6 +891,99 @@ impl Glean {
         }
     }
 
+    /// Handle the client becoming active, e.g. the application moving to the foreground.
+    ///
+    /// This starts a new session. On the first call after startup, if the last session
+    /// wasn't ended by `handle_client_inactive`, e.g. because the process was killed,
+    /// a `baseline` ping with reason `dirty_startup` is submitted
+    /// and its `Lifetime::Session` metrics are cleared.
+    /// The session id is then replaced, the session count incremented
+    /// and a `baseline` ping with reason `active` submitted.
+    /// It then starts measuring the duration of the session and sets the dirty flag.
+    ///
+    /// Calls to this and `handle_client_inactive` are expected to alternate.
+    pub fn handle_client_active(&self) {
+        let is_first_activation = !self.has_been_active.swap(true, Ordering::SeqCst);
+        if is_first_activation && self.is_dirty_flag_set() {
+            if let Err(err) = self
+                .internal_pings
+                .baseline
+                .submit(self, Some("dirty_startup"))
+            {
+                log::error!("Failed to submit baseline ping on dirty startup: {}", err);
+            }
+            self.clear_session_lifetime_metrics();
+        }
+
+        self.session_metrics.session_id.generate_and_set(self);
+        self.session_metrics.session_count.add(self, 1);
+
+        if let Err(err) = self.internal_pings.baseline.submit(self, Some("active")) {
+            log::error!("Failed to submit baseline ping on active: {}", err);
+        }
+
+        self.baseline_metrics
+            .lock()
+            .unwrap()
+            .duration
+            .set_start(self, time::precise_time_ns());
+        self.set_dirty_flag(true);
+    }
+
+    /// Handle the client becoming inactive, e.g. the application moving to the background.
+    ///
+    /// This stops measuring the duration of the session,
+    /// submits a `baseline` ping with reason `inactive`, including that duration,
+    /// and ends the session: its `Lifetime::Session` metrics are cleared
+    /// and the dirty flag is cleared.
+    pub fn handle_client_inactive(&self) {
+        self.baseline_metrics
+            .lock()
+            .unwrap()
+            .duration
+            .set_stop(self, time::precise_time_ns());
+
+        if let Err(err) = self.internal_pings.baseline.submit(self, Some("inactive")) {
+            log::error!("Failed to submit baseline ping on inactive: {}", err);
+        }
+
+        self.clear_session_lifetime_metrics();
+        self.set_dirty_flag(false);
+    }
+
+    /// Clear all the metrics that have `Lifetime::Session`.
+    fn clear_session_lifetime_metrics(&self) {
+        log::debug!("Clearing Lifetime::Session metrics");
+        if let Some(data) = self.data_store.as_ref() {
+            data.clear_lifetime(Lifetime::Session);
+        }
+    }
+
+    /// Get the id of the current session.
+    ///
+    /// A new id is generated by `handle_client_active`.
+    /// Returns `None` if no session was started yet, or upload is disabled.
+    pub fn session_id(&self) -> Option<Uuid> {
+        self.session_metrics
+            .session_id
+            .get_value(self, INTERNAL_STORAGE)
+    }
+
+    /// Get the number of sessions started on this profile,
+    /// including the current one.
+    ///
+    /// The count is incremented by `handle_client_active`.
+    pub fn session_count(&self) -> i32 {
+        match StorageManager.snapshot_metric(
+            self.storage(),
+            INTERNAL_STORAGE,
+            &self.session_metrics.session_count.meta().identifier(self),
+        ) {
+            Some(Metric::Counter(count)) => count,
+            _ => 0,
+        }
+    }
+
     /// **Test-only API (exported for FFI purposes).**
     ///
     /// Check if an experiment is currently active.
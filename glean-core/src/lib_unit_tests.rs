@@ -7,6 +7,9 @@
 
 use std::collections::HashSet;
 use std::iter::FromIterator;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
 
 use super::*;
 use crate::metrics::RecordedExperimentData;
@@ -372,6 +375,11 @@ fn correct_order() {
         Timespan(Duration::new(5, 0), TimeUnit::Second),
         TimingDistribution(Histogram::functional(2.0, 8.0)),
         MemoryDistribution(Histogram::functional(2.0, 8.0)),
+        Rate(0, 0),
+        Text("glean".into()),
+        Url("https://glean".into()),
+        Jwe("eyJhbGciOiJkaXIifQ....".into()),
+        Object("{}".into()),
     ];
 
     for metric in all_metrics {
@@ -396,6 +404,11 @@ fn correct_order() {
             Timespan(..)                      => assert_eq!(10, disc),
             TimingDistribution(..)            => assert_eq!(11, disc),
             MemoryDistribution(..)            => assert_eq!(12, disc),
+            Rate(..)                          => assert_eq!(13, disc),
+            Text(..)                          => assert_eq!(14, disc),
+            Url(..)                           => assert_eq!(15, disc),
+            Jwe(..)                           => assert_eq!(16, disc),
+            Object(..)                        => assert_eq!(17, disc),
         }
     }
 }
@@ -505,6 +518,32 @@ fn backwards_compatible_deserialization() {
                  108, 88, 181, 240, 63],
              MemoryDistribution(mem_dist),
         ),
+        (
+            "rate",
+            vec![13, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0],
+            Rate(1, 2),
+        ),
+        (
+            "text",
+            vec![14, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 103, 108, 101, 97, 110],
+            Text("glean".into()),
+        ),
+        (
+            "url",
+            vec![15, 0, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0, 104, 116, 116, 112, 115, 58, 47, 47,
+                 103, 108, 101, 97, 110],
+            Url("https://glean".into()),
+        ),
+        (
+            "jwe",
+            vec![16, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 104, 46, 107, 46, 105, 46, 99, 46, 116],
+            Jwe("h.k.i.c.t".into()),
+        ),
+        (
+            "object",
+            vec![17, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 123, 34, 97, 34, 58, 49, 125],
+            Object("{\"a\":1}".into()),
+        ),
     ];
 
     for (name, data, metric) in all_metrics {
@@ -763,3 +802,252 @@ fn timing_distribution_truncation_accumulate() {
         assert!(snapshot.values.len() < 316);
     }
 }
+
+/// Get the next ping request from the upload manager,
+/// waiting for the pending pings directories to be processed.
+fn get_next_ping_request(glean: &Glean) -> upload::PingRequest {
+    loop {
+        match glean.get_upload_task() {
+            PingUploadTask::Upload(request) => return request,
+            PingUploadTask::Wait(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+            PingUploadTask::Done => panic!("Expected a ping to upload"),
+        }
+    }
+}
+
+#[test]
+fn invalid_debug_options_are_not_set() {
+    let (mut glean, _t) = new_glean(None);
+
+    assert!(!glean.set_debug_view_tag("invalid tag"));
+    assert!(!glean.set_source_tags(vec!["glean-reserved".into()]));
+
+    let ping = PingType::new("custom", true, true, vec![]);
+    glean.register_ping_type(&ping);
+    assert!(glean.submit_ping(&ping, None).unwrap());
+
+    let request = get_next_ping_request(&glean);
+    assert!(!request.headers.contains_key("X-Debug-ID"));
+    assert!(!request.headers.contains_key("X-Source-Tags"));
+}
+
+#[test]
+fn debug_options_are_sent_as_headers() {
+    let (mut glean, t) = new_glean(None);
+
+    assert!(glean.set_debug_view_tag("valid-tag"));
+    assert!(glean.set_source_tags(vec!["automation".into(), "perf".into()]));
+
+    let ping = PingType::new("custom", true, true, vec![]);
+    glean.register_ping_type(&ping);
+    assert!(glean.submit_ping(&ping, None).unwrap());
+
+    let request = get_next_ping_request(&glean);
+    assert_eq!("valid-tag", request.headers["X-Debug-ID"]);
+    assert_eq!("automation,perf", request.headers["X-Source-Tags"]);
+
+    // The tags are persisted with the ping and sent after a restart.
+    drop(glean);
+    let (glean, _t) = new_glean(Some(t));
+    let reloaded = get_next_ping_request(&glean);
+    assert_eq!(request.document_id, reloaded.document_id);
+    assert_eq!("valid-tag", reloaded.headers["X-Debug-ID"]);
+    assert_eq!("automation,perf", reloaded.headers["X-Source-Tags"]);
+}
+
+/// A logger keeping the messages logged by all tests, so they can be checked.
+struct CapturingLogger {
+    messages: Mutex<Vec<String>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.messages
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURING_LOGGER: Lazy<CapturingLogger> = Lazy::new(|| CapturingLogger {
+    messages: Mutex::new(Vec::new()),
+});
+
+/// Start capturing the log messages, if that isn't done yet.
+fn capture_logs() -> &'static CapturingLogger {
+    if log::set_logger(&*CAPTURING_LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+    &CAPTURING_LOGGER
+}
+
+#[test]
+fn pings_are_logged_when_logging_pings() {
+    let logger = capture_logs();
+    let (mut glean, _t) = new_glean(None);
+    assert!(!glean.log_pings());
+
+    let ping = PingType::new("custom", true, true, vec![]);
+    glean.register_ping_type(&ping);
+    assert!(glean.submit_ping(&ping, None).unwrap());
+    let request = get_next_ping_request(&glean);
+    let logged_ping = |document_id: &str| {
+        logger
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|message| {
+                message.starts_with("Submitting ping 'custom'") && message.contains(document_id)
+            })
+            .cloned()
+    };
+    assert_eq!(None, logged_ping(&request.document_id));
+
+    glean.set_log_pings(true);
+    assert!(glean.log_pings());
+    assert!(glean.submit_ping(&ping, None).unwrap());
+    let request = get_next_ping_request(&glean);
+
+    // The message has the whole payload.
+    let logged = logged_ping(&request.document_id).expect("The ping should be logged");
+    assert!(logged.contains("\"ping_info\""));
+    assert!(logged.contains("\"client_info\""));
+}
+
+#[test]
+fn glean_works_with_the_in_memory_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: Some(DatabaseBackend::InMemory),
+    };
+    let glean = Glean::new(cfg).unwrap();
+
+    let metric = StringMetric::new(CommonMetricData {
+        name: "string_metric".into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::User,
+        ..Default::default()
+    });
+    metric.set(&glean, "in-memory");
+    assert_eq!(
+        "in-memory",
+        metric.test_get_value(&glean, "store1").unwrap()
+    );
+
+    // No database was created on disk.
+    assert!(!dir.path().join("db").exists());
+}
+
+#[test]
+fn undecodable_database_entries_are_reported() {
+    let (glean, dir) = new_glean(None);
+    let client_id = glean
+        .core_metrics
+        .client_id
+        .get_value(&glean, "glean_client_info");
+    drop(glean);
+
+    {
+        use crate::database::{RkvBackend, StorageBackend};
+        let backend = RkvBackend::new(&dir.path().display().to_string()).unwrap();
+        backend
+            .put(Lifetime::User, "store1#telemetry.broken", b"\xff")
+            .unwrap();
+    }
+
+    let (glean, _) = new_glean(Some(dir));
+    assert_eq!(
+        client_id,
+        glean
+            .core_metrics
+            .client_id
+            .get_value(&glean, "glean_client_info")
+    );
+    assert_eq!(
+        Some(1),
+        glean
+            .database_metrics
+            .undecodable_entries
+            .test_get_value(&glean, "metrics")
+    );
+    assert_eq!(
+        None,
+        glean
+            .database_metrics
+            .quarantined
+            .test_get_value(&glean, "metrics")
+    );
+}
+
+#[test]
+fn corrupted_database_is_quarantined_and_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("db");
+    std::fs::create_dir_all(&db_path).unwrap();
+    std::fs::write(db_path.join("data.mdb"), vec![0xab; 8192]).unwrap();
+
+    let (glean, dir) = new_glean(Some(dir));
+    assert!(dir.path().join("db-quarantine").exists());
+    assert_eq!(
+        Some(true),
+        glean
+            .database_metrics
+            .quarantined
+            .test_get_value(&glean, "metrics")
+    );
+    assert!(glean
+        .core_metrics
+        .client_id
+        .get_value(&glean, "glean_client_info")
+        .is_some());
+}
+
+#[test]
+fn shutdown_persists_delayed_ping_lifetime_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = Configuration {
+        data_path: dir.path().display().to_string(),
+        application_id: GLOBAL_APPLICATION_ID.into(),
+        upload_enabled: true,
+        max_events: None,
+        delay_ping_lifetime_io: true,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
+    };
+    let metric = StringMetric::new(CommonMetricData {
+        name: "string_metric".into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    });
+
+    {
+        let glean = Glean::new(cfg.clone()).unwrap();
+        metric.set(&glean, "delayed");
+        assert!(glean.shutdown(Duration::from_secs(5)));
+    }
+
+    let glean = Glean::new(cfg).unwrap();
+    assert_eq!("delayed", metric.test_get_value(&glean, "store1").unwrap());
+}
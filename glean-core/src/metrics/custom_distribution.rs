@@ -12,7 +12,7 @@ use crate::Glean;
 /// A custom distribution metric.
 ///
 /// Memory distributions are used to accumulate and store memory sizes.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CustomDistributionMetric {
     meta: CommonMetricData,
     range_min: u64,
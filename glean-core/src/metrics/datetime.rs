@@ -19,7 +19,7 @@ use chrono::{DateTime, FixedOffset, TimeZone};
 ///
 /// Used to record an absolute date and time, such as the time the user first ran
 /// the application.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DatetimeMetric {
     meta: CommonMetricData,
     time_unit: TimeUnit,
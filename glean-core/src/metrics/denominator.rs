@@ -0,0 +1,76 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::metrics::{CounterMetric, MetricType, NumeratorMetric};
+use crate::CommonMetricData;
+use crate::Glean;
+
+/// A denominator metric.
+///
+/// A counter that is also the denominator of a set of
+/// [`NumeratorMetric`](struct.NumeratorMetric.html)s.
+/// It is sent as a counter, and every increase is also added to the denominator of the numerators.
+#[derive(Clone, Debug)]
+pub struct DenominatorMetric {
+    counter: CounterMetric,
+    numerators: Vec<NumeratorMetric>,
+}
+
+impl MetricType for DenominatorMetric {
+    fn meta(&self) -> &CommonMetricData {
+        self.counter.meta()
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        self.counter.meta_mut()
+    }
+}
+
+impl DenominatorMetric {
+    /// Create a new denominator metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `meta` - The metadata of the denominator.
+    /// * `numerators` - The numerators this is the denominator of.
+    pub fn new(meta: CommonMetricData, numerators: Vec<NumeratorMetric>) -> Self {
+        Self {
+            counter: CounterMetric::new(meta),
+            numerators,
+        }
+    }
+
+    /// Increase the denominator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `amount` - The amount to increase by. Should be positive.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is 0 or negative.
+    pub fn add(&self, glean: &Glean, amount: i32) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        // The counter reports invalid amounts, the numerators don't record them.
+        if amount > 0 {
+            for numerator in &self.numerators {
+                numerator.0.add_to_denominator(glean, amount);
+            }
+        }
+        self.counter.add(glean, amount)
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored value as an integer.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<i32> {
+        self.counter.test_get_value(glean, storage_name)
+    }
+}
@@ -3,11 +3,12 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::collections::HashMap;
+use std::hash::Hash;
 
 use serde_json::{json, Value as JsonValue};
 
 use crate::error_recording::{record_error, ErrorType};
-use crate::event_database::RecordedEvent;
+use crate::event_database::{ExtraValue, RecordedEvent};
 use crate::metrics::MetricType;
 use crate::util::truncate_string_at_boundary_with_error;
 use crate::CommonMetricData;
@@ -15,6 +16,30 @@ use crate::Glean;
 
 const MAX_LENGTH_EXTRA_KEY_VALUE: usize = 100;
 
+/// The extra keys of an event metric.
+///
+/// This is usually implemented by an enum generated from the `metrics.yaml` file,
+/// with one variant per extra key.
+pub trait ExtraKeys: Copy + Eq + Hash {
+    /// The names of all extra keys.
+    const ALLOWED_KEYS: &'static [&'static str];
+
+    /// The name of the key, as listed in `ALLOWED_KEYS`.
+    fn as_str(self) -> &'static str;
+}
+
+/// The extra keys of events without extra keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoExtraKeys {}
+
+impl ExtraKeys for NoExtraKeys {
+    const ALLOWED_KEYS: &'static [&'static str] = &[];
+
+    fn as_str(self) -> &'static str {
+        match self {}
+    }
+}
+
 /// An event metric.
 ///
 /// Events allow recording of e.g. individual occurences of user actions, say
@@ -45,7 +70,13 @@ impl EventMetric {
         }
     }
 
-    /// Record an event.
+    /// Record an event, with extra keys given by their index.
+    ///
+    /// This is used by the FFI, where extra keys are passed as indices.
+    /// From Rust, use [`record_with_names`] or [`record_with_keys`] instead.
+    ///
+    /// [`record_with_names`]: #method.record_with_names
+    /// [`record_with_keys`]: #method.record_with_keys
     ///
     /// ## Arguments
     ///
@@ -67,39 +98,112 @@ impl EventMetric {
             return;
         }
 
-        let extra = extra.into();
-        let extra_strings: Option<HashMap<String, String>> = if let Some(extra) = extra {
-            if extra.is_empty() {
-                None
-            } else {
-                let mut extra_strings = HashMap::new();
-                for (k, v) in extra.into_iter() {
-                    match self.allowed_extra_keys.get(k as usize) {
-                        Some(k) => extra_strings.insert(
-                            k.to_string(),
-                            truncate_string_at_boundary_with_error(
+        let extra = match extra.into() {
+            Some(extra) => extra,
+            None => return self.record_validated(glean, timestamp, HashMap::new()),
+        };
+
+        let mut extra_strings = HashMap::new();
+        for (k, v) in extra.into_iter() {
+            match self.allowed_extra_keys.get(k as usize) {
+                Some(k) => extra_strings.insert(k.to_string(), ExtraValue::String(v)),
+                None => {
+                    let msg = format!("Invalid key index {}", k);
+                    record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+                    return;
+                }
+            };
+        }
+        self.record_validated(glean, timestamp, extra_strings);
+    }
+
+    /// Record an event, with extra keys given by their name.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `timestamp` - A monotonically increasing timestamp, in milliseconds.
+    ///   This must be provided since the actual recording of the event may
+    ///   happen some time later than the moment the event occurred.
+    /// * `extra` - A HashMap of (key, value) pairs, with string, integer or
+    ///   boolean values. If any key is not in the metric's `allowed_extra_keys`,
+    ///   an error is reported and no event is recorded.
+    pub fn record_with_names<M: Into<Option<HashMap<String, ExtraValue>>>>(
+        &self,
+        glean: &Glean,
+        timestamp: u64,
+        extra: M,
+    ) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        let extra = extra.into().unwrap_or_default();
+        if let Some(k) = extra.keys().find(|k| !self.allowed_extra_keys.contains(k)) {
+            let msg = format!("Invalid key {}", k);
+            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            return;
+        }
+        self.record_validated(glean, timestamp, extra);
+    }
+
+    /// Record an event, with extra keys of type `K`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `timestamp` - A monotonically increasing timestamp, in milliseconds.
+    ///   This must be provided since the actual recording of the event may
+    ///   happen some time later than the moment the event occurred.
+    /// * `extra` - A HashMap of (key, value) pairs. The keys are validated by
+    ///   name, as in [`record_with_names`].
+    ///
+    /// [`record_with_names`]: #method.record_with_names
+    pub fn record_with_keys<K: ExtraKeys, M: Into<Option<HashMap<K, ExtraValue>>>>(
+        &self,
+        glean: &Glean,
+        timestamp: u64,
+        extra: M,
+    ) {
+        let extra = extra.into().map(|extra| {
+            extra
+                .into_iter()
+                .map(|(k, v)| (k.as_str().to_string(), v))
+                .collect::<HashMap<_, _>>()
+        });
+        self.record_with_names(glean, timestamp, extra);
+    }
+
+    /// Record an event whose extra keys are known to be allowed.
+    ///
+    /// String values are truncated to `MAX_LENGTH_EXTRA_KEY_VALUE`.
+    fn record_validated(&self, glean: &Glean, timestamp: u64, extra: HashMap<String, ExtraValue>) {
+        let extra = if extra.is_empty() {
+            None
+        } else {
+            let extra = extra
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        ExtraValue::String(v) => {
+                            ExtraValue::String(truncate_string_at_boundary_with_error(
                                 glean,
                                 &self.meta,
                                 v,
                                 MAX_LENGTH_EXTRA_KEY_VALUE,
-                            ),
-                        ),
-                        None => {
-                            let msg = format!("Invalid key index {}", k);
-                            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
-                            return;
+                            ))
                         }
+                        v => v,
                     };
-                }
-                Some(extra_strings)
-            }
-        } else {
-            None
+                    (k, v)
+                })
+                .collect();
+            Some(extra)
         };
 
         glean
             .event_storage()
-            .record(glean, &self.meta, timestamp, extra_strings);
+            .record(glean, &self.meta, timestamp, extra);
     }
 
     /// **Test-only API (exported for FFI purposes).**
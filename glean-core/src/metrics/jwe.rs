@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::error_recording::{record_error, ErrorType};
+use crate::metrics::Metric;
+use crate::metrics::MetricType;
+use crate::storage::StorageManager;
+use crate::CommonMetricData;
+use crate::Glean;
+
+// The maximum number of bytes of every component other than the ciphertext.
+const MAX_ELEMENT_LENGTH: usize = 1024;
+
+// The maximum number of bytes of the ciphertext, 200 KB.
+const MAX_CIPHERTEXT_LENGTH: usize = 200 * 1024;
+
+/// The components of a JWE value, in the order of the compact serialization.
+const COMPONENTS: [&str; 5] = [
+    "header",
+    "key",
+    "initialization vector",
+    "ciphertext",
+    "tag",
+];
+
+/// A JWE metric.
+///
+/// Record a JSON Web Encryption value, as defined by RFC 7516,
+/// in its compact serialization:
+/// `BASE64URL(header).BASE64URL(key).BASE64URL(IV).BASE64URL(ciphertext).BASE64URL(tag)`.
+#[derive(Clone, Debug)]
+pub struct JweMetric {
+    meta: CommonMetricData,
+}
+
+impl MetricType for JweMetric {
+    fn meta(&self) -> &CommonMetricData {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        &mut self.meta
+    }
+}
+
+/// Whether a value is encoded in unpadded BASE64URL.
+fn is_base64url(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Validate the components of a JWE value.
+///
+/// ## Return value
+///
+/// The type and message of the error to record if the components are invalid.
+fn validate(components: &[&str]) -> Result<(), (ErrorType, String)> {
+    if components.len() != COMPONENTS.len() {
+        return Err((
+            ErrorType::InvalidValue,
+            format!(
+                "Expected {} JWE components, got {}",
+                COMPONENTS.len(),
+                components.len()
+            ),
+        ));
+    }
+
+    for (name, value) in COMPONENTS.iter().zip(components) {
+        // The key, IV and tag are empty for some algorithms, e.g. direct encryption.
+        let required = *name == "header" || *name == "ciphertext";
+        if required && value.is_empty() {
+            return Err((
+                ErrorType::InvalidValue,
+                format!("The JWE {} must not be empty", name),
+            ));
+        }
+
+        if !is_base64url(value) {
+            return Err((
+                ErrorType::InvalidValue,
+                format!("The JWE {} is not BASE64URL encoded", name),
+            ));
+        }
+
+        let max_length = if *name == "ciphertext" {
+            MAX_CIPHERTEXT_LENGTH
+        } else {
+            MAX_ELEMENT_LENGTH
+        };
+        if value.len() > max_length {
+            return Err((
+                ErrorType::InvalidOverflow,
+                format!(
+                    "The JWE {} length {} exceeds maximum of {}",
+                    name,
+                    value.len(),
+                    max_length
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+impl JweMetric {
+    /// Create a new JWE metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self { meta }
+    }
+
+    /// Set to the specified JWE value, in compact serialization.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `value` - The compact serialization of the JWE value.
+    ///
+    /// ## Notes
+    ///
+    /// Records an `InvalidValue` error and doesn't set the value if it doesn't
+    /// have five valid components, or an `InvalidOverflow` error if a component is too long.
+    pub fn set_with_compact_representation<S: Into<String>>(&self, glean: &Glean, value: S) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        let value = value.into();
+        let components: Vec<&str> = value.split('.').collect();
+        if let Err((error_type, msg)) = validate(&components) {
+            record_error(glean, &self.meta, error_type, msg, None);
+            return;
+        }
+
+        let value = Metric::Jwe(value);
+        glean.storage().record(glean, &self.meta, &value)
+    }
+
+    /// Build a JWE value from its components and set to it.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `header` - The BASE64URL encoded JWE protected header.
+    /// * `key` - The BASE64URL encoded encrypted key, which may be empty.
+    /// * `init_vector` - The BASE64URL encoded initialization vector, which may be empty.
+    /// * `cipher_text` - The BASE64URL encoded ciphertext.
+    /// * `auth_tag` - The BASE64URL encoded authentication tag, which may be empty.
+    ///
+    /// ## Notes
+    ///
+    /// Records an error and doesn't set the value if a component is invalid,
+    /// as in [`set_with_compact_representation`](#method.set_with_compact_representation).
+    pub fn set(
+        &self,
+        glean: &Glean,
+        header: &str,
+        key: &str,
+        init_vector: &str,
+        cipher_text: &str,
+        auth_tag: &str,
+    ) {
+        let components = [header, key, init_vector, cipher_text, auth_tag];
+        if components.iter().any(|component| component.contains('.')) {
+            if self.should_record(glean) {
+                let msg = "JWE components must not contain '.'".to_string();
+                record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            }
+            return;
+        }
+
+        self.set_with_compact_representation(glean, components.join("."))
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored value, in compact serialization.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<String> {
+        match StorageManager.snapshot_metric(
+            glean.storage(),
+            storage_name,
+            &self.meta.identifier(glean),
+        ) {
+            Some(Metric::Jwe(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validates_components() {
+        assert!(validate(&["eyJhbGciOiJkaXIifQ", "", "", "Y2lwaGVy", ""]).is_ok());
+        assert!(validate(&["aGVhZGVy", "a2V5", "aXY", "Y2lwaGVy", "dGFn"]).is_ok());
+
+        // Wrong number of components.
+        assert!(validate(&["aGVhZGVy", "a2V5", "aXY", "Y2lwaGVy"]).is_err());
+        // Missing header or ciphertext.
+        assert!(validate(&["", "a2V5", "aXY", "Y2lwaGVy", "dGFn"]).is_err());
+        assert!(validate(&["aGVhZGVy", "a2V5", "aXY", "", "dGFn"]).is_err());
+        // Padded or non-url base64.
+        assert!(validate(&["aGVhZGVy", "a2V5", "aXY=", "Y2lwaGVy", "dGFn"]).is_err());
+        assert!(validate(&["aGVhZGVy", "a2V5", "aXY", "Y2l+aGVy", "dGFn"]).is_err());
+
+        let long = "a".repeat(MAX_ELEMENT_LENGTH + 1);
+        match validate(&[&long, "", "", "Y2lwaGVy", ""]) {
+            Err((ErrorType::InvalidOverflow, _)) => {}
+            _ => panic!("Expected an overflow error"),
+        }
+        assert!(validate(&["aGVhZGVy", "", "", &long, ""]).is_ok());
+    }
+}
@@ -10,7 +10,7 @@ use crate::error_recording::{record_error, ErrorType};
 use crate::metrics::{Metric, MetricType};
 use crate::Glean;
 
-const MAX_LABELS: usize = 16;
+pub(crate) const MAX_LABELS: usize = 16;
 const OTHER_LABEL: &str = "__other__";
 const MAX_LABEL_LENGTH: usize = 61;
 
@@ -35,6 +35,12 @@ const MAX_LABEL_LENGTH: usize = 61;
 static LABEL_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new("^[a-z_][a-z0-9_-]{0,29}(\\.[a-z_][a-z0-9_-]{0,29})*$").unwrap());
 
+/// Whether a label has a valid length and format.
+#[cfg(feature = "registry")]
+pub(crate) fn is_valid_label(label: &str) -> bool {
+    label.len() <= MAX_LABEL_LENGTH && LABEL_REGEX.is_match(label)
+}
+
 /// A labeled metric.
 ///
 /// Labeled metrics allow to record multiple sub-metrics of the same type under different string labels.
@@ -10,7 +10,7 @@ use crate::error::{Error, ErrorKind};
 
 /// Different resolutions supported by the memory related metric types (e.g.
 /// MemoryDistributionMetric).
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum MemoryUnit {
     ///
@@ -14,21 +14,28 @@ mod boolean;
 mod counter;
 mod custom_distribution;
 mod datetime;
+mod denominator;
 mod event;
 mod experiment;
+mod jwe;
 mod labeled;
 mod memory_distribution;
 mod memory_unit;
+mod numerator;
+mod object;
 mod ping;
 mod quantity;
+mod rate;
 mod string;
 mod string_list;
+mod text;
 mod time_unit;
 mod timespan;
 mod timing_distribution;
+mod url;
 mod uuid;
 
-pub use crate::event_database::RecordedEvent;
+pub use crate::event_database::{ExtraValue, RecordedEvent};
 use crate::histogram::{Functional, Histogram, PrecomputedExponential, PrecomputedLinear};
 use crate::util::get_iso_time_string;
 use crate::CommonMetricData;
@@ -37,7 +44,8 @@ use crate::Glean;
 pub use self::boolean::BooleanMetric;
 pub use self::counter::CounterMetric;
 pub use self::datetime::DatetimeMetric;
-pub use self::event::EventMetric;
+pub use self::denominator::DenominatorMetric;
+pub use self::event::{EventMetric, ExtraKeys, NoExtraKeys};
 pub(crate) use self::experiment::ExperimentMetric;
 pub use crate::histogram::HistogramType;
 // Note: only expose RecordedExperimentData to tests in
@@ -45,19 +53,27 @@ pub use crate::histogram::HistogramType;
 pub use self::custom_distribution::CustomDistributionMetric;
 #[cfg(test)]
 pub(crate) use self::experiment::RecordedExperimentData;
+pub use self::jwe::JweMetric;
 pub use self::labeled::{
     combine_base_identifier_and_label, dynamic_label, strip_label, LabeledMetric,
 };
+#[cfg(feature = "registry")]
+pub(crate) use self::labeled::{is_valid_label, MAX_LABELS};
 pub use self::memory_distribution::MemoryDistributionMetric;
 pub use self::memory_unit::MemoryUnit;
+pub use self::numerator::NumeratorMetric;
+pub use self::object::{ObjectMetric, ObjectSchema};
 pub use self::ping::PingType;
 pub use self::quantity::QuantityMetric;
+pub use self::rate::{Rate, RateMetric};
 pub use self::string::StringMetric;
 pub use self::string_list::StringListMetric;
+pub use self::text::TextMetric;
 pub use self::time_unit::TimeUnit;
 pub use self::timespan::TimespanMetric;
 pub use self::timing_distribution::TimerId;
 pub use self::timing_distribution::TimingDistributionMetric;
+pub use self::url::UrlMetric;
 pub use self::uuid::UuidMetric;
 
 /// A snapshot of all buckets and the accumulated sum of a distribution.
@@ -112,6 +128,17 @@ pub enum Metric {
     TimingDistribution(Histogram<Functional>),
     /// A memory distribution. See [`MemoryDistributionMetric`](struct.MemoryDistributionMetric.html) for more information.
     MemoryDistribution(Histogram<Functional>),
+    /// A rate, as numerator and denominator.
+    /// See [`RateMetric`](struct.RateMetric.html) and [`NumeratorMetric`](struct.NumeratorMetric.html) for more information.
+    Rate(i32, i32),
+    /// A text metric. See [`TextMetric`](struct.TextMetric.html) for more information.
+    Text(String),
+    /// A URL metric. See [`UrlMetric`](struct.UrlMetric.html) for more information.
+    Url(String),
+    /// A JWE metric, in compact serialization. See [`JweMetric`](struct.JweMetric.html) for more information.
+    Jwe(String),
+    /// An object metric, as a JSON-encoded string. See [`ObjectMetric`](struct.ObjectMetric.html) for more information.
+    Object(String),
 }
 
 /// A `MetricType` describes common behavior across all metrics.
@@ -152,6 +179,11 @@ impl Metric {
             Metric::TimingDistribution(_) => "timing_distribution",
             Metric::Uuid(_) => "uuid",
             Metric::MemoryDistribution(_) => "memory_distribution",
+            Metric::Rate(..) => "rate",
+            Metric::Text(_) => "text",
+            Metric::Url(_) => "url",
+            Metric::Jwe(_) => "jwe",
+            Metric::Object(_) => "object",
         }
     }
 
@@ -175,6 +207,13 @@ impl Metric {
             Metric::TimingDistribution(hist) => json!(timing_distribution::snapshot(hist)),
             Metric::Uuid(s) => json!(s),
             Metric::MemoryDistribution(hist) => json!(memory_distribution::snapshot(hist)),
+            Metric::Rate(numerator, denominator) => {
+                json!({"numerator": numerator, "denominator": denominator})
+            }
+            Metric::Text(s) => json!(s),
+            Metric::Url(s) => json!(s),
+            Metric::Jwe(s) => json!(s),
+            Metric::Object(s) => serde_json::from_str(s).unwrap_or(JsonValue::Null),
         }
     }
 }
@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::metrics::{MetricType, Rate, RateMetric};
+use crate::CommonMetricData;
+use crate::Glean;
+
+/// A numerator metric.
+///
+/// A rate metric whose denominator is shared with other numerators,
+/// through a [`DenominatorMetric`](struct.DenominatorMetric.html).
+/// Only the numerator is increased directly.
+#[derive(Clone, Debug)]
+pub struct NumeratorMetric(pub(crate) RateMetric);
+
+impl MetricType for NumeratorMetric {
+    fn meta(&self) -> &CommonMetricData {
+        self.0.meta()
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        self.0.meta_mut()
+    }
+}
+
+impl NumeratorMetric {
+    /// Create a new numerator metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self(RateMetric::new(meta))
+    }
+
+    /// Increase the numerator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_numerator(&self, glean: &Glean, amount: i32) {
+        self.0.add_to_numerator(glean, amount)
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored numerator and denominator.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<Rate> {
+        self.0.test_get_value(glean, storage_name)
+    }
+}
@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+
+use crate::error_recording::{record_error, ErrorType};
+use crate::metrics::Metric;
+use crate::metrics::MetricType;
+use crate::storage::StorageManager;
+use crate::CommonMetricData;
+use crate::Glean;
+
+/// The shape of the values accepted by an object metric.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectSchema {
+    /// A boolean.
+    Boolean,
+    /// A number, either an integer or a floating point value.
+    Number,
+    /// A string.
+    String,
+    /// An array of at most `max_length` items, each matching `items`.
+    Array {
+        /// The schema of the array items.
+        items: Box<ObjectSchema>,
+        /// The maximum number of items.
+        max_length: usize,
+    },
+    /// An object with the given allowed keys, each with the schema of its value.
+    ///
+    /// Keys may be left out, but no other keys are allowed.
+    Object(HashMap<String, ObjectSchema>),
+}
+
+impl ObjectSchema {
+    /// Validate a value against this schema.
+    ///
+    /// ## Return value
+    ///
+    /// Returns a description of the first mismatch, if any.
+    /// `path` is the location of `value` in the whole object, used in that description.
+    fn validate(&self, value: &JsonValue, path: &str) -> Result<(), String> {
+        match (self, value) {
+            (ObjectSchema::Boolean, JsonValue::Bool(_)) => Ok(()),
+            (ObjectSchema::Number, JsonValue::Number(_)) => Ok(()),
+            (ObjectSchema::String, JsonValue::String(_)) => Ok(()),
+            (ObjectSchema::Array { items, max_length }, JsonValue::Array(values)) => {
+                if values.len() > *max_length {
+                    return Err(format!(
+                        "{}: array length {} exceeds maximum of {}",
+                        path,
+                        values.len(),
+                        max_length
+                    ));
+                }
+                values
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(i, v)| items.validate(v, &format!("{}[{}]", path, i)))
+            }
+            (ObjectSchema::Object(keys), JsonValue::Object(values)) => {
+                values.iter().try_for_each(|(k, v)| match keys.get(k) {
+                    Some(schema) => schema.validate(v, &format!("{}.{}", path, k)),
+                    None => Err(format!("{}: unknown key '{}'", path, k)),
+                })
+            }
+            (_, value) => Err(format!("{}: unexpected value {}", path, value)),
+        }
+    }
+}
+
+/// An object metric.
+///
+/// Record structured data as a JSON object or array.
+/// Its shape is fixed by the `ObjectSchema` the metric is created with,
+/// values that don't match it are rejected.
+#[derive(Clone, Debug)]
+pub struct ObjectMetric {
+    meta: CommonMetricData,
+    schema: ObjectSchema,
+}
+
+impl MetricType for ObjectMetric {
+    fn meta(&self) -> &CommonMetricData {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        &mut self.meta
+    }
+}
+
+impl ObjectMetric {
+    /// Create a new object metric.
+    ///
+    /// The root of the `schema` should be an `ObjectSchema::Object` or an `ObjectSchema::Array`.
+    pub fn new(meta: CommonMetricData, schema: ObjectSchema) -> Self {
+        Self { meta, schema }
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `value` - The JSON object or array to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Records an `InvalidValue` error and doesn't set the value
+    /// if it doesn't match the metric's schema.
+    pub fn set(&self, glean: &Glean, value: JsonValue) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        if let Err(msg) = self.schema.validate(&value, "$") {
+            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            return;
+        }
+
+        let value = Metric::Object(value.to_string());
+        glean.storage().record(glean, &self.meta, &value)
+    }
+
+    /// Set to the specified JSON-encoded value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `value` - The JSON-encoded object or array to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Records an `InvalidValue` error and doesn't set the value
+    /// if it isn't valid JSON or doesn't match the metric's schema.
+    pub fn set_string(&self, glean: &Glean, value: &str) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        match serde_json::from_str(value) {
+            Ok(value) => self.set(glean, value),
+            Err(e) => {
+                let msg = format!("Value is not valid JSON: {}", e);
+                record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            }
+        }
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored value.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<JsonValue> {
+        match StorageManager.snapshot_metric(
+            glean.storage(),
+            storage_name,
+            &self.meta.identifier(glean),
+        ) {
+            Some(Metric::Object(s)) => serde_json::from_str(&s).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validates_against_schema() {
+        let mut keys = HashMap::new();
+        keys.insert("name".to_string(), ObjectSchema::String);
+        keys.insert(
+            "sizes".to_string(),
+            ObjectSchema::Array {
+                items: Box::new(ObjectSchema::Number),
+                max_length: 2,
+            },
+        );
+        let schema = ObjectSchema::Object(keys);
+
+        assert!(schema.validate(&json!({}), "$").is_ok());
+        assert!(schema
+            .validate(&json!({"name": "a", "sizes": [1, 2.5]}), "$")
+            .is_ok());
+
+        assert!(schema.validate(&json!([]), "$").is_err());
+        assert!(schema.validate(&json!({"other": 1}), "$").is_err());
+        assert!(schema.validate(&json!({"name": 1}), "$").is_err());
+        assert!(schema.validate(&json!({"name": null}), "$").is_err());
+        assert!(schema.validate(&json!({"sizes": [1, 2, 3]}), "$").is_err());
+        assert!(schema.validate(&json!({"sizes": ["1"]}), "$").is_err());
+    }
+}
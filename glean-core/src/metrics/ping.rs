@@ -19,6 +19,10 @@ pub struct PingType {
     pub send_if_empty: bool,
     /// The "reason" codes that this ping can send
     pub reason_codes: Vec<String>,
+    /// Whether the ping includes text metrics.
+    ///
+    /// This is `false` for new ping types, pings have to opt in to carry text metrics.
+    pub include_text: bool,
 }
 
 impl PingType {
@@ -42,6 +46,7 @@ impl PingType {
             include_client_id,
             send_if_empty,
             reason_codes,
+            include_text: false,
         }
     }
 
@@ -0,0 +1,132 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error_recording::{record_error, ErrorType};
+use crate::metrics::Metric;
+use crate::metrics::MetricType;
+use crate::storage::StorageManager;
+use crate::CommonMetricData;
+use crate::Glean;
+
+/// A rate value as given by its numerator and denominator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rate {
+    /// A rate's numerator.
+    pub numerator: i32,
+    /// A rate's denominator.
+    pub denominator: i32,
+}
+
+impl From<(i32, i32)> for Rate {
+    fn from((numerator, denominator): (i32, i32)) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+/// A rate metric.
+///
+/// Used to determine the proportion of things.
+/// Both the numerator and the denominator are stored together,
+/// so they are always sent in the same ping.
+#[derive(Clone, Debug)]
+pub struct RateMetric {
+    meta: CommonMetricData,
+}
+
+impl MetricType for RateMetric {
+    fn meta(&self) -> &CommonMetricData {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        &mut self.meta
+    }
+}
+
+impl RateMetric {
+    /// Create a new rate metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self { meta }
+    }
+
+    /// Increase the numerator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_numerator(&self, glean: &Glean, amount: i32) {
+        self.add(glean, amount, 0, "numerator")
+    }
+
+    /// Increase the denominator by `amount`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `amount` - The amount to increase by. Should be non-negative.
+    ///
+    /// ## Notes
+    ///
+    /// Logs an error if the `amount` is negative.
+    pub fn add_to_denominator(&self, glean: &Glean, amount: i32) {
+        self.add(glean, 0, amount, "denominator")
+    }
+
+    fn add(&self, glean: &Glean, numerator: i32, denominator: i32, part: &str) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        if numerator < 0 || denominator < 0 {
+            record_error(
+                glean,
+                &self.meta,
+                ErrorType::InvalidValue,
+                format!(
+                    "Added negative value {} to {}",
+                    numerator.min(denominator),
+                    part
+                ),
+                None,
+            );
+            return;
+        }
+
+        glean
+            .storage()
+            .record_with(glean, &self.meta, |old_value| match old_value {
+                Some(Metric::Rate(old_numerator, old_denominator)) => Metric::Rate(
+                    old_numerator.saturating_add(numerator),
+                    old_denominator.saturating_add(denominator),
+                ),
+                _ => Metric::Rate(numerator, denominator),
+            })
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored numerator and denominator.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<Rate> {
+        match StorageManager.snapshot_metric(
+            glean.storage(),
+            storage_name,
+            &self.meta.identifier(glean),
+        ) {
+            Some(Metric::Rate(numerator, denominator)) => Some((numerator, denominator).into()),
+            _ => None,
+        }
+    }
+}
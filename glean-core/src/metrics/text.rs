@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::error_recording::{record_error, ErrorType};
+use crate::metrics::Metric;
+use crate::metrics::MetricType;
+use crate::storage::StorageManager;
+use crate::util::truncate_string_at_boundary;
+use crate::CommonMetricData;
+use crate::Glean;
+
+// The maximum number of bytes of a text value, 200 KB.
+const MAX_LENGTH_VALUE: usize = 200 * 1024;
+
+/// A text metric.
+///
+/// Record long-form Unicode text, length-limited to `MAX_LENGTH_VALUE` bytes.
+/// Text metrics are only included in pings that opt in, see
+/// [`PingType::include_text`](struct.PingType.html#structfield.include_text).
+#[derive(Clone, Debug)]
+pub struct TextMetric {
+    meta: CommonMetricData,
+}
+
+impl MetricType for TextMetric {
+    fn meta(&self) -> &CommonMetricData {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        &mut self.meta
+    }
+}
+
+impl TextMetric {
+    /// Create a new text metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self { meta }
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `value` - The text to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Truncates the value if it is longer than `MAX_LENGTH_VALUE` bytes
+    /// and records an `InvalidOverflow` error.
+    /// The value is not recorded for registered pings that don't include text,
+    /// which is reported as an `InvalidValue` error.
+    pub fn set<S: Into<String>>(&self, glean: &Glean, value: S) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        // Text would be dropped from these pings on submission, so it isn't stored for them at all.
+        let (pings, excluded): (Vec<_>, Vec<_>) =
+            self.meta.send_in_pings.iter().cloned().partition(|ping| {
+                glean
+                    .get_ping_by_name(ping)
+                    .map_or(true, |ping| ping.include_text)
+            });
+        if !excluded.is_empty() {
+            let msg = format!("Text can't be sent in pings {:?}", excluded);
+            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+        }
+        if pings.is_empty() {
+            return;
+        }
+
+        let mut s = value.into();
+        if s.len() > MAX_LENGTH_VALUE {
+            let msg = format!(
+                "Value length {} exceeds maximum of {}",
+                s.len(),
+                MAX_LENGTH_VALUE
+            );
+            record_error(glean, &self.meta, ErrorType::InvalidOverflow, msg, None);
+            s = truncate_string_at_boundary(s, MAX_LENGTH_VALUE);
+        }
+
+        let meta = CommonMetricData {
+            send_in_pings: pings,
+            ..self.meta.clone()
+        };
+        let value = Metric::Text(s);
+        glean.storage().record(glean, &meta, &value)
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored value as a string.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<String> {
+        match StorageManager.snapshot_metric(
+            glean.storage(),
+            storage_name,
+            &self.meta.identifier(glean),
+        ) {
+            Some(Metric::Text(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
@@ -15,7 +15,7 @@ use crate::Glean;
 /// A timespan metric.
 ///
 /// Timespans are used to make a measurement of how much time is spent in a particular task.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TimespanMetric {
     meta: CommonMetricData,
     time_unit: TimeUnit,
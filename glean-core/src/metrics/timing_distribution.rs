@@ -93,7 +93,7 @@ impl Timings {
 /// A timing distribution metric.
 ///
 /// Timing distributions are used to accumulate and store time measurement, for analyzing distributions of the timing data.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TimingDistributionMetric {
     meta: CommonMetricData,
     time_unit: TimeUnit,
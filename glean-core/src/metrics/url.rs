@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::error_recording::{record_error, ErrorType};
+use crate::metrics::Metric;
+use crate::metrics::MetricType;
+use crate::storage::StorageManager;
+use crate::util::truncate_string_at_boundary;
+use crate::CommonMetricData;
+use crate::Glean;
+
+// The maximum number of bytes of a URL.
+// This is the maximum URL length supported by most browsers and servers.
+const MAX_URL_LENGTH: usize = 8192;
+
+/// A URL metric.
+///
+/// Record a URL value. The URL must start with a valid scheme,
+/// `data:` URLs are not supported.
+/// URLs are length-limited to `MAX_URL_LENGTH` bytes.
+#[derive(Clone, Debug)]
+pub struct UrlMetric {
+    meta: CommonMetricData,
+}
+
+impl MetricType for UrlMetric {
+    fn meta(&self) -> &CommonMetricData {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut CommonMetricData {
+        &mut self.meta
+    }
+}
+
+/// Whether a URL starts with a valid scheme, as defined by RFC 3986:
+/// a letter followed by letters, digits, `+`, `-` or `.`, and a colon.
+fn has_valid_scheme(url: &str) -> bool {
+    let scheme = match url.find(':') {
+        Some(end) => &url[..end],
+        None => return false,
+    };
+
+    let mut chars = scheme.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        _ => false,
+    }
+}
+
+impl UrlMetric {
+    /// Create a new URL metric.
+    pub fn new(meta: CommonMetricData) -> Self {
+        Self { meta }
+    }
+
+    /// Set to the specified value.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance this metric belongs to.
+    /// * `value` - The URL to set the metric to.
+    ///
+    /// ## Notes
+    ///
+    /// Records an `InvalidValue` error and doesn't set the value
+    /// if the URL has no valid scheme or is a `data:` URL.
+    /// Truncates the value if it is longer than `MAX_URL_LENGTH` bytes
+    /// and records an `InvalidOverflow` error.
+    pub fn set<S: Into<String>>(&self, glean: &Glean, value: S) {
+        if !self.should_record(glean) {
+            return;
+        }
+
+        let mut s = value.into();
+        if !has_valid_scheme(&s) {
+            let msg = format!(
+                "URL of length {} does not start with a valid scheme",
+                s.len()
+            );
+            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            return;
+        }
+
+        if s.get(..5)
+            .map_or(false, |p| p.eq_ignore_ascii_case("data:"))
+        {
+            let msg = "URL metric does not support data URLs".to_string();
+            record_error(glean, &self.meta, ErrorType::InvalidValue, msg, None);
+            return;
+        }
+
+        if s.len() > MAX_URL_LENGTH {
+            let msg = format!(
+                "Value length {} exceeds maximum of {}",
+                s.len(),
+                MAX_URL_LENGTH
+            );
+            record_error(glean, &self.meta, ErrorType::InvalidOverflow, msg, None);
+            s = truncate_string_at_boundary(s, MAX_URL_LENGTH);
+        }
+
+        let value = Metric::Url(s);
+        glean.storage().record(glean, &self.meta, &value)
+    }
+
+    /// **Test-only API (exported for FFI purposes).**
+    ///
+    /// Get the currently stored value as a string.
+    ///
+    /// This doesn't clear the stored value.
+    pub fn test_get_value(&self, glean: &Glean, storage_name: &str) -> Option<String> {
+        match StorageManager.snapshot_metric(
+            glean.storage(),
+            storage_name,
+            &self.meta.identifier(glean),
+        ) {
+            Some(Metric::Url(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validates_schemes() {
+        assert!(has_valid_scheme("https://example.com"));
+        assert!(has_valid_scheme("moz-extension://uuid/page.html"));
+        assert!(has_valid_scheme("svn+ssh://example.com"));
+        assert!(has_valid_scheme("about:blank"));
+
+        assert!(!has_valid_scheme("example.com"));
+        assert!(!has_valid_scheme("://example.com"));
+        assert!(!has_valid_scheme("1http://example.com"));
+        assert!(!has_valid_scheme("ht tp://example.com"));
+        assert!(!has_valid_scheme("é:"));
+    }
+}
@@ -4,8 +4,7 @@
 
 //! Ping collection, assembly & submission.
 
-use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 
 use log::info;
@@ -14,6 +13,7 @@ use serde_json::{json, Value as JsonValue};
 use crate::common_metric_data::{CommonMetricData, Lifetime};
 use crate::metrics::{CounterMetric, DatetimeMetric, Metric, MetricType, PingType, TimeUnit};
 use crate::storage::StorageManager;
+use crate::upload::{write_ping_file, PingMetadata};
 use crate::util::{get_iso_time_string, local_now_with_offset};
 use crate::{
     Glean, Result, DELETION_REQUEST_PINGS_DIRECTORY, INTERNAL_STORAGE, PENDING_PINGS_DIRECTORY,
@@ -179,7 +179,10 @@ impl PingMaker {
     ) -> Option<JsonValue> {
         info!("Collecting {}", ping.name);
 
-        let metrics_data = StorageManager.snapshot_as_json(glean.storage(), &ping.name, true);
+        let mut metrics_data = StorageManager.snapshot_as_json(glean.storage(), &ping.name, true);
+        if !ping.include_text {
+            metrics_data = metrics_data.and_then(|data| Self::remove_text(&ping.name, data));
+        }
         let events_data = glean.event_storage().snapshot_as_json(&ping.name, true);
 
         let is_empty = metrics_data.is_none() && events_data.is_none();
@@ -208,6 +211,31 @@ impl PingMaker {
         Some(json)
     }
 
+    /// Remove the text metrics from the metrics of a ping that doesn't include them.
+    ///
+    /// Text metrics refuse to record for such pings once they are registered,
+    /// so this only catches text recorded before the ping was registered.
+    ///
+    /// ## Return value
+    ///
+    /// The remaining metrics, or `None` if there are none left.
+    fn remove_text(ping_name: &str, mut metrics_data: JsonValue) -> Option<JsonValue> {
+        let sections = metrics_data.as_object_mut()?;
+        if let Some(text) = sections.remove("text") {
+            log::warn!(
+                "Dropping text metrics from the {} ping, which doesn't include text: {:?}",
+                ping_name,
+                text.as_object().map(|text| text.keys().collect::<Vec<_>>())
+            );
+        }
+
+        if sections.is_empty() {
+            None
+        } else {
+            Some(metrics_data)
+        }
+    }
+
     /// Collect a snapshot for the given ping from storage and attach required meta information,
     /// returning it as a string containing JSON.
     ///
@@ -266,6 +294,7 @@ impl PingMaker {
         data_path: &Path,
         url_path: &str,
         ping_content: &JsonValue,
+        metadata: &PingMetadata,
     ) -> std::io::Result<()> {
         let pings_dir = self.get_pings_dir(data_path, Some(ping_name))?;
         let temp_dir = self.get_tmp_dir(data_path)?;
@@ -277,12 +306,7 @@ impl PingMaker {
 
         log::debug!("Storing ping '{}' at '{}'", doc_id, ping_path.display());
 
-        {
-            let mut file = File::create(&temp_ping_path)?;
-            file.write_all(url_path.as_bytes())?;
-            file.write_all(b"\n")?;
-            file.write_all(::serde_json::to_string(ping_content)?.as_bytes())?;
-        }
+        write_ping_file(&temp_ping_path, url_path, ping_content, metadata)?;
 
         if let Err(e) = std::fs::rename(&temp_ping_path, &ping_path) {
             log::warn!(
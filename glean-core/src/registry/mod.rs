@@ -0,0 +1,1324 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Metric and ping definitions loaded from `metrics.yaml` and `pings.yaml` files.
+//!
+//! The files follow the schemas used by [`glean_parser`](https://mozilla.github.io/glean_parser/).
+//! Only the fields needed at runtime are kept, the other fields are only checked for presence.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::error::ErrorKind;
+use crate::metrics::*;
+use crate::CommonMetricData;
+use crate::Lifetime;
+use crate::Result;
+
+/// The prefix of the `$schema` of `metrics.yaml` files.
+const METRICS_SCHEMA: &str = "moz://mozilla.org/schemas/glean/metrics/";
+
+/// The prefix of the `$schema` of `pings.yaml` files.
+const PINGS_SCHEMA: &str = "moz://mozilla.org/schemas/glean/pings/";
+
+/// The placeholder for the default ping of a metric in `send_in_pings`.
+const DEFAULT_PING: &str = "default";
+
+/// The category of Glean's own metrics, which are recorded without a category.
+const INTERNAL_CATEGORY: &str = "glean.internal.metrics";
+
+/// The definitions of the metrics collected by Glean itself.
+pub const GLEAN_METRICS: &str = include_str!("../../metrics.yaml");
+
+/// The definitions of the pings built into Glean.
+pub const GLEAN_PINGS: &str = include_str!("../../pings.yaml");
+
+/// The type of a metric, as named in the `type` field of its definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// A boolean metric.
+    Boolean,
+    /// A counter metric.
+    Counter,
+    /// A quantity metric.
+    Quantity,
+    /// A string metric.
+    String,
+    /// A string list metric.
+    StringList,
+    /// A UUID metric.
+    Uuid,
+    /// A datetime metric.
+    Datetime,
+    /// A timespan metric.
+    Timespan,
+    /// A timing distribution metric.
+    TimingDistribution,
+    /// A memory distribution metric.
+    MemoryDistribution,
+    /// A custom distribution metric.
+    CustomDistribution,
+    /// An event metric.
+    Event,
+    /// A rate metric, or a numerator if it has a `denominator_metric`.
+    Rate,
+    /// A text metric.
+    Text,
+    /// A URL metric.
+    Url,
+    /// A JWE metric.
+    Jwe,
+    /// An object metric.
+    Object,
+    /// A labeled boolean metric.
+    LabeledBoolean,
+    /// A labeled counter metric.
+    LabeledCounter,
+    /// A labeled string metric.
+    LabeledString,
+    /// A labeled quantity metric.
+    LabeledQuantity,
+    /// A labeled timespan metric.
+    LabeledTimespan,
+    /// A labeled timing distribution metric.
+    LabeledTimingDistribution,
+    /// A labeled memory distribution metric.
+    LabeledMemoryDistribution,
+    /// A labeled custom distribution metric.
+    LabeledCustomDistribution,
+}
+
+impl MetricKind {
+    /// The type named in the `type` field of a definition, if it is known.
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        let kind = match name {
+            "boolean" => MetricKind::Boolean,
+            "counter" => MetricKind::Counter,
+            "quantity" => MetricKind::Quantity,
+            "string" => MetricKind::String,
+            "string_list" => MetricKind::StringList,
+            "uuid" => MetricKind::Uuid,
+            "datetime" => MetricKind::Datetime,
+            "timespan" => MetricKind::Timespan,
+            "timing_distribution" => MetricKind::TimingDistribution,
+            "memory_distribution" => MetricKind::MemoryDistribution,
+            "custom_distribution" => MetricKind::CustomDistribution,
+            "event" => MetricKind::Event,
+            "rate" => MetricKind::Rate,
+            "text" => MetricKind::Text,
+            "url" => MetricKind::Url,
+            "jwe" => MetricKind::Jwe,
+            "object" => MetricKind::Object,
+            "labeled_boolean" => MetricKind::LabeledBoolean,
+            "labeled_counter" => MetricKind::LabeledCounter,
+            "labeled_string" => MetricKind::LabeledString,
+            "labeled_quantity" => MetricKind::LabeledQuantity,
+            "labeled_timespan" => MetricKind::LabeledTimespan,
+            "labeled_timing_distribution" => MetricKind::LabeledTimingDistribution,
+            "labeled_memory_distribution" => MetricKind::LabeledMemoryDistribution,
+            "labeled_custom_distribution" => MetricKind::LabeledCustomDistribution,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    fn is_labeled(self) -> bool {
+        match self {
+            MetricKind::LabeledBoolean
+            | MetricKind::LabeledCounter
+            | MetricKind::LabeledString
+            | MetricKind::LabeledQuantity
+            | MetricKind::LabeledTimespan
+            | MetricKind::LabeledTimingDistribution
+            | MetricKind::LabeledMemoryDistribution
+            | MetricKind::LabeledCustomDistribution => true,
+            _ => false,
+        }
+    }
+}
+
+/// A metric definition, as read from a `metrics.yaml` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDefinition {
+    /// The metric's category.
+    pub category: String,
+    /// The metric's name.
+    pub name: String,
+    /// The metric's type.
+    pub kind: MetricKind,
+    /// The metric's lifetime.
+    pub lifetime: Lifetime,
+    /// The pings the metric is sent in. The `default` placeholder is resolved.
+    pub send_in_pings: Vec<String>,
+    /// Whether the metric is disabled.
+    pub disabled: bool,
+    /// The metric's description.
+    pub description: String,
+    /// When the metric expires: `never`, `expired` or a date.
+    pub expires: String,
+    /// The time unit of datetime, timespan and timing distribution metrics
+    /// and of their labeled variants.
+    pub time_unit: Option<TimeUnit>,
+    /// The memory unit of (labeled) memory distribution metrics.
+    pub memory_unit: Option<MemoryUnit>,
+    /// The bucketing of (labeled) custom distribution metrics:
+    /// `(range_min, range_max, bucket_count, histogram_type)`.
+    pub custom_buckets: Option<(u64, u64, u64, HistogramType)>,
+    /// The static labels of labeled metrics, if any.
+    pub labels: Option<Vec<String>>,
+    /// The allowed extra keys of event metrics.
+    pub extra_keys: Vec<String>,
+    /// The schema of object metrics, read from their `structure`.
+    pub object_schema: Option<ObjectSchema>,
+    /// The identifier of the counter a rate metric shares its denominator with.
+    pub denominator_metric: Option<String>,
+    /// The metadata of the rate metrics using a counter as their `denominator_metric`.
+    ///
+    /// This is filled in by the registry. A counter with numerators is built as a denominator.
+    pub numerators: Vec<CommonMetricData>,
+}
+
+/// A metric instance built from its definition.
+#[derive(Debug)]
+pub enum RegisteredMetric {
+    /// A boolean metric.
+    Boolean(BooleanMetric),
+    /// A counter metric.
+    Counter(CounterMetric),
+    /// A quantity metric.
+    Quantity(QuantityMetric),
+    /// A string metric.
+    String(StringMetric),
+    /// A string list metric.
+    StringList(StringListMetric),
+    /// A UUID metric.
+    Uuid(UuidMetric),
+    /// A datetime metric.
+    Datetime(DatetimeMetric),
+    /// A timespan metric.
+    Timespan(TimespanMetric),
+    /// A timing distribution metric.
+    TimingDistribution(TimingDistributionMetric),
+    /// A memory distribution metric.
+    MemoryDistribution(MemoryDistributionMetric),
+    /// A custom distribution metric.
+    CustomDistribution(CustomDistributionMetric),
+    /// An event metric.
+    Event(EventMetric),
+    /// A rate metric.
+    Rate(RateMetric),
+    /// A rate metric with a `denominator_metric`.
+    Numerator(NumeratorMetric),
+    /// A counter metric used as `denominator_metric` by rate metrics.
+    Denominator(DenominatorMetric),
+    /// A text metric.
+    Text(TextMetric),
+    /// A URL metric.
+    Url(UrlMetric),
+    /// A JWE metric.
+    Jwe(JweMetric),
+    /// An object metric.
+    Object(ObjectMetric),
+    /// A labeled boolean metric.
+    LabeledBoolean(LabeledMetric<BooleanMetric>),
+    /// A labeled counter metric.
+    LabeledCounter(LabeledMetric<CounterMetric>),
+    /// A labeled string metric.
+    LabeledString(LabeledMetric<StringMetric>),
+    /// A labeled quantity metric.
+    LabeledQuantity(LabeledMetric<QuantityMetric>),
+    /// A labeled timespan metric.
+    LabeledTimespan(LabeledMetric<TimespanMetric>),
+    /// A labeled timing distribution metric.
+    LabeledTimingDistribution(LabeledMetric<TimingDistributionMetric>),
+    /// A labeled memory distribution metric.
+    LabeledMemoryDistribution(LabeledMetric<MemoryDistributionMetric>),
+    /// A labeled custom distribution metric.
+    LabeledCustomDistribution(LabeledMetric<CustomDistributionMetric>),
+}
+
+impl MetricDefinition {
+    /// The metric's identifier, `category.name`.
+    pub fn identifier(&self) -> String {
+        if self.category.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.category, self.name)
+        }
+    }
+
+    /// The common metric data of the metric.
+    ///
+    /// Glean's own metrics, in the `glean.internal.metrics` category,
+    /// are recorded without a category, as `glean_parser` does.
+    pub fn common_metric_data(&self) -> CommonMetricData {
+        let category = if self.category == INTERNAL_CATEGORY {
+            String::new()
+        } else {
+            self.category.clone()
+        };
+        CommonMetricData {
+            name: self.name.clone(),
+            category,
+            send_in_pings: self.send_in_pings.clone(),
+            lifetime: self.lifetime,
+            disabled: self.disabled,
+            dynamic_label: None,
+        }
+    }
+
+    /// Build an instance of the metric's type.
+    pub fn build(&self) -> RegisteredMetric {
+        let meta = self.common_metric_data();
+        let time_unit = self.time_unit.unwrap_or(TimeUnit::Millisecond);
+        let memory_unit = self.memory_unit.unwrap_or(MemoryUnit::Byte);
+        let custom_distribution = |meta| {
+            let (range_min, range_max, bucket_count, histogram_type) = self
+                .custom_buckets
+                .expect("Custom distribution definitions are validated to have buckets");
+            CustomDistributionMetric::new(meta, range_min, range_max, bucket_count, histogram_type)
+        };
+        match self.kind {
+            MetricKind::Boolean => RegisteredMetric::Boolean(BooleanMetric::new(meta)),
+            MetricKind::Counter if !self.numerators.is_empty() => {
+                let numerators = self
+                    .numerators
+                    .iter()
+                    .map(|numerator| NumeratorMetric::new(numerator.clone()))
+                    .collect();
+                RegisteredMetric::Denominator(DenominatorMetric::new(meta, numerators))
+            }
+            MetricKind::Counter => RegisteredMetric::Counter(CounterMetric::new(meta)),
+            MetricKind::Quantity => RegisteredMetric::Quantity(QuantityMetric::new(meta)),
+            MetricKind::String => RegisteredMetric::String(StringMetric::new(meta)),
+            MetricKind::StringList => RegisteredMetric::StringList(StringListMetric::new(meta)),
+            MetricKind::Uuid => RegisteredMetric::Uuid(UuidMetric::new(meta)),
+            MetricKind::Datetime => {
+                RegisteredMetric::Datetime(DatetimeMetric::new(meta, time_unit))
+            }
+            MetricKind::Timespan => {
+                RegisteredMetric::Timespan(TimespanMetric::new(meta, time_unit))
+            }
+            MetricKind::TimingDistribution => {
+                RegisteredMetric::TimingDistribution(TimingDistributionMetric::new(meta, time_unit))
+            }
+            MetricKind::MemoryDistribution => RegisteredMetric::MemoryDistribution(
+                MemoryDistributionMetric::new(meta, memory_unit),
+            ),
+            MetricKind::CustomDistribution => {
+                RegisteredMetric::CustomDistribution(custom_distribution(meta))
+            }
+            MetricKind::Event => {
+                RegisteredMetric::Event(EventMetric::new(meta, self.extra_keys.clone()))
+            }
+            MetricKind::Rate if self.denominator_metric.is_some() => {
+                RegisteredMetric::Numerator(NumeratorMetric::new(meta))
+            }
+            MetricKind::Rate => RegisteredMetric::Rate(RateMetric::new(meta)),
+            MetricKind::Text => RegisteredMetric::Text(TextMetric::new(meta)),
+            MetricKind::Url => RegisteredMetric::Url(UrlMetric::new(meta)),
+            MetricKind::Jwe => RegisteredMetric::Jwe(JweMetric::new(meta)),
+            MetricKind::Object => RegisteredMetric::Object(ObjectMetric::new(
+                meta,
+                self.object_schema
+                    .clone()
+                    .expect("Object definitions are validated to have a structure"),
+            )),
+            MetricKind::LabeledBoolean => RegisteredMetric::LabeledBoolean(LabeledMetric::new(
+                BooleanMetric::new(meta),
+                self.labels.clone(),
+            )),
+            MetricKind::LabeledCounter => RegisteredMetric::LabeledCounter(LabeledMetric::new(
+                CounterMetric::new(meta),
+                self.labels.clone(),
+            )),
+            MetricKind::LabeledString => RegisteredMetric::LabeledString(LabeledMetric::new(
+                StringMetric::new(meta),
+                self.labels.clone(),
+            )),
+            MetricKind::LabeledQuantity => RegisteredMetric::LabeledQuantity(LabeledMetric::new(
+                QuantityMetric::new(meta),
+                self.labels.clone(),
+            )),
+            MetricKind::LabeledTimespan => RegisteredMetric::LabeledTimespan(LabeledMetric::new(
+                TimespanMetric::new(meta, time_unit),
+                self.labels.clone(),
+            )),
+            MetricKind::LabeledTimingDistribution => {
+                RegisteredMetric::LabeledTimingDistribution(LabeledMetric::new(
+                    TimingDistributionMetric::new(meta, time_unit),
+                    self.labels.clone(),
+                ))
+            }
+            MetricKind::LabeledMemoryDistribution => {
+                RegisteredMetric::LabeledMemoryDistribution(LabeledMetric::new(
+                    MemoryDistributionMetric::new(meta, memory_unit),
+                    self.labels.clone(),
+                ))
+            }
+            MetricKind::LabeledCustomDistribution => RegisteredMetric::LabeledCustomDistribution(
+                LabeledMetric::new(custom_distribution(meta), self.labels.clone()),
+            ),
+        }
+    }
+}
+
+/// A ping definition, as read from a `pings.yaml` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingDefinition {
+    /// The ping's name.
+    pub name: String,
+    /// The ping's description.
+    pub description: String,
+    /// Whether the ping includes the client id.
+    pub include_client_id: bool,
+    /// Whether the ping is sent even if it contains no metrics.
+    pub send_if_empty: bool,
+    /// The reasons the ping can be submitted for.
+    pub reason_codes: Vec<String>,
+    /// Whether the ping includes text metrics.
+    pub include_text: bool,
+}
+
+impl PingDefinition {
+    /// Build the ping type.
+    pub fn build(&self) -> PingType {
+        let mut ping = PingType::new(
+            self.name.clone(),
+            self.include_client_id,
+            self.send_if_empty,
+            self.reason_codes.clone(),
+        );
+        ping.include_text = self.include_text;
+        ping
+    }
+}
+
+/// The fields of a definition, read one at a time.
+struct Fields(Map<String, JsonValue>);
+
+impl Fields {
+    fn new(definition: JsonValue) -> std::result::Result<Self, String> {
+        match definition {
+            JsonValue::Object(fields) => Ok(Fields(fields)),
+            other => Err(format!("the definition is not a mapping: {}", other)),
+        }
+    }
+
+    /// Take an optional field. A `null` value counts as missing.
+    fn optional<T: DeserializeOwned>(
+        &mut self,
+        name: &str,
+    ) -> std::result::Result<Option<T>, String> {
+        match self.0.remove(name) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| format!("invalid {}: {}", name, e)),
+        }
+    }
+
+    /// Take a required field.
+    fn required<T: DeserializeOwned>(&mut self, name: &str) -> std::result::Result<T, String> {
+        self.optional(name)?
+            .ok_or_else(|| format!("missing field `{}`", name))
+    }
+}
+
+/// The fields of a metric definition in a `metrics.yaml` file.
+struct RawMetric {
+    kind: MetricKind,
+    description: String,
+    bugs: Vec<JsonValue>,
+    data_reviews: Vec<JsonValue>,
+    notification_emails: Vec<String>,
+    expires: JsonValue,
+    lifetime: Option<String>,
+    send_in_pings: Option<Vec<String>>,
+    disabled: bool,
+    time_unit: Option<TimeUnit>,
+    memory_unit: Option<MemoryUnit>,
+    range_min: Option<u64>,
+    range_max: Option<u64>,
+    bucket_count: Option<u64>,
+    histogram_type: Option<HistogramType>,
+    labels: Option<Vec<String>>,
+    extra_keys: Option<BTreeMap<String, JsonValue>>,
+    structure: Option<JsonValue>,
+    denominator_metric: Option<String>,
+}
+
+impl RawMetric {
+    fn new(definition: JsonValue) -> std::result::Result<Self, String> {
+        let mut fields = Fields::new(definition)?;
+        let kind: String = fields.required("type")?;
+        Ok(RawMetric {
+            kind: MetricKind::from_type_name(&kind)
+                .ok_or_else(|| format!("unknown type '{}'", kind))?,
+            description: fields.required("description")?,
+            bugs: fields.required("bugs")?,
+            data_reviews: fields.required("data_reviews")?,
+            notification_emails: fields.required("notification_emails")?,
+            expires: fields.required("expires")?,
+            lifetime: fields.optional("lifetime")?,
+            send_in_pings: fields.optional("send_in_pings")?,
+            disabled: fields.optional("disabled")?.unwrap_or(false),
+            time_unit: fields.optional("time_unit")?,
+            memory_unit: fields.optional("memory_unit")?,
+            range_min: fields.optional("range_min")?,
+            range_max: fields.optional("range_max")?,
+            bucket_count: fields.optional("bucket_count")?,
+            histogram_type: fields.optional("histogram_type")?,
+            labels: fields.optional("labels")?,
+            extra_keys: fields.optional("extra_keys")?,
+            structure: fields.optional("structure")?,
+            denominator_metric: fields.optional("denominator_metric")?,
+        })
+    }
+}
+
+/// The fields of a ping definition in a `pings.yaml` file.
+struct RawPing {
+    description: String,
+    include_client_id: bool,
+    send_if_empty: bool,
+    include_text: bool,
+    reasons: Option<BTreeMap<String, String>>,
+    bugs: Vec<JsonValue>,
+    data_reviews: Vec<JsonValue>,
+    notification_emails: Vec<String>,
+}
+
+impl RawPing {
+    fn new(definition: JsonValue) -> std::result::Result<Self, String> {
+        let mut fields = Fields::new(definition)?;
+        Ok(RawPing {
+            description: fields.required("description")?,
+            include_client_id: fields.required("include_client_id")?,
+            send_if_empty: fields.optional("send_if_empty")?.unwrap_or(false),
+            include_text: fields.optional("include_text")?.unwrap_or(false),
+            reasons: fields.optional("reasons")?,
+            bugs: fields.required("bugs")?,
+            data_reviews: fields.required("data_reviews")?,
+            notification_emails: fields.required("notification_emails")?,
+        })
+    }
+}
+
+/// A registry of metric and ping definitions.
+///
+/// Definitions are loaded from `metrics.yaml` and `pings.yaml` files
+/// and validated when they are loaded.
+/// A file with an invalid definition is rejected as a whole.
+///
+/// ## Example
+///
+/// ```rust
+/// # use glean_core::registry::{MetricRegistry, RegisteredMetric};
+/// let mut registry = MetricRegistry::new();
+/// registry.load_metrics(r#"
+/// $schema: moz://mozilla.org/schemas/glean/metrics/1-0-0
+/// browser:
+///   page_loads:
+///     type: counter
+///     description: The number of loaded pages.
+///     bugs:
+///       - https://bugzilla.mozilla.org/123456789
+///     data_reviews:
+///       - https://bugzilla.mozilla.org/123456789
+///     notification_emails:
+///       - nobody@example.com
+///     expires: never
+/// "#).unwrap();
+///
+/// let definition = registry.metric("browser.page_loads").unwrap();
+/// assert_eq!(vec!["metrics".to_string()], definition.send_in_pings);
+/// match definition.build() {
+///     RegisteredMetric::Counter(_) => {}
+///     _ => panic!("Expected a counter metric"),
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MetricRegistry {
+    metrics: BTreeMap<String, MetricDefinition>,
+    pings: BTreeMap<String, PingDefinition>,
+}
+
+impl MetricRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the metric definitions of a `metrics.yaml` document.
+    ///
+    /// ## Arguments
+    ///
+    /// * `source` - the content of the `metrics.yaml` file.
+    ///
+    /// ## Return value
+    ///
+    /// An error if the document or any definition in it is invalid,
+    /// or if a metric is already registered. Nothing is registered in that case.
+    pub fn load_metrics(&mut self, source: &str) -> Result<()> {
+        let document = parse_document(source, METRICS_SCHEMA)?;
+
+        let mut metrics = Vec::new();
+        for (category, definitions) in document {
+            if category == "no_lint" {
+                continue;
+            }
+            let definitions = match definitions {
+                JsonValue::Object(definitions) => definitions,
+                _ => return Err(invalid(format!("category '{}' is not a mapping", category))),
+            };
+            for (name, definition) in definitions {
+                let metric = parse_metric(&category, &name, definition)?;
+                let identifier = metric.identifier();
+                if self.metrics.contains_key(&identifier) {
+                    return Err(invalid(format!(
+                        "metric '{}' is already registered",
+                        identifier
+                    )));
+                }
+                metrics.push((identifier, metric));
+            }
+        }
+
+        // A denominator may be defined in a file loaded later, so only the known ones are checked.
+        let lookup = |identifier: &str| {
+            self.metrics.get(identifier).or_else(|| {
+                metrics
+                    .iter()
+                    .find(|(other, _)| other == identifier)
+                    .map(|(_, metric)| metric)
+            })
+        };
+        let all_metrics = self.metrics.values().chain(metrics.iter().map(|(_, m)| m));
+        for metric in all_metrics {
+            if let Some(denominator) = &metric.denominator_metric {
+                match lookup(denominator) {
+                    Some(denominator) if denominator.kind != MetricKind::Counter => {
+                        return Err(invalid(format!(
+                            "metric '{}': denominator_metric '{}' is not a counter",
+                            metric.identifier(),
+                            denominator.identifier()
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.metrics.extend(metrics);
+        self.link_numerators();
+        Ok(())
+    }
+
+    /// Set the numerators of all counters used as `denominator_metric`.
+    fn link_numerators(&mut self) {
+        let mut numerators: BTreeMap<String, Vec<CommonMetricData>> = BTreeMap::new();
+        for metric in self.metrics.values() {
+            if let Some(denominator) = &metric.denominator_metric {
+                numerators
+                    .entry(denominator.clone())
+                    .or_default()
+                    .push(metric.common_metric_data());
+            }
+        }
+        for (identifier, metric) in self.metrics.iter_mut() {
+            metric.numerators = numerators.remove(identifier).unwrap_or_default();
+        }
+    }
+
+    /// Load the ping definitions of a `pings.yaml` document.
+    ///
+    /// ## Arguments
+    ///
+    /// * `source` - the content of the `pings.yaml` file.
+    ///
+    /// ## Return value
+    ///
+    /// An error if the document or any definition in it is invalid,
+    /// or if a ping is already registered. Nothing is registered in that case.
+    pub fn load_pings(&mut self, source: &str) -> Result<()> {
+        let document = parse_document(source, PINGS_SCHEMA)?;
+
+        let mut pings = Vec::new();
+        for (name, definition) in document {
+            if name == "no_lint" {
+                continue;
+            }
+            let ping = parse_ping(&name, definition)?;
+            if self.pings.contains_key(&name) {
+                return Err(invalid(format!("ping '{}' is already registered", name)));
+            }
+            pings.push((name, ping));
+        }
+
+        self.pings.extend(pings);
+        Ok(())
+    }
+
+    /// Load the metric definitions of a `metrics.yaml` file.
+    ///
+    /// See [`load_metrics`](#method.load_metrics).
+    pub fn load_metrics_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.load_metrics(&fs::read_to_string(path)?)
+    }
+
+    /// Load the ping definitions of a `pings.yaml` file.
+    ///
+    /// See [`load_pings`](#method.load_pings).
+    pub fn load_pings_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.load_pings(&fs::read_to_string(path)?)
+    }
+
+    /// Get the definition of a metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `identifier` - the metric's identifier, `category.name`.
+    pub fn metric(&self, identifier: &str) -> Option<&MetricDefinition> {
+        self.metrics.get(identifier)
+    }
+
+    /// Get the definition of a ping.
+    pub fn ping(&self, name: &str) -> Option<&PingDefinition> {
+        self.pings.get(name)
+    }
+
+    /// Build an instance of a metric.
+    ///
+    /// ## Arguments
+    ///
+    /// * `identifier` - the metric's identifier, `category.name`.
+    ///
+    /// ## Return value
+    ///
+    /// The metric, or `None` if no metric with this identifier is registered.
+    pub fn build_metric(&self, identifier: &str) -> Option<RegisteredMetric> {
+        self.metric(identifier).map(MetricDefinition::build)
+    }
+
+    /// All registered metric definitions, ordered by identifier.
+    pub fn metrics(&self) -> impl Iterator<Item = &MetricDefinition> {
+        self.metrics.values()
+    }
+
+    /// All registered ping definitions, ordered by name.
+    pub fn pings(&self) -> impl Iterator<Item = &PingDefinition> {
+        self.pings.values()
+    }
+
+    /// Check that the `denominator_metric` of every rate metric is registered.
+    ///
+    /// A denominator may be defined in a file loaded later,
+    /// so this is checked once all files are loaded.
+    pub fn check_denominators(&self) -> Result<()> {
+        for metric in self.metrics() {
+            if let Some(denominator) = &metric.denominator_metric {
+                if self.metric(denominator).is_none() {
+                    return Err(invalid(format!(
+                        "metric '{}': unknown denominator_metric '{}'",
+                        metric.identifier(),
+                        denominator
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> crate::Error {
+    ErrorKind::InvalidDefinition(message).into()
+}
+
+/// Whether an identifier matches the label format, with or without dots.
+fn is_valid_identifier(identifier: &str, allow_dots: bool) -> bool {
+    is_valid_label(identifier) && (allow_dots || !identifier.contains('.'))
+}
+
+/// Parse a YAML document and check its `$schema`.
+///
+/// ## Return value
+///
+/// The top-level entries of the document, except `$schema`.
+fn parse_document(source: &str, schema: &str) -> Result<Map<String, JsonValue>> {
+    let document = serde_yaml::from_str(source)
+        .map_err(|e| crate::Error::from(ErrorKind::Yaml(e.to_string())))?;
+    let mut document = match document {
+        JsonValue::Object(document) => document,
+        _ => return Err(invalid("the document is not a mapping".into())),
+    };
+
+    match document.remove("$schema") {
+        Some(JsonValue::String(ref s)) if s.starts_with(schema) => Ok(document),
+        Some(other) => Err(invalid(format!("unsupported $schema {}", other))),
+        None => Err(invalid("missing $schema".into())),
+    }
+}
+
+fn parse_metric(category: &str, name: &str, definition: JsonValue) -> Result<MetricDefinition> {
+    let identifier = format!("{}.{}", category, name);
+    let error = |message: String| invalid(format!("metric '{}': {}", identifier, message));
+
+    if !is_valid_identifier(category, true) {
+        return Err(error("invalid category".into()));
+    }
+    if !is_valid_identifier(name, false) {
+        return Err(error("invalid name".into()));
+    }
+
+    let raw = RawMetric::new(definition).map_err(error)?;
+    if raw.description.trim().is_empty() {
+        return Err(error("missing description".into()));
+    }
+    if raw.bugs.is_empty() || raw.data_reviews.is_empty() || raw.notification_emails.is_empty() {
+        return Err(error(
+            "bugs, data_reviews and notification_emails must not be empty".into(),
+        ));
+    }
+    let expires = match raw.expires {
+        JsonValue::String(expires) => expires,
+        other => return Err(error(format!("invalid expires {}", other))),
+    };
+
+    let lifetime = match raw.lifetime.as_deref() {
+        None | Some("ping") => Lifetime::Ping,
+        Some("application") => Lifetime::Application,
+        Some("user") => Lifetime::User,
+        Some("session") => Lifetime::Session,
+        Some(other) => return Err(error(format!("invalid lifetime '{}'", other))),
+    };
+
+    let default_ping = if raw.kind == MetricKind::Event {
+        "events"
+    } else {
+        "metrics"
+    };
+    let send_in_pings: Vec<String> = raw
+        .send_in_pings
+        .unwrap_or_else(|| vec![DEFAULT_PING.into()])
+        .into_iter()
+        .map(|ping| {
+            if ping == DEFAULT_PING {
+                default_ping.into()
+            } else {
+                ping
+            }
+        })
+        .collect();
+    if send_in_pings.is_empty() {
+        return Err(error("send_in_pings must not be empty".into()));
+    }
+    if let Some(ping) = send_in_pings
+        .iter()
+        .find(|ping| !is_valid_identifier(ping, false))
+    {
+        return Err(error(format!("invalid ping name '{}'", ping)));
+    }
+
+    if raw.labels.is_some() && !raw.kind.is_labeled() {
+        return Err(error("only labeled metrics can have labels".into()));
+    }
+    if let Some(labels) = &raw.labels {
+        if labels.is_empty() || labels.len() > MAX_LABELS {
+            return Err(error(format!(
+                "labeled metrics must have between 1 and {} labels",
+                MAX_LABELS
+            )));
+        }
+        if let Some(label) = labels.iter().find(|label| !is_valid_label(label)) {
+            return Err(error(format!("invalid label '{}'", label)));
+        }
+    }
+
+    if raw.extra_keys.is_some() && raw.kind != MetricKind::Event {
+        return Err(error("only events can have extra keys".into()));
+    }
+    let extra_keys: Vec<String> = raw
+        .extra_keys
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    if let Some(key) = extra_keys.iter().find(|key| !is_valid_label(key)) {
+        return Err(error(format!("invalid extra key '{}'", key)));
+    }
+
+    let time_unit = match raw.kind {
+        MetricKind::TimingDistribution | MetricKind::LabeledTimingDistribution => {
+            Some(raw.time_unit.unwrap_or(TimeUnit::Nanosecond))
+        }
+        MetricKind::Datetime | MetricKind::Timespan | MetricKind::LabeledTimespan => {
+            Some(raw.time_unit.unwrap_or(TimeUnit::Millisecond))
+        }
+        _ => None,
+    };
+    let memory_unit = match raw.kind {
+        MetricKind::MemoryDistribution | MetricKind::LabeledMemoryDistribution => {
+            Some(raw.memory_unit.unwrap_or(MemoryUnit::Byte))
+        }
+        _ => None,
+    };
+    let custom_buckets = match raw.kind {
+        MetricKind::CustomDistribution | MetricKind::LabeledCustomDistribution => {
+            match (raw.range_max, raw.bucket_count, raw.histogram_type) {
+                (Some(range_max), Some(bucket_count), Some(histogram_type)) => Some((
+                    raw.range_min.unwrap_or(1),
+                    range_max,
+                    bucket_count,
+                    histogram_type,
+                )),
+                _ => {
+                    return Err(error(
+                        "custom distributions need range_max, bucket_count and histogram_type"
+                            .into(),
+                    ))
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if raw.structure.is_some() && raw.kind != MetricKind::Object {
+        return Err(error("only objects can have a structure".into()));
+    }
+    let object_schema = match (raw.kind, &raw.structure) {
+        (MetricKind::Object, Some(structure)) => {
+            let schema = parse_structure(structure).map_err(error)?;
+            match schema {
+                ObjectSchema::Array { .. } | ObjectSchema::Object(_) => Some(schema),
+                _ => return Err(error("the structure must be an object or an array".into())),
+            }
+        }
+        (MetricKind::Object, None) => return Err(error("objects need a structure".into())),
+        _ => None,
+    };
+
+    if raw.denominator_metric.is_some() && raw.kind != MetricKind::Rate {
+        return Err(error("only rates can have a denominator_metric".into()));
+    }
+    if let Some(denominator) = &raw.denominator_metric {
+        if !denominator.contains('.') || !is_valid_label(denominator) {
+            return Err(error(format!(
+                "invalid denominator_metric '{}'",
+                denominator
+            )));
+        }
+    }
+
+    Ok(MetricDefinition {
+        category: category.into(),
+        name: name.into(),
+        kind: raw.kind,
+        lifetime,
+        send_in_pings,
+        disabled: raw.disabled,
+        description: raw.description,
+        expires,
+        time_unit,
+        memory_unit,
+        custom_buckets,
+        labels: raw.labels,
+        extra_keys,
+        object_schema,
+        denominator_metric: raw.denominator_metric,
+        numerators: Vec::new(),
+    })
+}
+
+/// Parse the `structure` of an object metric into its schema.
+///
+/// Arrays need the `items` they contain and their `max_length`,
+/// objects the `properties` they allow.
+fn parse_structure(structure: &JsonValue) -> std::result::Result<ObjectSchema, String> {
+    let field = |name: &str| structure.get(name);
+    match field("type").and_then(JsonValue::as_str) {
+        Some("boolean") => Ok(ObjectSchema::Boolean),
+        Some("number") => Ok(ObjectSchema::Number),
+        Some("string") => Ok(ObjectSchema::String),
+        Some("array") => {
+            let items = field("items").ok_or("arrays in the structure need items")?;
+            let max_length = field("max_length")
+                .and_then(JsonValue::as_u64)
+                .ok_or("arrays in the structure need a max_length")?;
+            Ok(ObjectSchema::Array {
+                items: Box::new(parse_structure(items)?),
+                max_length: max_length as usize,
+            })
+        }
+        Some("object") => {
+            let properties = field("properties")
+                .and_then(JsonValue::as_object)
+                .ok_or("objects in the structure need properties")?;
+            properties
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), parse_structure(value)?)))
+                .collect::<std::result::Result<_, String>>()
+                .map(ObjectSchema::Object)
+        }
+        _ => Err(format!("invalid structure {}", structure)),
+    }
+}
+
+fn parse_ping(name: &str, definition: JsonValue) -> Result<PingDefinition> {
+    let error = |message: String| invalid(format!("ping '{}': {}", name, message));
+
+    if !is_valid_identifier(name, false) {
+        return Err(error("invalid name".into()));
+    }
+
+    let raw = RawPing::new(definition).map_err(error)?;
+    if raw.description.trim().is_empty() {
+        return Err(error("missing description".into()));
+    }
+    if raw.bugs.is_empty() || raw.data_reviews.is_empty() || raw.notification_emails.is_empty() {
+        return Err(error(
+            "bugs, data_reviews and notification_emails must not be empty".into(),
+        ));
+    }
+
+    let reason_codes: Vec<String> = raw
+        .reasons
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(reason, _)| reason)
+        .collect();
+    if let Some(reason) = reason_codes
+        .iter()
+        .find(|reason| !is_valid_identifier(reason, false))
+    {
+        return Err(error(format!("invalid reason '{}'", reason)));
+    }
+
+    Ok(PingDefinition {
+        name: name.into(),
+        description: raw.description,
+        include_client_id: raw.include_client_id,
+        send_if_empty: raw.send_if_empty,
+        reason_codes,
+        include_text: raw.include_text,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEADER: &str = "$schema: moz://mozilla.org/schemas/glean/metrics/1-0-0\n";
+
+    fn metric_yaml(category: &str, name: &str, fields: &str) -> String {
+        format!(
+            "{}{}:\n  {}:\n{}    description: A metric.\n    bugs: [1]\n    \
+             data_reviews: [1]\n    notification_emails: [nobody@example.com]\n    \
+             expires: never\n",
+            HEADER, category, name, fields
+        )
+    }
+
+    #[test]
+    fn loads_the_glean_metrics_and_pings() {
+        let mut registry = MetricRegistry::new();
+        registry.load_metrics(GLEAN_METRICS).unwrap();
+        registry.load_pings(GLEAN_PINGS).unwrap();
+
+        let duration = registry.metric("glean.baseline.duration").unwrap();
+        assert_eq!(MetricKind::Timespan, duration.kind);
+        assert_eq!(Some(TimeUnit::Second), duration.time_unit);
+        assert_eq!(vec!["baseline".to_string()], duration.send_in_pings);
+        match registry.build_metric("glean.baseline.duration") {
+            Some(RegisteredMetric::Timespan(_)) => {}
+            _ => panic!("Expected a timespan metric"),
+        }
+
+        let invalid_value = registry.metric("glean.error.invalid_value").unwrap();
+        assert_eq!(MetricKind::LabeledCounter, invalid_value.kind);
+        assert_eq!(vec!["all-pings".to_string()], invalid_value.send_in_pings);
+
+        // Glean's own metrics are recorded without a category.
+        let session_count = registry
+            .metric("glean.internal.metrics.session_count")
+            .unwrap()
+            .common_metric_data();
+        assert_eq!("", session_count.category);
+        assert_eq!(Lifetime::User, session_count.lifetime);
+        assert_eq!(
+            vec!["glean_internal_info".to_string()],
+            session_count.send_in_pings
+        );
+
+        let baseline = registry.ping("baseline").unwrap();
+        assert!(baseline.include_client_id);
+        assert_eq!(
+            vec![
+                "active",
+                "background",
+                "dirty_startup",
+                "foreground",
+                "inactive"
+            ],
+            baseline.reason_codes
+        );
+        assert!(registry.ping("deletion-request").is_some());
+    }
+
+    #[test]
+    fn applies_defaults() {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "timing",
+                "    type: timing_distribution\n",
+            ))
+            .unwrap();
+        registry
+            .load_metrics(&metric_yaml("cat", "event", "    type: event\n"))
+            .unwrap();
+
+        let timing = registry.metric("cat.timing").unwrap();
+        assert_eq!(Lifetime::Ping, timing.lifetime);
+        assert_eq!(vec!["metrics".to_string()], timing.send_in_pings);
+        assert_eq!(Some(TimeUnit::Nanosecond), timing.time_unit);
+        assert!(!timing.disabled);
+
+        let event = registry.metric("cat.event").unwrap();
+        assert_eq!(vec!["events".to_string()], event.send_in_pings);
+    }
+
+    #[test]
+    fn builds_labeled_metrics_and_events() {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "labeled",
+                "    type: labeled_string\n    labels:\n      - a_label\n      - other.label\n",
+            ))
+            .unwrap();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "event",
+                "    type: event\n    extra_keys:\n      key1:\n        description: Key.\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            Some(vec!["a_label".to_string(), "other.label".to_string()]),
+            registry.metric("cat.labeled").unwrap().labels
+        );
+        match registry.build_metric("cat.labeled") {
+            Some(RegisteredMetric::LabeledString(_)) => {}
+            _ => panic!("Expected a labeled string metric"),
+        }
+        assert_eq!(
+            vec!["key1".to_string()],
+            registry.metric("cat.event").unwrap().extra_keys
+        );
+    }
+
+    #[test]
+    fn builds_the_newer_metric_types() {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "labeled_timing",
+                "    type: labeled_timing_distribution\n",
+            ))
+            .unwrap();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "labeled_memory",
+                "    type: labeled_memory_distribution\n    memory_unit: kilobyte\n",
+            ))
+            .unwrap();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "object",
+                "    type: object\n    structure:\n      type: object\n      properties:\n        \
+                 ids:\n          type: array\n          max_length: 3\n          items:\n            \
+                 type: number\n",
+            ))
+            .unwrap();
+        for (name, kind) in &[("text", "text"), ("url", "url"), ("jwe", "jwe")] {
+            registry
+                .load_metrics(&metric_yaml("cat", name, &format!("    type: {}\n", kind)))
+                .unwrap();
+        }
+
+        assert_eq!(
+            Some(TimeUnit::Nanosecond),
+            registry.metric("cat.labeled_timing").unwrap().time_unit
+        );
+        match registry.build_metric("cat.labeled_timing") {
+            Some(RegisteredMetric::LabeledTimingDistribution(_)) => {}
+            _ => panic!("Expected a labeled timing distribution metric"),
+        }
+        assert_eq!(
+            Some(MemoryUnit::Kilobyte),
+            registry.metric("cat.labeled_memory").unwrap().memory_unit
+        );
+
+        let mut properties = std::collections::HashMap::new();
+        properties.insert(
+            "ids".to_string(),
+            ObjectSchema::Array {
+                items: Box::new(ObjectSchema::Number),
+                max_length: 3,
+            },
+        );
+        assert_eq!(
+            Some(ObjectSchema::Object(properties)),
+            registry.metric("cat.object").unwrap().object_schema
+        );
+
+        match registry.build_metric("cat.text") {
+            Some(RegisteredMetric::Text(_)) => {}
+            _ => panic!("Expected a text metric"),
+        }
+        match registry.build_metric("cat.url") {
+            Some(RegisteredMetric::Url(_)) => {}
+            _ => panic!("Expected a URL metric"),
+        }
+        match registry.build_metric("cat.jwe") {
+            Some(RegisteredMetric::Jwe(_)) => {}
+            _ => panic!("Expected a JWE metric"),
+        }
+    }
+
+    #[test]
+    fn links_rates_to_their_denominator() {
+        let mut registry = MetricRegistry::new();
+        registry
+            .load_metrics(&metric_yaml(
+                "cat",
+                "crashes",
+                "    type: rate\n    denominator_metric: cat.sessions\n",
+            ))
+            .unwrap();
+        registry
+            .load_metrics(&metric_yaml("cat", "rate", "    type: rate\n"))
+            .unwrap();
+        assert!(registry.check_denominators().is_err());
+
+        // The denominator is loaded after its numerator.
+        registry
+            .load_metrics(&metric_yaml("cat", "sessions", "    type: counter\n"))
+            .unwrap();
+        assert!(registry.check_denominators().is_ok());
+
+        let crashes = registry.metric("cat.crashes").unwrap();
+        assert_eq!(
+            vec![crashes.common_metric_data()],
+            registry.metric("cat.sessions").unwrap().numerators
+        );
+        match registry.build_metric("cat.sessions") {
+            Some(RegisteredMetric::Denominator(_)) => {}
+            _ => panic!("Expected a denominator metric"),
+        }
+        match registry.build_metric("cat.crashes") {
+            Some(RegisteredMetric::Numerator(_)) => {}
+            _ => panic!("Expected a numerator metric"),
+        }
+        match registry.build_metric("cat.rate") {
+            Some(RegisteredMetric::Rate(_)) => {}
+            _ => panic!("Expected a rate metric"),
+        }
+
+        let not_a_counter = metric_yaml(
+            "cat",
+            "hits",
+            "    type: rate\n    denominator_metric: cat.rate\n",
+        );
+        assert!(registry.load_metrics(&not_a_counter).is_err());
+        assert!(registry.metric("cat.hits").is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let invalid = [
+            metric_yaml("Cat", "name", "    type: counter\n"),
+            metric_yaml("cat", "name.dotted", "    type: counter\n"),
+            metric_yaml("cat", "1name", "    type: counter\n"),
+            metric_yaml("cat", "name", "    type: unknown\n"),
+            metric_yaml("cat", "name", "    type: counter\n    lifetime: forever\n"),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: counter\n    send_in_pings: [Bad]\n",
+            ),
+            metric_yaml("cat", "name", "    type: counter\n    labels: [a]\n"),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: labeled_counter\n    labels: [Bad]\n",
+            ),
+            metric_yaml("cat", "name", "    type: event\n    extra_keys: {Bad: 1}\n"),
+            metric_yaml("cat", "name", "    type: custom_distribution\n"),
+            metric_yaml("cat", "name", "    type: labeled_custom_distribution\n"),
+            metric_yaml("cat", "name", "    type: object\n"),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: object\n    structure:\n      type: string\n",
+            ),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: object\n    structure:\n      type: array\n      items:\n        \
+                 type: string\n",
+            ),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: counter\n    structure:\n      type: object\n      properties: {}\n",
+            ),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: counter\n    denominator_metric: cat.other\n",
+            ),
+            metric_yaml(
+                "cat",
+                "name",
+                "    type: rate\n    denominator_metric: other\n",
+            ),
+            format!("{}cat:\n  name:\n    type: counter\n", HEADER),
+            metric_yaml("cat", "name", "    type: counter\n").replace(HEADER, ""),
+        ];
+
+        for yaml in invalid.iter() {
+            let mut registry = MetricRegistry::new();
+            assert!(registry.load_metrics(yaml).is_err(), "{}", yaml);
+            assert_eq!(0, registry.metrics().count());
+        }
+    }
+
+    #[test]
+    fn rejects_duplicates_without_registering_anything() {
+        let mut registry = MetricRegistry::new();
+        let first = metric_yaml("cat", "first", "    type: counter\n");
+        registry.load_metrics(&first).unwrap();
+
+        let both = format!(
+            "{}  second:\n    type: counter\n    description: A metric.\n    bugs: [1]\n    \
+             data_reviews: [1]\n    notification_emails: [nobody@example.com]\n    \
+             expires: never\n",
+            first
+        );
+        assert!(registry.load_metrics(&both).is_err());
+        assert!(registry.metric("cat.second").is_none());
+    }
+}
@@ -0,0 +1,430 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The `metrics` ping scheduler.
+//!
+//! The `metrics` ping is sent once a day, at 4am local time.
+//! If the application wasn't running at that time, it is sent on the next start.
+//! It is also sent on the first start after an application upgrade.
+//!
+//! The date the ping was last sent and the last seen application version
+//! are stored in `INTERNAL_STORAGE`.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::metrics::{DatetimeMetric, StringMetric, TimeUnit};
+use crate::util::local_now_with_offset;
+use crate::{CommonMetricData, Glean, Lifetime, INTERNAL_STORAGE};
+
+/// The hour of the day (local time) the `metrics` ping is due.
+pub const DUE_HOUR_OF_THE_DAY: u32 = 4;
+
+/// A source of the current local time.
+///
+/// This can be replaced in tests to control when the `metrics` ping is due.
+pub trait Clock: Send + Sync {
+    /// The current local time.
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The system's clock.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        local_now_with_offset()
+    }
+}
+
+/// Runs a task with the Glean object.
+///
+/// Embedders use this to run the scheduled collections
+/// in order with their other API calls, e.g. on a dispatcher.
+pub type GleanRunner = dyn Fn(Box<dyn FnOnce(&Glean) + Send>) + Send + Sync;
+
+/// A scheduled collection.
+struct Timer {
+    /// The reason code the `metrics` ping will be submitted with.
+    reason: &'static str,
+
+    /// The time left until the collection, from the moment it was scheduled.
+    delay: Duration,
+
+    /// Dropping this cancels the collection.
+    _cancel: Sender<()>,
+}
+
+/// Schedules the `metrics` ping.
+pub struct MetricsPingScheduler {
+    clock: Box<dyn Clock>,
+    runner: Box<GleanRunner>,
+    last_sent_time: DatetimeMetric,
+    last_version: StringMetric,
+    timer: Mutex<Option<Timer>>,
+}
+
+impl std::fmt::Debug for MetricsPingScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let timer = self.timer.lock().unwrap();
+        f.debug_struct("MetricsPingScheduler")
+            .field("reason", &timer.as_ref().map(|timer| timer.reason))
+            .field("delay", &timer.as_ref().map(|timer| timer.delay))
+            .finish()
+    }
+}
+
+impl MetricsPingScheduler {
+    /// Create a new scheduler using the system's clock.
+    ///
+    /// ## Arguments
+    ///
+    /// * `runner` - Runs the scheduled collections with the Glean object.
+    pub fn new(
+        runner: impl Fn(Box<dyn FnOnce(&Glean) + Send>) + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        Self::with_clock(runner, SystemClock)
+    }
+
+    /// Create a new scheduler using the given clock.
+    ///
+    /// ## Arguments
+    ///
+    /// * `runner` - Runs the scheduled collections with the Glean object.
+    /// * `clock` - The source of the current local time.
+    pub fn with_clock(
+        runner: impl Fn(Box<dyn FnOnce(&Glean) + Send>) + Send + Sync + 'static,
+        clock: impl Clock + 'static,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            clock: Box::new(clock),
+            runner: Box::new(runner),
+            last_sent_time: DatetimeMetric::new(
+                CommonMetricData {
+                    name: "last_sent_time".into(),
+                    category: "mps".into(),
+                    send_in_pings: vec![INTERNAL_STORAGE.into()],
+                    lifetime: Lifetime::User,
+                    ..Default::default()
+                },
+                TimeUnit::Minute,
+            ),
+            last_version: StringMetric::new(CommonMetricData {
+                name: "last_version_of_app_used".into(),
+                category: "mps".into(),
+                send_in_pings: vec![INTERNAL_STORAGE.into()],
+                lifetime: Lifetime::User,
+                ..Default::default()
+            }),
+            timer: Mutex::new(None),
+        })
+    }
+
+    /// Schedule the `metrics` ping, submitting it right away if it is overdue.
+    ///
+    /// This should be called once on startup, after Glean is initialized.
+    ///
+    /// ## Arguments
+    ///
+    /// * `glean` - The Glean instance to submit the ping from.
+    /// * `app_version` - The current version of the application,
+    ///   used to detect upgrades.
+    pub fn schedule(self: &Arc<Self>, glean: &Glean, app_version: &str) {
+        let now = self.clock.now();
+
+        if self.is_different_version(glean, app_version) {
+            log::info!("The application was upgraded, collecting the metrics ping");
+            self.collect_and_reschedule(glean, now, "upgrade");
+            return;
+        }
+
+        let last_sent_time = self.last_sent_time.get_value(glean, INTERNAL_STORAGE);
+        if let Some(last_sent_time) = last_sent_time {
+            if last_sent_time.with_timezone(now.offset()).date() == now.date() {
+                log::info!("The metrics ping was already sent today, scheduling for tomorrow");
+                self.schedule_collection(now, true, "tomorrow");
+                return;
+            }
+        }
+
+        if now >= due_time_today(now) {
+            log::info!("The metrics ping is overdue, collecting it now");
+            self.collect_and_reschedule(glean, now, "overdue");
+        } else {
+            log::info!("Scheduling the metrics ping for today");
+            self.schedule_collection(now, false, "today");
+        }
+    }
+
+    /// Cancel the scheduled collection, if any.
+    pub fn cancel(&self) {
+        self.timer.lock().unwrap().take();
+    }
+
+    /// Whether the stored application version differs from `app_version`.
+    ///
+    /// This stores `app_version` as the last seen version.
+    /// On the very first start there is no stored version, which counts as different.
+    fn is_different_version(&self, glean: &Glean, app_version: &str) -> bool {
+        let last_version = self.last_version.test_get_value(glean, INTERNAL_STORAGE);
+        if last_version.as_deref() == Some(app_version) {
+            return false;
+        }
+
+        self.last_version.set(glean, app_version);
+        true
+    }
+
+    /// Submit the `metrics` ping, store the time and schedule the next collection.
+    fn collect_and_reschedule(
+        self: &Arc<Self>,
+        glean: &Glean,
+        now: DateTime<FixedOffset>,
+        reason: &'static str,
+    ) {
+        if let Err(e) = glean.submit_ping(&glean.internal_pings.metrics, Some(reason)) {
+            log::error!("Failed to submit the metrics ping: {}", e);
+        }
+        self.last_sent_time.set(glean, Some(now));
+        self.schedule_collection(now, true, "reschedule");
+    }
+
+    /// Start a timer thread collecting the `metrics` ping when it is due.
+    ///
+    /// Any previously scheduled collection is cancelled.
+    fn schedule_collection(
+        self: &Arc<Self>,
+        now: DateTime<FixedOffset>,
+        next_day: bool,
+        reason: &'static str,
+    ) {
+        let delay = duration_until_due(now, next_day);
+        let (cancel, cancelled) = mpsc::channel::<()>();
+        let scheduler = Arc::downgrade(self);
+
+        let spawned = thread::Builder::new()
+            .name("glean.mps".into())
+            .spawn(move || {
+                // Dropping the sender cancels the collection.
+                if let Err(RecvTimeoutError::Timeout) = cancelled.recv_timeout(delay) {
+                    fire(scheduler, reason);
+                }
+            });
+
+        let timer = match spawned {
+            Ok(_) => Some(Timer {
+                reason,
+                delay,
+                _cancel: cancel,
+            }),
+            Err(e) => {
+                log::error!("Failed to spawn the metrics ping scheduler thread: {}", e);
+                None
+            }
+        };
+        *self.timer.lock().unwrap() = timer;
+    }
+}
+
+impl Drop for MetricsPingScheduler {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Hand the due collection to the scheduler's runner.
+fn fire(scheduler: Weak<MetricsPingScheduler>, reason: &'static str) {
+    let scheduler = match scheduler.upgrade() {
+        Some(scheduler) => scheduler,
+        None => return,
+    };
+
+    let task_scheduler = Arc::clone(&scheduler);
+    (scheduler.runner)(Box::new(move |glean| {
+        let now = task_scheduler.clock.now();
+        task_scheduler.collect_and_reschedule(glean, now, reason);
+    }));
+}
+
+/// The time the `metrics` ping is due on the day of `now`.
+fn due_time_today(now: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    now.date().and_hms(DUE_HOUR_OF_THE_DAY, 0, 0)
+}
+
+/// The time left from `now` until the `metrics` ping is due.
+///
+/// ## Arguments
+///
+/// * `now` - The current local time.
+/// * `next_day` - Whether to wait for the due time of the next day,
+///   instead of today's. If today's due time has passed, this is 0.
+fn duration_until_due(now: DateTime<FixedOffset>, next_day: bool) -> Duration {
+    let mut due = due_time_today(now);
+    if next_day {
+        due = due + chrono::Duration::days(1);
+    }
+
+    (due - now)
+        .to_std()
+        .unwrap_or_else(|_| Duration::from_secs(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::new_glean;
+    use chrono::TimeZone;
+
+    struct FakeClock(DateTime<FixedOffset>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<FixedOffset> {
+            self.0
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east(3600)
+            .ymd(2020, 6, 1)
+            .and_hms(hour, minute, 0)
+    }
+
+    fn scheduler_at(now: DateTime<FixedOffset>) -> Arc<MetricsPingScheduler> {
+        MetricsPingScheduler::with_clock(|_task| {}, FakeClock(now))
+    }
+
+    fn scheduled(scheduler: &MetricsPingScheduler) -> Option<(&'static str, Duration)> {
+        scheduler
+            .timer
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|timer| (timer.reason, timer.delay))
+    }
+
+    #[test]
+    fn duration_until_due_is_computed_from_local_time() {
+        assert_eq!(
+            Duration::from_secs(3600 + 30 * 60),
+            duration_until_due(at(2, 30), false)
+        );
+        assert_eq!(Duration::from_secs(0), duration_until_due(at(4, 0), false));
+        assert_eq!(Duration::from_secs(0), duration_until_due(at(13, 0), false));
+        assert_eq!(
+            Duration::from_secs(15 * 3600),
+            duration_until_due(at(13, 0), true)
+        );
+        assert_eq!(
+            Duration::from_secs(25 * 3600),
+            duration_until_due(at(3, 0), true)
+        );
+    }
+
+    #[test]
+    fn first_start_collects_with_upgrade_reason() {
+        let (glean, _t) = new_glean(None);
+        let scheduler = scheduler_at(at(2, 0));
+
+        scheduler.schedule(&glean, "1.0");
+
+        assert_eq!(
+            Some(at(2, 0)),
+            scheduler.last_sent_time.get_value(&glean, INTERNAL_STORAGE)
+        );
+        assert_eq!(
+            Some(("reschedule", Duration::from_secs(26 * 3600))),
+            scheduled(&scheduler)
+        );
+    }
+
+    #[test]
+    fn schedules_today_tomorrow_or_collects_overdue() {
+        let (glean, _t) = new_glean(None);
+        scheduler_at(at(2, 0)).schedule(&glean, "1.0");
+
+        // Already sent today.
+        let scheduler = scheduler_at(at(23, 0));
+        scheduler.schedule(&glean, "1.0");
+        assert_eq!(
+            Some(("tomorrow", Duration::from_secs(5 * 3600))),
+            scheduled(&scheduler)
+        );
+
+        // Not sent yet today, before the due time.
+        let scheduler = scheduler_at(at(2, 0) + chrono::Duration::days(1));
+        scheduler.schedule(&glean, "1.0");
+        assert_eq!(
+            Some(("today", Duration::from_secs(2 * 3600))),
+            scheduled(&scheduler)
+        );
+
+        // Not sent yet today, after the due time.
+        let now = at(5, 0) + chrono::Duration::days(1);
+        let scheduler = scheduler_at(now);
+        scheduler.schedule(&glean, "1.0");
+        assert_eq!(
+            Some(now),
+            scheduler.last_sent_time.get_value(&glean, INTERNAL_STORAGE)
+        );
+        assert_eq!(
+            Some(("reschedule", Duration::from_secs(23 * 3600))),
+            scheduled(&scheduler)
+        );
+    }
+
+    #[test]
+    fn upgrade_collects_even_if_sent_today() {
+        let (glean, _t) = new_glean(None);
+        scheduler_at(at(5, 0)).schedule(&glean, "1.0");
+
+        let now = at(6, 0);
+        let scheduler = scheduler_at(now);
+        scheduler.schedule(&glean, "2.0");
+        assert_eq!(
+            Some(now),
+            scheduler.last_sent_time.get_value(&glean, INTERNAL_STORAGE)
+        );
+        assert_eq!(Some("reschedule"), scheduled(&scheduler).map(|s| s.0));
+    }
+
+    #[test]
+    fn due_collection_is_handed_to_the_runner() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        // Due right away.
+        let scheduler = MetricsPingScheduler::with_clock(
+            move |task| sender.lock().unwrap().send(task).unwrap(),
+            FakeClock(at(3, 59) + chrono::Duration::seconds(59)),
+        );
+        let (glean, _t) = new_glean(None);
+        scheduler.last_version.set(&glean, "1.0");
+
+        scheduler.schedule(&glean, "1.0");
+        assert_eq!(Some("today"), scheduled(&scheduler).map(|s| s.0));
+
+        let task = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        task(&glean);
+        assert!(scheduler
+            .last_sent_time
+            .get_value(&glean, INTERNAL_STORAGE)
+            .is_some());
+        assert_eq!(Some("reschedule"), scheduled(&scheduler).map(|s| s.0));
+    }
+
+    #[test]
+    fn cancel_stops_the_timer() {
+        let (glean, _t) = new_glean(None);
+        let scheduler = scheduler_at(at(2, 0));
+        scheduler.schedule(&glean, "1.0");
+        assert!(scheduled(&scheduler).is_some());
+
+        scheduler.cancel();
+        assert!(scheduled(&scheduler).is_none());
+    }
+}
@@ -97,6 +97,7 @@ impl StorageManager {
         storage.iter_store_from(Lifetime::Ping, &store_name, None, &mut snapshotter);
         storage.iter_store_from(Lifetime::Application, &store_name, None, &mut snapshotter);
         storage.iter_store_from(Lifetime::User, &store_name, None, &mut snapshotter);
+        storage.iter_store_from(Lifetime::Session, &store_name, None, &mut snapshotter);
 
         if clear_store {
             if let Err(e) = storage.clear_ping_lifetime_storage(store_name) {
@@ -142,6 +143,7 @@ impl StorageManager {
         storage.iter_store_from(Lifetime::Ping, &store_name, None, &mut snapshotter);
         storage.iter_store_from(Lifetime::Application, &store_name, None, &mut snapshotter);
         storage.iter_store_from(Lifetime::User, &store_name, None, &mut snapshotter);
+        storage.iter_store_from(Lifetime::Session, &store_name, None, &mut snapshotter);
 
         snapshot
     }
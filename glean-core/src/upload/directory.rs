@@ -4,17 +4,132 @@
 
 //! Pings directory processing utilities.
 
-use std::cmp::Ordering;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader};
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
+use chrono::prelude::{DateTime, FixedOffset, Local};
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
-use super::PingRequest;
+use super::policy::{PendingPing, PendingPingsUsage};
+use super::{PingRequest, PingStorageQuota};
+use crate::util::local_now_with_offset;
 use crate::{DELETION_REQUEST_PINGS_DIRECTORY, PENDING_PINGS_DIRECTORY};
 
+/// The version of the pending ping file format written by this version of Glean.
+///
+/// * Version 0 files have two lines: the URL path and the JSON body of the ping.
+/// * Version 1 files add a third line: the [`PingMetadata`](struct.PingMetadata.html) as JSON.
+pub const PING_FILE_VERSION: u32 = 1;
+
+/// Metadata about a pending ping, persisted alongside it in the ping file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PingMetadata {
+    /// The version of the file format this metadata was written with.
+    pub version: u32,
+    /// The number of failed upload attempts so far.
+    #[serde(default)]
+    pub attempts: u32,
+    /// The time the ping was first stored to disk.
+    pub first_queued: DateTime<FixedOffset>,
+    /// The last error encountered while uploading the ping, if any.
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// The debug view tag set when the ping was submitted, if any.
+    #[serde(default)]
+    pub debug_view_tag: Option<String>,
+    /// The source tags set when the ping was submitted, if any.
+    #[serde(default)]
+    pub source_tags: Option<Vec<String>>,
+}
+
+impl PingMetadata {
+    /// Creates the metadata for a ping that is stored for the first time.
+    pub fn new() -> Self {
+        Self {
+            version: PING_FILE_VERSION,
+            attempts: 0,
+            first_queued: local_now_with_offset(),
+            last_error: None,
+            debug_view_tag: None,
+            source_tags: None,
+        }
+    }
+
+    /// Creates the request to upload the ping stored with this metadata.
+    fn to_request(&self, document_id: &str, path: &str, body: JsonValue) -> PingRequest {
+        PingRequest::new(document_id, path, body)
+            .with_debug_headers(self.debug_view_tag.as_deref(), self.source_tags.as_deref())
+    }
+
+    /// Creates the metadata for a version 0 ping file, which doesn't persist any.
+    ///
+    /// The file's modification time is the best guess for when it was first stored.
+    fn from_legacy_file(path: &Path) -> Self {
+        let first_queued = fs::metadata(path)
+            .and_then(|data| data.modified())
+            .map(|modified| {
+                let modified: DateTime<Local> = modified.into();
+                modified.with_timezone(modified.offset())
+            })
+            .unwrap_or_else(|_| local_now_with_offset());
+
+        Self {
+            version: 0,
+            attempts: 0,
+            first_queued,
+            last_error: None,
+            debug_view_tag: None,
+            source_tags: None,
+        }
+    }
+}
+
+impl Default for PingMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The contents of a pending ping file.
+#[derive(Debug)]
+struct PingFile {
+    /// The path for the server to upload the ping to.
+    path: String,
+    /// The ping contents.
+    body: JsonValue,
+    /// The metadata stored with the ping.
+    metadata: PingMetadata,
+}
+
+/// Writes a ping file in the current format.
+///
+/// The first line has the URL path, the second line has the body of the ping as JSON
+/// and the third line has the metadata as JSON.
+///
+/// ## Arguments
+///
+/// * `file_path` - The path of the file to write to.
+/// * `url_path` - The path for the server to upload the ping to.
+/// * `body` - The ping contents.
+/// * `metadata` - The metadata to store with the ping.
+pub(crate) fn write_ping_file(
+    file_path: &Path,
+    url_path: &str,
+    body: &JsonValue,
+    metadata: &PingMetadata,
+) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+    file.write_all(url_path.as_bytes())?;
+    file.write_all(b"\n")?;
+    file.write_all(serde_json::to_string(body)?.as_bytes())?;
+    file.write_all(b"\n")?;
+    file.write_all(serde_json::to_string(metadata)?.as_bytes())?;
+    Ok(())
+}
+
 /// Get the file name from a path as a &str.
 ///
 /// # Panics
@@ -41,6 +156,8 @@ fn get_file_name_as_str(path: &Path) -> Option<&str> {
 pub struct PingDirectoryManager {
     /// Paths to the pings directories.
     pings_dirs: [PathBuf; 2],
+    /// Path to the directory for transactional writes of ping files.
+    tmp_dir: PathBuf,
 }
 
 impl PingDirectoryManager {
@@ -56,6 +173,7 @@ impl PingDirectoryManager {
                 data_path.join(PENDING_PINGS_DIRECTORY),
                 data_path.join(DELETION_REQUEST_PINGS_DIRECTORY),
             ],
+            tmp_dir: data_path.join("tmp"),
         }
     }
 
@@ -90,6 +208,49 @@ impl PingDirectoryManager {
     ///
     /// * `document_id` - The UUID of the ping file to be processed
     pub fn process_file(&self, document_id: &str) -> Option<PingRequest> {
+        self.read_file(document_id)
+            .map(|file| file.metadata.to_request(document_id, &file.path, file.body))
+    }
+
+    /// Records a failed upload attempt in the metadata of a ping file.
+    ///
+    /// If the file is not properly formatted, it will be deleted and `None` will be returned.
+    ///
+    /// ## Arguments
+    ///
+    /// * `document_id` - The UUID of the ping file that failed to upload
+    /// * `error` - A description of the error
+    ///
+    /// ## Return value
+    ///
+    /// The `PingRequest` read from the file and the updated metadata.
+    /// The updated metadata is returned even if it could not be persisted.
+    pub fn record_failed_attempt(
+        &self,
+        document_id: &str,
+        error: &str,
+    ) -> Option<(PingRequest, PingMetadata)> {
+        let mut file = self.read_file(document_id)?;
+        file.metadata.version = PING_FILE_VERSION;
+        file.metadata.attempts += 1;
+        file.metadata.last_error = Some(error.to_string());
+
+        if let Err(e) = self.rewrite_file(document_id, &file) {
+            log::warn!(
+                "Unable to persist the upload attempts of ping {}. {}",
+                document_id,
+                e
+            );
+        }
+
+        let request = file.metadata.to_request(document_id, &file.path, file.body);
+        Some((request, file.metadata))
+    }
+
+    /// Reads and parses a ping file, in any of the supported format versions.
+    ///
+    /// If the file is not properly formatted, it will be deleted and `None` will be returned.
+    fn read_file(&self, document_id: &str) -> Option<PingFile> {
         let path = match self.get_file_path(document_id) {
             Some(path) => path,
             None => {
@@ -108,17 +269,33 @@ impl PingDirectoryManager {
         log::info!("Processing ping at: {}", path.display());
 
         // The way the ping file is structured,
-        // first line should always have the path
-        // and second line should have the body with the ping contents in JSON format
+        // first line should always have the path,
+        // second line should have the body with the ping contents in JSON format
+        // and the third line, if any, has the metadata in JSON format.
         let mut lines = BufReader::new(file).lines();
-        if let (Some(Ok(path)), Some(Ok(body))) = (lines.next(), lines.next()) {
-            if let Ok(parsed_body) = serde_json::from_str::<JsonValue>(&body) {
-                return Some(PingRequest::new(document_id, &path, parsed_body));
-            } else {
-                log::warn!(
+        if let (Some(Ok(url_path)), Some(Ok(body))) = (lines.next(), lines.next()) {
+            let metadata = match lines.next() {
+                None => Some(PingMetadata::from_legacy_file(&path)),
+                Some(Ok(metadata)) => serde_json::from_str::<PingMetadata>(&metadata).ok(),
+                Some(Err(_)) => None,
+            };
+
+            match (serde_json::from_str::<JsonValue>(&body), metadata) {
+                (Ok(parsed_body), Some(metadata)) => {
+                    return Some(PingFile {
+                        path: url_path,
+                        body: parsed_body,
+                        metadata,
+                    });
+                }
+                (Err(_), _) => log::warn!(
                     "Error processing ping file: {}. Can't parse ping contents as JSON.",
                     document_id
-                );
+                ),
+                (_, None) => log::warn!(
+                    "Error processing ping file: {}. Can't parse ping metadata as JSON.",
+                    document_id
+                ),
             }
         } else {
             log::warn!(
@@ -130,9 +307,25 @@ impl PingDirectoryManager {
         None
     }
 
+    /// Replaces a ping file with new contents, in the current format.
+    ///
+    /// The file is first written to a temporary location and then moved,
+    /// for transactional writes.
+    fn rewrite_file(&self, document_id: &str, file: &PingFile) -> io::Result<()> {
+        let path = self.get_file_path(document_id).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Cannot find ping file to rewrite")
+        })?;
+
+        create_dir_all(&self.tmp_dir)?;
+        let temp_path = self.tmp_dir.join(document_id);
+        write_ping_file(&temp_path, &file.path, &file.body, &file.metadata)?;
+        fs::rename(&temp_path, &path)
+    }
+
     /// Process the pings directory and return a vector of `PingRequest`s
     /// corresponding to each valid ping file in the directory.
-    /// This vector will be ordered by file `modified_date`.
+    /// This vector will be ordered by the time each ping was first stored,
+    /// which is not affected by rewriting the file after a failed upload.
     ///
     /// Any files that don't match the UUID regex will be deleted
     /// to prevent files from polluting the pings directory.
@@ -147,7 +340,7 @@ impl PingDirectoryManager {
 
         // Walk the pings directory and process each file in it,
         // deleting invalid ones and ignoring unreadable ones.
-        // Create a vector of tuples: (modified_date, PingRequest)
+        // Create a vector of tuples: (first_queued, PingRequest)
         // using the contents and metadata of all valid files.
         let mut pending_pings: Vec<_> = self
             .get_ping_entries()
@@ -162,27 +355,18 @@ impl PingDirectoryManager {
                         return None;
                     }
                     // In case we can't process the file we just ignore it.
-                    if let Some(request) = self.process_file(file_name) {
-                        // Get the modified date of the file, which will later be used
-                        // for sorting the resulting vector.
-                        let modified_date = fs::metadata(&path).and_then(|data| data.modified());
-                        return Some((modified_date, request));
+                    if let Some(file) = self.read_file(file_name) {
+                        let first_queued = file.metadata.first_queued;
+                        let request = file.metadata.to_request(file_name, &file.path, file.body);
+                        return Some((first_queued, request));
                     }
                 };
                 None
             })
             .collect();
 
-        // Sort by `modified_date`.
-        pending_pings.sort_by(|(a, _), (b, _)| {
-            // We might not be able to get the modified date for a given file,
-            // in which case we just put it at the end.
-            if let (Ok(a), Ok(b)) = (a, b) {
-                a.cmp(b)
-            } else {
-                Ordering::Less
-            }
-        });
+        // Sort by `first_queued`, oldest first.
+        pending_pings.sort_by_key(|(first_queued, _)| *first_queued);
 
         // Return the vector leaving only the `PingRequest`s in it
         pending_pings
@@ -191,6 +375,102 @@ impl PingDirectoryManager {
             .collect()
     }
 
+    /// Scans the pending pings directory for the pings counted against the storage quota.
+    ///
+    /// `deletion-request` pings are not counted.
+    ///
+    /// ## Return value
+    ///
+    /// The usage of the pending pings directory.
+    pub(crate) fn pending_pings_usage(&self) -> PendingPingsUsage {
+        // Only the first directory has the pending pings,
+        // the second one has the `deletion-request` pings.
+        let entries = match self.pings_dirs[0].read_dir() {
+            Ok(entries) => entries,
+            Err(_) => return PendingPingsUsage::default(),
+        };
+
+        let pending_pings = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let document_id = get_file_name_as_str(&path)?;
+                if Uuid::parse_str(document_id).is_err() {
+                    return None;
+                }
+                let size = entry.metadata().ok()?.len();
+                let metadata = self.read_metadata(&path)?;
+                Some(PendingPing {
+                    document_id: document_id.to_string(),
+                    first_queued: metadata.first_queued,
+                    size,
+                })
+            })
+            .collect();
+        PendingPingsUsage::new(pending_pings)
+    }
+
+    /// Describes a ping that was just stored in the pending pings directory,
+    /// to count it against the storage quota.
+    ///
+    /// ## Arguments
+    ///
+    /// * `document_id` - The UUID of the ping.
+    ///
+    /// ## Return value
+    ///
+    /// `None` if the ping is not in the pending pings directory,
+    /// e.g. because it is a `deletion-request` ping.
+    pub(crate) fn new_pending_ping(&self, document_id: &str) -> Option<PendingPing> {
+        let size = self.pings_dirs[0].join(document_id).metadata().ok()?.len();
+        Some(PendingPing {
+            document_id: document_id.to_string(),
+            first_queued: local_now_with_offset(),
+            size,
+        })
+    }
+
+    /// Deletes pending pings until the given usage is within the given quota.
+    ///
+    /// See [`PendingPingsUsage::evict`](../policy/struct.PendingPingsUsage.html#method.evict)
+    /// for which pings are deleted.
+    ///
+    /// ## Arguments
+    ///
+    /// * `quota` - The limits on the pending pings.
+    /// * `usage` - The pending pings counted against the quota.
+    ///
+    /// ## Return value
+    ///
+    /// The document ids of the deleted pings.
+    pub(crate) fn enforce_quota(
+        &self,
+        quota: &PingStorageQuota,
+        usage: &mut PendingPingsUsage,
+    ) -> Vec<String> {
+        let evicted = usage.evict(quota, local_now_with_offset());
+        for document_id in &evicted {
+            log::info!(
+                "Pending pings exceed their storage quota. Deleting ping {}",
+                document_id
+            );
+            self.delete_file(document_id);
+        }
+        evicted
+    }
+
+    /// Reads the metadata of a ping file, without parsing the ping contents.
+    ///
+    /// Returns `None` if the file is not properly formatted.
+    fn read_metadata(&self, path: &Path) -> Option<PingMetadata> {
+        let file = File::open(path).ok()?;
+        let mut lines = BufReader::new(file).lines().skip(2);
+        match lines.next() {
+            None => Some(PingMetadata::from_legacy_file(path)),
+            Some(line) => serde_json::from_str(&line.ok()?).ok(),
+        }
+    }
+
     /// Get all the ping entries in all ping directories.
     fn get_ping_entries(&self) -> Vec<fs::DirEntry> {
         let mut result = Vec::new();
@@ -385,4 +665,258 @@ mod test {
 
         assert!(requests[0].is_deletion_request());
     }
+
+    #[test]
+    fn test_stores_ping_metadata_in_ping_files() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit the ping to populate the pending_pings directory
+        glean.submit_ping(&ping_type, None).unwrap();
+
+        let directory_manager = PingDirectoryManager::new(dir.path());
+        let requests = directory_manager.process_dir();
+        assert_eq!(requests.len(), 1);
+
+        let file = directory_manager
+            .read_file(&requests[0].document_id)
+            .unwrap();
+        assert_eq!(PING_FILE_VERSION, file.metadata.version);
+        assert_eq!(0, file.metadata.attempts);
+        assert_eq!(None, file.metadata.last_error);
+    }
+
+    #[test]
+    fn test_processes_ping_files_without_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        // Write a ping file in the two-line format of version 0.
+        let document_id = Uuid::new_v4().to_string();
+        let pending_pings_dir = dir.path().join(PENDING_PINGS_DIRECTORY);
+        fs::create_dir_all(&pending_pings_dir).unwrap();
+        let mut file = File::create(pending_pings_dir.join(&document_id)).unwrap();
+        file.write_all(b"/submit/app_id/test/1/doc_id\n{\"ping_info\":{}}")
+            .unwrap();
+
+        // Try and process the pings folder
+        let requests = directory_manager.process_dir();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(document_id, requests[0].document_id);
+        assert_eq!("/submit/app_id/test/1/doc_id", requests[0].path);
+
+        let file = directory_manager.read_file(&document_id).unwrap();
+        assert_eq!(0, file.metadata.version);
+        assert_eq!(0, file.metadata.attempts);
+
+        // Recording a failure upgrades the file to the current format.
+        let (request, metadata) = directory_manager
+            .record_failed_attempt(&document_id, "HttpStatus(500)")
+            .unwrap();
+        assert_eq!(requests[0].path, request.path);
+        assert_eq!(requests[0].body, request.body);
+        assert_eq!(PING_FILE_VERSION, metadata.version);
+        assert_eq!(1, metadata.attempts);
+        assert_eq!(file.metadata.first_queued, metadata.first_queued);
+
+        let file = directory_manager.read_file(&document_id).unwrap();
+        assert_eq!(metadata, file.metadata);
+        assert_eq!("/submit/app_id/test/1/doc_id", file.path);
+    }
+
+    #[test]
+    fn test_failed_attempts_are_persisted() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit the ping to populate the pending_pings directory
+        glean.submit_ping(&ping_type, None).unwrap();
+
+        let directory_manager = PingDirectoryManager::new(dir.path());
+        let requests = directory_manager.process_dir();
+        let document_id = &requests[0].document_id;
+        let first_queued = directory_manager
+            .read_file(document_id)
+            .unwrap()
+            .metadata
+            .first_queued;
+
+        directory_manager.record_failed_attempt(document_id, "RecoverableFailure");
+        directory_manager.record_failed_attempt(document_id, "HttpStatus(503)");
+
+        // Read the ping file back with a new directory manager
+        let directory_manager = PingDirectoryManager::new(dir.path());
+        let file = directory_manager.read_file(document_id).unwrap();
+        assert_eq!(2, file.metadata.attempts);
+        assert_eq!(first_queued, file.metadata.first_queued);
+        assert_eq!(
+            Some("HttpStatus(503)".to_string()),
+            file.metadata.last_error
+        );
+    }
+
+    #[test]
+    fn test_non_json_metadata_files_are_deleted_and_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        let pending_pings_dir = dir.path().join(PENDING_PINGS_DIRECTORY);
+        fs::create_dir_all(&pending_pings_dir).unwrap();
+        let bad_metadata_file_path = pending_pings_dir.join(Uuid::new_v4().to_string());
+        let mut bad_metadata_file = File::create(&bad_metadata_file_path).unwrap();
+        bad_metadata_file
+            .write_all(b"/submit/app_id/test/1/doc_id\n{}\nThis is not JSON!!!!")
+            .unwrap();
+
+        // Try and process the pings folder
+        assert_eq!(directory_manager.process_dir().len(), 0);
+
+        // Verify that file was indeed deleted
+        assert!(!bad_metadata_file_path.exists());
+    }
+
+    /// Writes a pending ping file, as if it was first stored `age_seconds` ago.
+    fn write_pending_ping(dir: &Path, body: &JsonValue, age_seconds: i64) -> String {
+        let document_id = Uuid::new_v4().to_string();
+        let pending_pings_dir = dir.join(PENDING_PINGS_DIRECTORY);
+        fs::create_dir_all(&pending_pings_dir).unwrap();
+
+        let metadata = PingMetadata {
+            first_queued: local_now_with_offset() - chrono::Duration::seconds(age_seconds),
+            ..PingMetadata::new()
+        };
+        let url_path = format!("/submit/app_id/test/1/{}", document_id);
+        write_ping_file(
+            &pending_pings_dir.join(&document_id),
+            &url_path,
+            body,
+            &metadata,
+        )
+        .unwrap();
+        document_id
+    }
+
+    #[test]
+    fn test_failed_attempts_dont_change_the_processing_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        let older = write_pending_ping(dir.path(), &serde_json::json!({}), 20);
+        let newer = write_pending_ping(dir.path(), &serde_json::json!({}), 10);
+
+        // Rewriting the older ping file must not move it behind the newer one.
+        directory_manager.record_failed_attempt(&older, "RecoverableFailure");
+
+        let requests = directory_manager.process_dir();
+        let document_ids: Vec<_> = requests.iter().map(|r| r.document_id.clone()).collect();
+        assert_eq!(vec![older, newer], document_ids);
+    }
+
+    #[test]
+    fn test_enforce_quota_deletes_the_oldest_pings_over_the_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        let oldest = write_pending_ping(dir.path(), &serde_json::json!({}), 30);
+        let older = write_pending_ping(dir.path(), &serde_json::json!({}), 20);
+        let newest = write_pending_ping(dir.path(), &serde_json::json!({}), 10);
+
+        let quota = PingStorageQuota {
+            max_count: 1,
+            ..Default::default()
+        };
+        let mut usage = directory_manager.pending_pings_usage();
+        assert_eq!(
+            vec![oldest, older],
+            directory_manager.enforce_quota(&quota, &mut usage)
+        );
+        assert_eq!(1, usage.count());
+
+        let requests = directory_manager.process_dir();
+        assert_eq!(1, requests.len());
+        assert_eq!(newest, requests[0].document_id);
+    }
+
+    #[test]
+    fn test_enforce_quota_deletes_the_oldest_pings_over_the_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        let body = serde_json::json!({ "payload": "x".repeat(1000) });
+        let oldest = write_pending_ping(dir.path(), &body, 30);
+        let newest = write_pending_ping(dir.path(), &body, 10);
+
+        // Only one of the pings fits in the quota.
+        let quota = PingStorageQuota {
+            max_total_bytes: 1500,
+            ..Default::default()
+        };
+        let mut usage = directory_manager.pending_pings_usage();
+        assert_eq!(
+            vec![oldest],
+            directory_manager.enforce_quota(&quota, &mut usage)
+        );
+        assert!(usage.total_bytes() <= quota.max_total_bytes);
+
+        let requests = directory_manager.process_dir();
+        assert_eq!(1, requests.len());
+        assert_eq!(newest, requests[0].document_id);
+    }
+
+    #[test]
+    fn test_enforce_quota_deletes_pings_over_the_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory_manager = PingDirectoryManager::new(dir.path());
+
+        let too_old = write_pending_ping(dir.path(), &serde_json::json!({}), 2 * 60 * 60);
+        let recent = write_pending_ping(dir.path(), &serde_json::json!({}), 10);
+
+        let quota = PingStorageQuota {
+            max_age_seconds: 60 * 60,
+            ..Default::default()
+        };
+        let mut usage = directory_manager.pending_pings_usage();
+        assert_eq!(
+            vec![too_old],
+            directory_manager.enforce_quota(&quota, &mut usage)
+        );
+
+        let requests = directory_manager.process_dir();
+        assert_eq!(1, requests.len());
+        assert_eq!(recent, requests[0].document_id);
+    }
+
+    #[test]
+    fn test_enforce_quota_never_deletes_deletion_request_pings() {
+        let (glean, dir) = new_glean(None);
+
+        // Submit a deletion request ping to populate deletion request folder.
+        glean
+            .internal_pings
+            .deletion_request
+            .submit(&glean, None)
+            .unwrap();
+
+        let directory_manager = PingDirectoryManager::new(dir.path());
+        let quota = PingStorageQuota {
+            max_total_bytes: 0,
+            max_count: 0,
+            max_age_seconds: 0,
+        };
+        let mut usage = directory_manager.pending_pings_usage();
+        assert_eq!(0, usage.count());
+        assert!(directory_manager
+            .enforce_quota(&quota, &mut usage)
+            .is_empty());
+
+        let requests = directory_manager.process_dir();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].is_deletion_request());
+    }
 }
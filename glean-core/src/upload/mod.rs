@@ -7,29 +7,42 @@
 //! * Keeps track of pending pings, loading any unsent ping from disk on startup;
 //! * Exposes `get_upload_task` API for the platform layer to request next upload task;
 //! * Exposes `process_ping_upload_response` API to check the HTTP response from the ping upload
-//!   and either delete the corresponding ping from disk or re-enqueue it for sending.
+//!   and either delete the corresponding ping from disk or re-enqueue it for sending;
+//! * Limits the number of uploads per time interval and backs off exponentially
+//!   when retrying pings that failed to upload.
 
 // !IMPORTANT!
 // Remove the next line when this module's functionality is in the Glean object.
 // This is here just to not have lint error for now.
 #![allow(dead_code)]
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
-use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use serde_json::Value as JsonValue;
 
+use crate::internal_metrics::UploadMetrics;
+use crate::Glean;
+pub(crate) use directory::write_ping_file;
 use directory::PingDirectoryManager;
+pub use directory::{PingMetadata, PING_FILE_VERSION};
+use policy::{PendingPingsUsage, RateLimiter, RateLimiterState};
+pub use policy::{PingRateLimit, PingStorageQuota};
 pub use request::PingRequest;
 pub use result::{ffi_upload_result, UploadResult};
 
 mod directory;
+mod policy;
 mod request;
 mod result;
 
+/// The time to wait, in milliseconds, while the pending pings directories are processed.
+const WAIT_TIME_FOR_PROCESSING: u64 = 100;
+
 /// When asking for the next ping request to upload,
 /// the requester may receive one out of three possible tasks.
 ///
@@ -39,9 +52,13 @@ pub enum PingUploadTask {
     /// A PingRequest popped from the front of the queue.
     /// See [`PingRequest`](struct.PingRequest.html) for more information.
     Upload(PingRequest),
-    /// A flag signaling that the pending pings directories are not done being processed,
+    /// A flag signaling that there are pings to upload, but none can be uploaded right now,
     /// thus the requester should wait and come back later.
-    Wait,
+    ///
+    /// This happens while the pending pings directories are not done being processed,
+    /// when the upload rate limit was reached or when all queued pings are backing off after failures.
+    /// Contains the time to wait, in milliseconds.
+    Wait(u64),
     /// A flag signaling that the pending pings queue is empty and requester is done.
     Done,
 }
@@ -55,36 +72,97 @@ pub struct PingUploadManager {
     directory_manager: PingDirectoryManager,
     /// A flag signaling if we are done processing the pending pings directories.
     processed_pending_pings: Arc<AtomicBool>,
+    /// The thread processing the pending pings directories, until it is joined.
+    directory_processor: Mutex<Option<JoinHandle<()>>>,
+    /// The retry state of pings that failed to upload with a recoverable failure,
+    /// keyed by document id.
+    retries: RwLock<HashMap<String, RetryState>>,
+    /// Limits the number of uploads per time interval.
+    rate_limiter: RwLock<RateLimiter>,
+    /// The limits on the pending pings kept on disk.
+    storage_quota: PingStorageQuota,
+    /// The pending pings counted against the storage quota,
+    /// kept up to date as pings are enqueued and deleted.
+    pending_pings_usage: Arc<Mutex<PendingPingsUsage>>,
+    /// The number of pings deleted for exceeding the storage quota,
+    /// which are not recorded in the metrics yet.
+    evicted_pings: Arc<AtomicUsize>,
+    /// Metrics about the upload process.
+    upload_metrics: UploadMetrics,
+}
+
+/// The retry state of a ping that failed to upload.
+#[derive(Debug)]
+struct RetryState {
+    /// The number of recoverable failures so far.
+    attempts: u32,
+    /// The ping should not be uploaded again before this time.
+    retry_after: Instant,
 }
 
 impl PingUploadManager {
+    /// Create a new PingUploadManager, with the default storage quota.
+    ///
+    /// See [`with_storage_quota`](#method.with_storage_quota) for more information.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_path` - Path to the pending pings directory.
+    pub fn new<P: Into<PathBuf>>(data_path: P) -> Self {
+        Self::with_storage_quota(data_path, PingStorageQuota::default())
+    }
+
     /// Create a new PingUploadManager.
     ///
     /// Spawns a new thread and processes the pending pings directory,
-    /// filling up the queue with whatever pings are in there.
+    /// deleting the pings that exceed the storage quota
+    /// and filling up the queue with whatever pings are left in there.
     ///
     /// # Arguments
     ///
     /// * `data_path` - Path to the pending pings directory.
+    /// * `storage_quota` - The limits on the pending pings kept on disk.
     ///
     /// # Panics
     ///
     /// Will panic if unable to spawn a new thread.
-    pub fn new<P: Into<PathBuf>>(data_path: P) -> Self {
+    pub fn with_storage_quota<P: Into<PathBuf>>(
+        data_path: P,
+        storage_quota: PingStorageQuota,
+    ) -> Self {
         let queue = Arc::new(RwLock::new(VecDeque::new()));
         let directory_manager = PingDirectoryManager::new(data_path);
         let processed_pending_pings = Arc::new(AtomicBool::new(false));
+        let pending_pings_usage = Arc::new(Mutex::new(PendingPingsUsage::default()));
+        let evicted_pings = Arc::new(AtomicUsize::new(0));
 
         let local_queue = queue.clone();
         let local_flag = processed_pending_pings.clone();
         let local_manager = directory_manager.clone();
-        let _ = thread::Builder::new()
+        let local_usage = pending_pings_usage.clone();
+        let local_evicted = evicted_pings.clone();
+        let directory_processor = thread::Builder::new()
             .name("glean.ping_directory_manager.process_dir".to_string())
             .spawn(move || {
                 let mut local_queue = local_queue
                     .write()
                     .expect("Can't write to pending pings queue.");
-                local_queue.extend(local_manager.process_dir());
+                let mut local_usage = local_usage
+                    .lock()
+                    .expect("Can't lock the pending pings usage.");
+                // This also counts any ping enqueued before the directory is processed.
+                *local_usage = local_manager.pending_pings_usage();
+                let evicted = local_manager.enforce_quota(&storage_quota, &mut local_usage);
+                local_evicted.fetch_add(evicted.len(), Ordering::SeqCst);
+
+                let requests = local_manager.process_dir();
+                // Malformed ping files are deleted while processing the directory.
+                local_usage.retain(|document_id| {
+                    requests
+                        .iter()
+                        .any(|request| request.document_id == document_id)
+                });
+                local_queue.extend(requests);
                 local_flag.store(true, Ordering::SeqCst);
             })
             .expect("Unable to spawn thread to process pings directories.");
@@ -92,24 +170,125 @@ impl PingUploadManager {
         Self {
             queue,
             processed_pending_pings,
+            directory_processor: Mutex::new(Some(directory_processor)),
             directory_manager,
+            retries: RwLock::new(HashMap::new()),
+            rate_limiter: RwLock::new(RateLimiter::new(PingRateLimit::default())),
+            storage_quota,
+            pending_pings_usage,
+            evicted_pings,
+            upload_metrics: UploadMetrics::new(),
         }
     }
 
+    /// Replace the limit on the number of uploads per time interval.
+    ///
+    /// This resets the count of uploads in the current interval.
+    pub fn set_rate_limit(&self, limit: PingRateLimit) {
+        let mut rate_limiter = self
+            .rate_limiter
+            .write()
+            .expect("Can't write to the rate limiter.");
+        *rate_limiter = RateLimiter::new(limit);
+    }
+
     fn has_processed_pings_dir(&self) -> bool {
         self.processed_pending_pings.load(Ordering::SeqCst)
     }
 
+    /// Wait for the pending pings directories to be processed and join the processing thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The time after which to stop waiting.
+    ///
+    /// # Returns
+    ///
+    /// Whether the processing finished before the deadline.
+    pub fn join_directory_processor(&self, deadline: Instant) -> bool {
+        while !self.has_processed_pings_dir() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let handle = self
+            .directory_processor
+            .lock()
+            .expect("Can't lock the directory processor.")
+            .take();
+        if let Some(handle) = handle {
+            if handle.join().is_err() {
+                log::error!("The pending pings directory processing thread panicked");
+            }
+        }
+        true
+    }
+
     /// Creates a `PingRequest` and adds it to the queue.
-    pub fn enqueue_ping(&self, document_id: &str, path: &str, body: JsonValue) {
+    ///
+    /// If the pending pings exceed the storage quota afterwards,
+    /// the oldest pings are deleted and removed from the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `glean` - The Glean object, used to record the number of deleted pings.
+    /// * `document_id` - The UUID of the ping.
+    /// * `path` - The path for the server to upload the ping to.
+    /// * `body` - The ping contents.
+    pub fn enqueue_ping(&self, glean: &Glean, document_id: &str, path: &str, body: JsonValue) {
         log::trace!("Enqueuing ping {} at {}", document_id, path);
 
-        let mut queue = self
-            .queue
+        let request = PingRequest::new(document_id, path, body).with_debug_headers(
+            glean.debug.debug_view_tag.as_deref(),
+            glean.debug.source_tags.as_deref(),
+        );
+        self.queue
             .write()
-            .expect("Can't write to pending pings queue.");
-        let request = PingRequest::new(document_id, path, body);
-        queue.push_back(request);
+            .expect("Can't write to pending pings queue.")
+            .push_back(request);
+
+        let evicted = match self.directory_manager.new_pending_ping(document_id) {
+            Some(pending_ping) => {
+                let mut usage = self
+                    .pending_pings_usage
+                    .lock()
+                    .expect("Can't lock the pending pings usage.");
+                usage.push(pending_ping);
+                self.directory_manager
+                    .enforce_quota(&self.storage_quota, &mut usage)
+            }
+            None => Vec::new(),
+        };
+        if !evicted.is_empty() {
+            self.queue
+                .write()
+                .expect("Can't write to pending pings queue.")
+                .retain(|request| !evicted.contains(&request.document_id));
+            let mut retries = self
+                .retries
+                .write()
+                .expect("Can't write to the ping retry state.");
+            for document_id in &evicted {
+                retries.remove(document_id);
+            }
+            self.evicted_pings
+                .fetch_add(evicted.len(), Ordering::SeqCst);
+        }
+
+        self.record_evicted_pings(glean);
+    }
+
+    /// Records the number of pings deleted for exceeding the storage quota
+    /// since the last time this was called.
+    fn record_evicted_pings(&self, glean: &Glean) {
+        let evicted = self.evicted_pings.swap(0, Ordering::SeqCst);
+        if evicted > 0 {
+            self.upload_metrics
+                .pending_pings_evicted
+                .add(glean, evicted as i32);
+        }
     }
 
     /// Clears the pending pings queue, leaves the deletion-request pings.
@@ -125,6 +304,19 @@ impl PingUploadManager {
             "{} pings left in the queue (only deletion-request expected)",
             queue.len()
         );
+
+        let mut retries = self
+            .retries
+            .write()
+            .expect("Can't write to the ping retry state.");
+        retries.retain(|document_id, _| queue.iter().any(|ping| &ping.document_id == document_id));
+
+        // Only `deletion-request` pings are left, which don't count against the storage quota.
+        self.pending_pings_usage
+            .lock()
+            .expect("Can't lock the pending pings usage.")
+            .clear();
+
         queue
     }
 
@@ -138,27 +330,69 @@ impl PingUploadManager {
             log::info!(
                 "Tried getting an upload task, but processing is ongoing. Will come back later."
             );
-            return PingUploadTask::Wait;
+            return PingUploadTask::Wait(WAIT_TIME_FOR_PROCESSING);
         }
 
         let mut queue = self
             .queue
             .write()
             .expect("Can't write to pending pings queue.");
-        match queue.pop_front() {
-            Some(request) => {
+        if queue.is_empty() {
+            log::info!("No more pings to upload! You are done.");
+            return PingUploadTask::Done;
+        }
+
+        // Pings that failed before are only handed out again after their backoff time.
+        let now = Instant::now();
+        let retries = self
+            .retries
+            .read()
+            .expect("Can't read the ping retry state.");
+        let ready = queue
+            .iter()
+            .position(|request| match retries.get(&request.document_id) {
+                Some(retry) => retry.retry_after <= now,
+                None => true,
+            });
+
+        let index = match ready {
+            Some(index) => index,
+            None => {
+                // Every queued ping is backing off, wait for the first one to be ready again.
+                let wait = queue
+                    .iter()
+                    .filter_map(|request| retries.get(&request.document_id))
+                    .map(|retry| retry.retry_after.saturating_duration_since(now))
+                    .min()
+                    .map_or(WAIT_TIME_FOR_PROCESSING, policy::duration_as_millis);
                 log::info!(
-                    "New upload task with id {} (path: {})",
-                    request.document_id,
-                    request.path
+                    "All pending pings are backing off after failures. Will come back in {}ms.",
+                    wait
                 );
-                PingUploadTask::Upload(request)
-            }
-            None => {
-                log::info!("No more pings to upload! You are done.");
-                PingUploadTask::Done
+                return PingUploadTask::Wait(wait);
             }
+        };
+
+        let mut rate_limiter = self
+            .rate_limiter
+            .write()
+            .expect("Can't write to the rate limiter.");
+        if let RateLimiterState::Throttled(remaining) = rate_limiter.get_state() {
+            log::info!(
+                "Tried getting an upload task, but we are throttled. Will come back in {}ms.",
+                remaining
+            );
+            return PingUploadTask::Wait(remaining);
         }
+
+        // Safe unwrap: the index was found in this very queue, which we hold a lock on.
+        let request = queue.remove(index).unwrap();
+        log::info!(
+            "New upload task with id {} (path: {})",
+            request.document_id,
+            request.path
+        );
+        PingUploadTask::Upload(request)
     }
 
     /// Processes the response from an attempt to upload a ping.
@@ -186,6 +420,10 @@ impl PingUploadManager {
     ///
     /// * **Any other error**
     ///   For any other error, a warning is logged and the ping is re-enqueued.
+    ///   The ping is not handed out again before a backoff time passed,
+    ///   which doubles with every failure of that ping.
+    ///   After `MAX_RECOVERABLE_FAILURES` failures the ping is deleted
+    ///   and counted in the `glean.error.upload_retries_exhausted` metric.
     ///   _Known other errors:_
     ///   * 500 - internal error
     ///
@@ -196,14 +434,24 @@ impl PingUploadManager {
     ///
     /// # Arguments
     ///
+    /// `glean` - The Glean object, used to record errors.
     /// `document_id` - The UUID of the ping in question.
     /// `status` - The HTTP status of the response.
-    pub fn process_ping_upload_response(&self, document_id: &str, status: UploadResult) {
+    pub fn process_ping_upload_response(
+        &self,
+        glean: &Glean,
+        document_id: &str,
+        status: UploadResult,
+    ) {
+        // Pings deleted for exceeding the storage quota at startup are recorded
+        // the first time the Glean object is available.
+        self.record_evicted_pings(glean);
+
         use UploadResult::*;
         match status {
             HttpStatus(status @ 200..=299) => {
                 log::info!("Ping {} successfully sent {}.", document_id, status);
-                self.directory_manager.delete_file(document_id);
+                self.delete_ping(document_id);
             }
 
             UnrecoverableFailure | HttpStatus(400..=499) => {
@@ -212,37 +460,124 @@ impl PingUploadManager {
                     document_id,
                     status
                 );
-                self.directory_manager.delete_file(document_id);
+                self.delete_ping(document_id);
             }
 
             RecoverableFailure | HttpStatus(_) => {
+                // The attempts are persisted in the ping file, so they survive restarts.
+                let (request, metadata) = match self
+                    .directory_manager
+                    .record_failed_attempt(document_id, &format!("{:?}", status))
+                {
+                    Some(persisted) => persisted,
+                    None => {
+                        // The file is gone or was deleted for being malformed,
+                        // so there is nothing left to retry.
+                        log::error!(
+                            "Recoverable upload failure while attempting to send ping {}, but its file can't be read. Dropping it. Error was {:?}",
+                            document_id,
+                            status
+                        );
+                        self.forget_retries(document_id);
+                        self.forget_usage(document_id);
+                        return;
+                    }
+                };
+                let attempts = self.record_recoverable_failure(document_id, metadata.attempts);
+                if attempts >= policy::MAX_RECOVERABLE_FAILURES {
+                    log::error!(
+                        "Recoverable upload failure while attempting to send ping {}. Giving up after {} attempts. Error was {:?}",
+                        document_id,
+                        attempts,
+                        status
+                    );
+                    self.delete_ping(document_id);
+                    self.upload_metrics.retries_exhausted.add(glean, 1);
+                    return;
+                }
+
                 log::error!(
                     "Recoverable upload failure while attempting to send ping {}, will retry. Error was {:?}",
                     document_id,
                     status
                 );
-                if let Some(request) = self.directory_manager.process_file(document_id) {
-                    let mut queue = self
-                        .queue
-                        .write()
-                        .expect("Can't write to pending pings queue.");
+                let mut queue = self
+                    .queue
+                    .write()
+                    .expect("Can't write to pending pings queue.");
+                if !queue.iter().any(|queued| queued.document_id == document_id) {
                     queue.push_back(request);
                 }
             }
         };
     }
+
+    /// Count a recoverable failure for a ping and schedule its next attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `document_id` - The UUID of the ping in question.
+    /// * `persisted_attempts` - The number of failed attempts recorded in the ping file,
+    ///   including this one.
+    ///
+    /// # Return value
+    ///
+    /// The number of recoverable failures of this ping so far.
+    fn record_recoverable_failure(&self, document_id: &str, persisted_attempts: u32) -> u32 {
+        let mut retries = self
+            .retries
+            .write()
+            .expect("Can't write to the ping retry state.");
+        let retry = retries
+            .entry(document_id.to_string())
+            .or_insert_with(|| RetryState {
+                attempts: 0,
+                retry_after: Instant::now(),
+            });
+        // If the ping file could not be updated, fall back to the attempts counted in memory.
+        retry.attempts = std::cmp::max(retry.attempts + 1, persisted_attempts);
+        retry.retry_after = Instant::now() + policy::backoff(retry.attempts);
+        retry.attempts
+    }
+
+    /// Delete a ping that won't be uploaded again, along with its state.
+    fn delete_ping(&self, document_id: &str) {
+        self.forget_retries(document_id);
+        self.forget_usage(document_id);
+        self.directory_manager.delete_file(document_id);
+    }
+
+    /// Stop counting a ping that is gone against the storage quota.
+    fn forget_usage(&self, document_id: &str) {
+        self.pending_pings_usage
+            .lock()
+            .expect("Can't lock the pending pings usage.")
+            .remove(document_id);
+    }
+
+    /// Drop the retry state of a ping that won't be retried anymore.
+    fn forget_retries(&self, document_id: &str) {
+        let mut retries = self
+            .retries
+            .write()
+            .expect("Can't write to the ping retry state.");
+        retries.remove(document_id);
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::fs;
     use std::thread;
     use std::time::Duration;
 
     use serde_json::json;
+    use uuid::Uuid;
 
     use super::UploadResult::*;
     use super::*;
     use crate::metrics::PingType;
+    use crate::ping::PingMaker;
     use crate::{tests::new_glean, PENDING_PINGS_DIRECTORY};
 
     const DOCUMENT_ID: &str = "40e31919-684f-43b0-a5aa-e15c2d56a674"; // Just a random UUID.
@@ -255,7 +590,7 @@ mod test {
         let upload_manager = PingUploadManager::new(dir.path());
 
         // Wait for processing of pending pings directory to finish.
-        while upload_manager.get_upload_task() == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
             thread::sleep(Duration::from_millis(10));
         }
 
@@ -267,16 +602,17 @@ mod test {
     #[test]
     fn test_returns_ping_request_when_there_is_one() {
         // Create a new upload_manager
+        let (glean, _) = new_glean(None);
         let dir = tempfile::tempdir().unwrap();
         let upload_manager = PingUploadManager::new(dir.path());
 
         // Wait for processing of pending pings directory to finish.
-        while upload_manager.get_upload_task() == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
             thread::sleep(Duration::from_millis(10));
         }
 
         // Enqueue a ping
-        upload_manager.enqueue_ping(DOCUMENT_ID, PATH, json!({}));
+        upload_manager.enqueue_ping(&glean, DOCUMENT_ID, PATH, json!({}));
 
         // Try and get the next request.
         // Verify request was returned
@@ -289,18 +625,19 @@ mod test {
     #[test]
     fn test_returns_as_many_ping_requests_as_there_are() {
         // Create a new upload_manager
+        let (glean, _) = new_glean(None);
         let dir = tempfile::tempdir().unwrap();
         let upload_manager = PingUploadManager::new(dir.path());
 
         // Wait for processing of pending pings directory to finish.
-        while upload_manager.get_upload_task() == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
             thread::sleep(Duration::from_millis(10));
         }
 
         // Enqueue a ping multiple times
         let n = 10;
         for _ in 0..n {
-            upload_manager.enqueue_ping(DOCUMENT_ID, PATH, json!({}));
+            upload_manager.enqueue_ping(&glean, DOCUMENT_ID, PATH, json!({}));
         }
 
         // Verify a request is returned for each submitted ping
@@ -318,17 +655,18 @@ mod test {
     #[test]
     fn test_clearing_the_queue_works_correctly() {
         // Create a new upload_manager
+        let (glean, _) = new_glean(None);
         let dir = tempfile::tempdir().unwrap();
         let upload_manager = PingUploadManager::new(dir.path());
 
         // Wait for processing of pending pings directory to finish.
-        while upload_manager.get_upload_task() == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
             thread::sleep(Duration::from_millis(10));
         }
 
         // Enqueue a ping multiple times
         for _ in 0..10 {
-            upload_manager.enqueue_ping(DOCUMENT_ID, PATH, json!({}));
+            upload_manager.enqueue_ping(&glean, DOCUMENT_ID, PATH, json!({}));
         }
 
         // Clear the queue
@@ -390,7 +728,7 @@ mod test {
 
         // Wait for processing of pending pings directory to finish.
         let mut upload_task = upload_manager.get_upload_task();
-        while upload_task == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_task {
             thread::sleep(Duration::from_millis(10));
             upload_task = upload_manager.get_upload_task();
         }
@@ -425,7 +763,7 @@ mod test {
 
         // Wait for processing of pending pings directory to finish.
         let mut upload_task = upload_manager.get_upload_task();
-        while upload_task == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_task {
             thread::sleep(Duration::from_millis(10));
             upload_task = upload_manager.get_upload_task();
         }
@@ -438,7 +776,7 @@ mod test {
             PingUploadTask::Upload(request) => {
                 // Simulate the processing of a sucessfull request
                 let document_id = request.document_id;
-                upload_manager.process_ping_upload_response(&document_id, HttpStatus(200));
+                upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(200));
                 // Verify file was deleted
                 assert!(!pending_pings_dir.join(document_id).exists());
             }
@@ -465,7 +803,7 @@ mod test {
 
         // Wait for processing of pending pings directory to finish.
         let mut upload_task = upload_manager.get_upload_task();
-        while upload_task == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_task {
             thread::sleep(Duration::from_millis(10));
             upload_task = upload_manager.get_upload_task();
         }
@@ -478,7 +816,7 @@ mod test {
             PingUploadTask::Upload(request) => {
                 // Simulate the processing of a client error
                 let document_id = request.document_id;
-                upload_manager.process_ping_upload_response(&document_id, HttpStatus(404));
+                upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(404));
                 // Verify file was deleted
                 assert!(!pending_pings_dir.join(document_id).exists());
             }
@@ -505,7 +843,7 @@ mod test {
 
         // Wait for processing of pending pings directory to finish.
         let mut upload_task = upload_manager.get_upload_task();
-        while upload_task == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_task {
             thread::sleep(Duration::from_millis(10));
             upload_task = upload_manager.get_upload_task();
         }
@@ -515,20 +853,20 @@ mod test {
             PingUploadTask::Upload(request) => {
                 // Simulate the processing of a client error
                 let document_id = request.document_id;
-                upload_manager.process_ping_upload_response(&document_id, HttpStatus(500));
+                upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(500));
                 // Verify this ping was indeed re-enqueued
-                match upload_manager.get_upload_task() {
-                    PingUploadTask::Upload(request) => {
-                        assert_eq!(document_id, request.document_id);
-                    }
-                    _ => panic!("Expected upload manager to return the next request!"),
-                }
+                let queue = upload_manager.queue.read().unwrap();
+                assert_eq!(1, queue.len());
+                assert_eq!(document_id, queue[0].document_id);
             }
             _ => panic!("Expected upload manager to return the next request!"),
         }
 
-        // Verify that after request is returned, none are left
-        assert_eq!(upload_manager.get_upload_task(), PingUploadTask::Done);
+        // Verify the ping is not handed out again before its backoff time passed
+        match upload_manager.get_upload_task() {
+            PingUploadTask::Wait(time) => assert!(time > 0),
+            _ => panic!("Expected upload manager to wait for the ping to be retried!"),
+        }
     }
 
     #[test]
@@ -547,7 +885,7 @@ mod test {
 
         // Wait for processing of pending pings directory to finish.
         let mut upload_task = upload_manager.get_upload_task();
-        while upload_task == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_task {
             thread::sleep(Duration::from_millis(10));
             upload_task = upload_manager.get_upload_task();
         }
@@ -560,7 +898,11 @@ mod test {
             PingUploadTask::Upload(request) => {
                 // Simulate the processing of a client error
                 let document_id = request.document_id;
-                upload_manager.process_ping_upload_response(&document_id, UnrecoverableFailure);
+                upload_manager.process_ping_upload_response(
+                    &glean,
+                    &document_id,
+                    UnrecoverableFailure,
+                );
                 // Verify file was deleted
                 assert!(!pending_pings_dir.join(document_id).exists());
             }
@@ -571,14 +913,55 @@ mod test {
         assert_eq!(upload_manager.get_upload_task(), PingUploadTask::Done);
     }
 
+    #[test]
+    fn test_drops_pings_whose_file_is_gone_after_a_server_error() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, /* send_if_empty */ true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit a ping
+        glean.submit_ping(&ping_type, None).unwrap();
+
+        // Create a new upload_manager
+        let upload_manager = PingUploadManager::new(dir.path());
+
+        // Wait for processing of pending pings directory to finish.
+        let mut upload_task = upload_manager.get_upload_task();
+        while let PingUploadTask::Wait(_) = upload_task {
+            thread::sleep(Duration::from_millis(10));
+            upload_task = upload_manager.get_upload_task();
+        }
+
+        match upload_task {
+            PingUploadTask::Upload(request) => {
+                // The ping file disappears while the upload is in progress
+                let document_id = request.document_id;
+                let pending_pings_dir = dir.path().join(PENDING_PINGS_DIRECTORY);
+                fs::remove_file(pending_pings_dir.join(&document_id)).unwrap();
+
+                upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(500));
+                // Verify there is nothing left to retry
+                assert!(upload_manager.queue.read().unwrap().is_empty());
+                assert!(upload_manager.retries.read().unwrap().is_empty());
+            }
+            _ => panic!("Expected upload manager to return the next request!"),
+        }
+
+        assert_eq!(upload_manager.get_upload_task(), PingUploadTask::Done);
+    }
+
     #[test]
     fn new_pings_are_added_while_upload_in_progress() {
+        let (glean, _) = new_glean(None);
+
         // Create a new upload_manager
         let dir = tempfile::tempdir().unwrap();
         let upload_manager = PingUploadManager::new(dir.path());
 
         // Wait for processing of pending pings directory to finish.
-        while upload_manager.get_upload_task() == PingUploadTask::Wait {
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
             thread::sleep(Duration::from_millis(10));
         }
 
@@ -589,7 +972,7 @@ mod test {
         let path2 = format!("/submit/app_id/test-ping/1/{}", doc2);
 
         // Enqueue a ping
-        upload_manager.enqueue_ping(doc1, &path1, json!({}));
+        upload_manager.enqueue_ping(&glean, doc1, &path1, json!({}));
 
         // Try and get the first request.
         let req = match upload_manager.get_upload_task() {
@@ -599,10 +982,10 @@ mod test {
         assert_eq!(doc1, req.document_id);
 
         // Schedule the next one while the first one is "in progress"
-        upload_manager.enqueue_ping(doc2, &path2, json!({}));
+        upload_manager.enqueue_ping(&glean, doc2, &path2, json!({}));
 
         // Mark as processed
-        upload_manager.process_ping_upload_response(&req.document_id, HttpStatus(200));
+        upload_manager.process_ping_upload_response(&glean, &req.document_id, HttpStatus(200));
 
         // Get the second request.
         let req = match upload_manager.get_upload_task() {
@@ -612,7 +995,7 @@ mod test {
         assert_eq!(doc2, req.document_id);
 
         // Mark as processed
-        upload_manager.process_ping_upload_response(&req.document_id, HttpStatus(200));
+        upload_manager.process_ping_upload_response(&glean, &req.document_id, HttpStatus(200));
 
         // ... and then we're done.
         match upload_manager.get_upload_task() {
@@ -620,4 +1003,242 @@ mod test {
             _ => panic!("Expected upload manager to return the next request!"),
         }
     }
+
+    #[test]
+    fn pings_are_discarded_after_too_many_recoverable_failures() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, /* send_if_empty */ true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit a ping
+        glean.submit_ping(&ping_type, None).unwrap();
+
+        // Create a new upload_manager
+        let upload_manager = PingUploadManager::new(dir.path());
+
+        // Wait for processing of pending pings directory to finish.
+        let mut upload_task = upload_manager.get_upload_task();
+        while let PingUploadTask::Wait(_) = upload_task {
+            thread::sleep(Duration::from_millis(10));
+            upload_task = upload_manager.get_upload_task();
+        }
+
+        let document_id = match upload_task {
+            PingUploadTask::Upload(request) => request.document_id,
+            _ => panic!("Expected upload manager to return the next request!"),
+        };
+
+        // Fail the ping as often as allowed, it's kept on disk until the last attempt.
+        let pending_pings_dir = dir.path().join(PENDING_PINGS_DIRECTORY);
+        for _ in 0..policy::MAX_RECOVERABLE_FAILURES - 1 {
+            upload_manager.process_ping_upload_response(&glean, &document_id, RecoverableFailure);
+            assert!(pending_pings_dir.join(&document_id).exists());
+        }
+
+        upload_manager.process_ping_upload_response(&glean, &document_id, RecoverableFailure);
+        assert!(!pending_pings_dir.join(&document_id).exists());
+        assert_eq!(
+            Some(1),
+            upload_manager
+                .upload_metrics
+                .retries_exhausted
+                .test_get_value(&glean, "metrics")
+        );
+    }
+
+    #[test]
+    fn recoverable_failures_are_counted_across_restarts() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, /* send_if_empty */ true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit a ping
+        glean.submit_ping(&ping_type, None).unwrap();
+
+        // Fail the ping as often as allowed, but once, with a new upload manager each time,
+        // as if Glean was restarted in between.
+        let pending_pings_dir = dir.path().join(PENDING_PINGS_DIRECTORY);
+        let mut document_id = String::new();
+        for attempt in 0..policy::MAX_RECOVERABLE_FAILURES {
+            let upload_manager = PingUploadManager::new(dir.path());
+
+            // Wait for processing of pending pings directory to finish.
+            let mut upload_task = upload_manager.get_upload_task();
+            while let PingUploadTask::Wait(_) = upload_task {
+                thread::sleep(Duration::from_millis(10));
+                upload_task = upload_manager.get_upload_task();
+            }
+
+            document_id = match upload_task {
+                PingUploadTask::Upload(request) => request.document_id,
+                _ => panic!("Expected upload manager to return the next request!"),
+            };
+
+            upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(500));
+            if attempt < policy::MAX_RECOVERABLE_FAILURES - 1 {
+                assert!(pending_pings_dir.join(&document_id).exists());
+            }
+        }
+
+        // The last attempt discarded the ping.
+        assert!(!pending_pings_dir.join(&document_id).exists());
+    }
+
+    #[test]
+    fn pings_exceeding_the_storage_quota_are_evicted() {
+        let (mut glean, dir) = new_glean(None);
+
+        // Register a ping for testing
+        let ping_type = PingType::new("test", true, /* send_if_empty */ true, vec![]);
+        glean.register_ping_type(&ping_type);
+
+        // Submit more pings than the quota allows
+        for _ in 0..3 {
+            glean.submit_ping(&ping_type, None).unwrap();
+        }
+
+        // Create a new upload manager, only allowing a single pending ping.
+        let quota = PingStorageQuota {
+            max_count: 1,
+            ..Default::default()
+        };
+        let upload_manager = PingUploadManager::with_storage_quota(dir.path(), quota);
+
+        // Wait for processing of pending pings directory to finish.
+        let mut upload_task = upload_manager.get_upload_task();
+        while let PingUploadTask::Wait(_) = upload_task {
+            thread::sleep(Duration::from_millis(10));
+            upload_task = upload_manager.get_upload_task();
+        }
+
+        // Only the newest ping is left.
+        let document_id = match upload_task {
+            PingUploadTask::Upload(request) => request.document_id,
+            _ => panic!("Expected upload manager to return the next request!"),
+        };
+        assert_eq!(upload_manager.get_upload_task(), PingUploadTask::Done);
+
+        // The evicted pings are recorded when the upload response is processed.
+        upload_manager.process_ping_upload_response(&glean, &document_id, HttpStatus(200));
+        assert_eq!(
+            Some(2),
+            upload_manager
+                .upload_metrics
+                .pending_pings_evicted
+                .test_get_value(&glean, "metrics")
+        );
+
+        // Enqueuing pings evicts the older ones right away.
+        for _ in 0..2 {
+            let document_id = Uuid::new_v4().to_string();
+            let path = format!("/submit/app_id/test/1/{}", document_id);
+            let content = json!({});
+            PingMaker::new()
+                .store_ping(
+                    &document_id,
+                    "test",
+                    dir.path(),
+                    &path,
+                    &content,
+                    &PingMetadata::new(),
+                )
+                .unwrap();
+            upload_manager.enqueue_ping(&glean, &document_id, &path, content);
+        }
+        assert_eq!(
+            Some(3),
+            upload_manager
+                .upload_metrics
+                .pending_pings_evicted
+                .test_get_value(&glean, "metrics")
+        );
+        match upload_manager.get_upload_task() {
+            PingUploadTask::Upload(_) => {}
+            _ => panic!("Expected upload manager to return the next request!"),
+        }
+        assert_eq!(upload_manager.get_upload_task(), PingUploadTask::Done);
+    }
+
+    #[test]
+    fn deleted_pings_stop_counting_against_the_storage_quota() {
+        let (glean, dir) = new_glean(None);
+
+        // Only allow a single pending ping.
+        let quota = PingStorageQuota {
+            max_count: 1,
+            ..Default::default()
+        };
+        let upload_manager = PingUploadManager::with_storage_quota(dir.path(), quota);
+        assert!(upload_manager.join_directory_processor(Instant::now() + Duration::from_secs(5)));
+
+        for _ in 0..2 {
+            let document_id = Uuid::new_v4().to_string();
+            let path = format!("/submit/app_id/test/1/{}", document_id);
+            let content = json!({});
+            PingMaker::new()
+                .store_ping(
+                    &document_id,
+                    "test",
+                    dir.path(),
+                    &path,
+                    &content,
+                    &PingMetadata::new(),
+                )
+                .unwrap();
+            upload_manager.enqueue_ping(&glean, &document_id, &path, content);
+
+            // Uploading the ping makes room for the next one.
+            match upload_manager.get_upload_task() {
+                PingUploadTask::Upload(request) => upload_manager.process_ping_upload_response(
+                    &glean,
+                    &request.document_id,
+                    HttpStatus(200),
+                ),
+                _ => panic!("Expected upload manager to return the next request!"),
+            }
+        }
+
+        assert_eq!(
+            None,
+            upload_manager
+                .upload_metrics
+                .pending_pings_evicted
+                .test_get_value(&glean, "metrics")
+        );
+    }
+
+    #[test]
+    fn uploads_are_rate_limited() {
+        // Create a new upload_manager
+        let (glean, _) = new_glean(None);
+        let dir = tempfile::tempdir().unwrap();
+        let upload_manager = PingUploadManager::new(dir.path());
+        upload_manager.set_rate_limit(PingRateLimit {
+            seconds_per_interval: 60,
+            pings_per_interval: 1,
+        });
+
+        // Wait for processing of pending pings directory to finish.
+        while let PingUploadTask::Wait(_) = upload_manager.get_upload_task() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        upload_manager.enqueue_ping(&glean, DOCUMENT_ID, PATH, json!({}));
+        upload_manager.enqueue_ping(&glean, DOCUMENT_ID, PATH, json!({}));
+
+        match upload_manager.get_upload_task() {
+            PingUploadTask::Upload(_) => {}
+            _ => panic!("Expected upload manager to return the next request!"),
+        }
+
+        // The second ping exceeds the rate limit.
+        match upload_manager.get_upload_task() {
+            PingUploadTask::Wait(time) => assert!(time <= 60 * 1000),
+            _ => panic!("Expected upload manager to be throttled!"),
+        }
+    }
 }
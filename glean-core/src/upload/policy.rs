@@ -0,0 +1,394 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Policies for ping uploads: rate limiting, retries and storage quotas.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, FixedOffset};
+
+/// The default number of seconds in a rate limiting interval.
+const DEFAULT_SECONDS_PER_INTERVAL: u64 = 60;
+/// The default number of pings allowed to be uploaded per rate limiting interval.
+const DEFAULT_PINGS_PER_INTERVAL: u32 = 15;
+
+/// The default maximum total size, in bytes, of the pending pings.
+const DEFAULT_MAX_PENDING_PINGS_BYTES: u64 = 10 * 1024 * 1024;
+/// The default maximum number of pending pings.
+const DEFAULT_MAX_PENDING_PINGS_COUNT: usize = 250;
+/// The default maximum age, in seconds, of a pending ping.
+const DEFAULT_MAX_PENDING_PING_AGE_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+/// The time to wait after the first recoverable failure of a ping.
+/// Every further failure doubles the time to wait.
+const BASE_BACKOFF: Duration = Duration::from_secs(30);
+/// The maximum time to wait before retrying a ping.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of recoverable failures a ping may have
+/// before it is discarded.
+pub(crate) const MAX_RECOVERABLE_FAILURES: u32 = 8;
+
+/// The limit on the number of pings uploaded per time interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PingRateLimit {
+    /// The length of an interval, in seconds.
+    pub seconds_per_interval: u64,
+    /// The maximum number of pings uploaded during an interval.
+    pub pings_per_interval: u32,
+}
+
+impl Default for PingRateLimit {
+    fn default() -> Self {
+        Self {
+            seconds_per_interval: DEFAULT_SECONDS_PER_INTERVAL,
+            pings_per_interval: DEFAULT_PINGS_PER_INTERVAL,
+        }
+    }
+}
+
+/// The limits on the pending pings kept on disk.
+///
+/// When a limit is exceeded, the oldest pings are deleted.
+/// `deletion-request` pings are never deleted and don't count against the limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PingStorageQuota {
+    /// The maximum total size, in bytes, of the pending ping files.
+    pub max_total_bytes: u64,
+    /// The maximum number of pending pings.
+    pub max_count: usize,
+    /// The maximum age, in seconds, of a pending ping.
+    pub max_age_seconds: u64,
+}
+
+impl Default for PingStorageQuota {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: DEFAULT_MAX_PENDING_PINGS_BYTES,
+            max_count: DEFAULT_MAX_PENDING_PINGS_COUNT,
+            max_age_seconds: DEFAULT_MAX_PENDING_PING_AGE_SECONDS,
+        }
+    }
+}
+
+/// A pending ping counted against the storage quota.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingPing {
+    /// The UUID of the ping.
+    pub(crate) document_id: String,
+    /// When the ping was first queued.
+    pub(crate) first_queued: DateTime<FixedOffset>,
+    /// The size of the ping file, in bytes.
+    pub(crate) size: u64,
+}
+
+/// The pending pings counted against the storage quota, with their total size.
+///
+/// This is kept up to date as pings are enqueued and removed,
+/// so the quota can be enforced without scanning the pending pings directory.
+#[derive(Debug, Default)]
+pub(crate) struct PendingPingsUsage {
+    /// The pending pings, oldest first.
+    pings: VecDeque<PendingPing>,
+    /// The total size of the pending pings, in bytes.
+    total_bytes: u64,
+}
+
+impl PendingPingsUsage {
+    /// Create the usage of the given pending pings, in any order.
+    pub(crate) fn new(mut pings: Vec<PendingPing>) -> Self {
+        pings.sort_by_key(|ping| ping.first_queued);
+        let total_bytes = pings.iter().map(|ping| ping.size).sum();
+        Self {
+            pings: pings.into(),
+            total_bytes,
+        }
+    }
+
+    /// The number of pending pings.
+    pub(crate) fn count(&self) -> usize {
+        self.pings.len()
+    }
+
+    /// The total size of the pending pings, in bytes.
+    pub(crate) fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Count a newly queued ping, which is the newest one.
+    ///
+    /// A ping that is already counted is not counted again.
+    pub(crate) fn push(&mut self, ping: PendingPing) {
+        if self
+            .pings
+            .iter()
+            .any(|pending| pending.document_id == ping.document_id)
+        {
+            return;
+        }
+        self.total_bytes += ping.size;
+        self.pings.push_back(ping);
+    }
+
+    /// Stop counting a ping, e.g. after it was uploaded.
+    pub(crate) fn remove(&mut self, document_id: &str) {
+        if let Some(index) = self
+            .pings
+            .iter()
+            .position(|ping| ping.document_id == document_id)
+        {
+            // Safe unwrap: the index was just found.
+            let ping = self.pings.remove(index).unwrap();
+            self.total_bytes -= ping.size;
+        }
+    }
+
+    /// Keep counting only the pings for which `keep` returns `true`.
+    pub(crate) fn retain<F: FnMut(&str) -> bool>(&mut self, mut keep: F) {
+        let total_bytes = &mut self.total_bytes;
+        self.pings.retain(|ping| {
+            let kept = keep(&ping.document_id);
+            if !kept {
+                *total_bytes -= ping.size;
+            }
+            kept
+        });
+    }
+
+    /// Stop counting all pings.
+    pub(crate) fn clear(&mut self) {
+        self.pings.clear();
+        self.total_bytes = 0;
+    }
+
+    /// Stop counting the pings that exceed the quota.
+    ///
+    /// Pings older than the maximum age are always evicted.
+    /// Otherwise the oldest pings are evicted until the rest is within the quota.
+    ///
+    /// ## Arguments
+    ///
+    /// * `quota` - The limits on the pending pings.
+    /// * `now` - The current time, to compute the age of the pings.
+    ///
+    /// ## Return value
+    ///
+    /// The document ids of the evicted pings, oldest first.
+    pub(crate) fn evict(
+        &mut self,
+        quota: &PingStorageQuota,
+        now: DateTime<FixedOffset>,
+    ) -> Vec<String> {
+        let mut evicted = Vec::new();
+        while let Some(oldest) = self.pings.front() {
+            let age = now.signed_duration_since(oldest.first_queued).num_seconds();
+            let too_old = age > 0 && age as u64 > quota.max_age_seconds;
+            if !too_old
+                && self.pings.len() <= quota.max_count
+                && self.total_bytes <= quota.max_total_bytes
+            {
+                break;
+            }
+
+            // Safe unwrap: the front was just checked.
+            let oldest = self.pings.pop_front().unwrap();
+            self.total_bytes -= oldest.size;
+            evicted.push(oldest.document_id);
+        }
+        evicted
+    }
+}
+
+/// The state of the rate limiter after asking for an upload.
+#[derive(Debug, PartialEq)]
+pub(crate) enum RateLimiterState {
+    /// The upload is allowed and has been counted.
+    Incrementing,
+    /// The limit is reached. The upload needs to wait for the given number of milliseconds.
+    Throttled(u64),
+}
+
+/// Counts uploads in fixed time intervals.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// When the current interval started.
+    started: Option<Instant>,
+    /// The number of uploads in the current interval.
+    count: u32,
+    /// The length of an interval.
+    interval: Duration,
+    /// The maximum number of uploads per interval.
+    max_count: u32,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter from the given limits.
+    pub(crate) fn new(limit: PingRateLimit) -> Self {
+        Self {
+            started: None,
+            count: 0,
+            interval: Duration::from_secs(limit.seconds_per_interval),
+            max_count: limit.pings_per_interval,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.started = Some(Instant::now());
+        self.count = 0;
+    }
+
+    /// Ask for permission to upload a ping.
+    ///
+    /// If the upload is allowed it is counted against the current interval.
+    pub(crate) fn get_state(&mut self) -> RateLimiterState {
+        let elapsed = match self.started {
+            Some(started) => started.elapsed(),
+            None => {
+                self.reset();
+                Duration::from_secs(0)
+            }
+        };
+
+        if elapsed >= self.interval {
+            self.reset();
+        }
+
+        if self.count >= self.max_count {
+            // Safe unwrap: `started` is always set by now.
+            let remaining = self.interval - self.started.unwrap().elapsed().min(self.interval);
+            return RateLimiterState::Throttled(duration_as_millis(remaining));
+        }
+
+        self.count += 1;
+        RateLimiterState::Incrementing
+    }
+}
+
+/// The time to wait before retrying a ping that failed `attempts` times.
+///
+/// Doubles with every attempt, starting at `BASE_BACKOFF` and capped at `MAX_BACKOFF`.
+pub(crate) fn backoff(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    BASE_BACKOFF
+        .checked_mul(1 << exponent)
+        .map_or(MAX_BACKOFF, |backoff| backoff.min(MAX_BACKOFF))
+}
+
+/// A duration in milliseconds, saturating at `u64::MAX`.
+pub(crate) fn duration_as_millis(duration: Duration) -> u64 {
+    let millis = duration.as_millis();
+    if millis > u128::from(std::u64::MAX) {
+        std::u64::MAX
+    } else {
+        millis as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_throttles_after_max_count() {
+        let mut limiter = RateLimiter::new(PingRateLimit {
+            seconds_per_interval: 60,
+            pings_per_interval: 3,
+        });
+
+        for _ in 0..3 {
+            assert_eq!(RateLimiterState::Incrementing, limiter.get_state());
+        }
+
+        match limiter.get_state() {
+            RateLimiterState::Throttled(remaining) => assert!(remaining <= 60 * 1000),
+            state => panic!("Expected the rate limiter to throttle, got {:?}", state),
+        }
+    }
+
+    #[test]
+    fn rate_limiter_resets_after_interval() {
+        let mut limiter = RateLimiter::new(PingRateLimit {
+            seconds_per_interval: 0,
+            pings_per_interval: 1,
+        });
+
+        // With an empty interval, every request starts a new interval.
+        assert_eq!(RateLimiterState::Incrementing, limiter.get_state());
+        assert_eq!(RateLimiterState::Incrementing, limiter.get_state());
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_is_capped() {
+        assert_eq!(BASE_BACKOFF, backoff(1));
+        assert_eq!(BASE_BACKOFF * 2, backoff(2));
+        assert_eq!(BASE_BACKOFF * 4, backoff(3));
+        assert_eq!(MAX_BACKOFF, backoff(100));
+    }
+
+    fn pending_ping(document_id: &str, age_seconds: i64, size: u64) -> PendingPing {
+        PendingPing {
+            document_id: document_id.to_string(),
+            first_queued: crate::util::local_now_with_offset()
+                - chrono::Duration::seconds(age_seconds),
+            size,
+        }
+    }
+
+    #[test]
+    fn pending_pings_usage_keeps_running_totals() {
+        let mut usage = PendingPingsUsage::new(vec![
+            pending_ping("newer", 10, 100),
+            pending_ping("older", 20, 200),
+        ]);
+        assert_eq!(2, usage.count());
+        assert_eq!(300, usage.total_bytes());
+
+        // A ping is only counted once.
+        usage.push(pending_ping("newest", 0, 50));
+        usage.push(pending_ping("newest", 0, 50));
+        assert_eq!(3, usage.count());
+        assert_eq!(350, usage.total_bytes());
+
+        usage.remove("older");
+        usage.remove("unknown");
+        assert_eq!(2, usage.count());
+        assert_eq!(150, usage.total_bytes());
+
+        usage.clear();
+        assert_eq!(0, usage.count());
+        assert_eq!(0, usage.total_bytes());
+    }
+
+    #[test]
+    fn pending_pings_usage_evicts_the_oldest_pings_over_the_quota() {
+        let now = crate::util::local_now_with_offset();
+        let mut usage = PendingPingsUsage::new(vec![
+            pending_ping("newest", 10, 100),
+            pending_ping("oldest", 30, 100),
+            pending_ping("older", 20, 100),
+        ]);
+
+        let quota = PingStorageQuota {
+            max_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(vec!["oldest".to_string()], usage.evict(&quota, now));
+
+        let quota = PingStorageQuota {
+            max_total_bytes: 100,
+            ..Default::default()
+        };
+        assert_eq!(vec!["older".to_string()], usage.evict(&quota, now));
+        assert_eq!(1, usage.count());
+        assert_eq!(100, usage.total_bytes());
+
+        let quota = PingStorageQuota {
+            max_age_seconds: 5,
+            ..Default::default()
+        };
+        assert_eq!(vec!["newest".to_string()], usage.evict(&quota, now));
+        assert_eq!(0, usage.total_bytes());
+    }
+}
@@ -50,6 +50,26 @@ impl PingRequest {
         }
     }
 
+    /// Adds the debugging headers to the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `debug_view_tag` - The tag for the `X-Debug-ID` header, if any.
+    /// * `source_tags` - The tags for the `X-Source-Tags` header, if any.
+    pub fn with_debug_headers(
+        mut self,
+        debug_view_tag: Option<&str>,
+        source_tags: Option<&[String]>,
+    ) -> Self {
+        if let Some(tag) = debug_view_tag {
+            self.headers.insert("X-Debug-ID", tag.to_string());
+        }
+        if let Some(tags) = source_tags {
+            self.headers.insert("X-Source-Tags", tags.join(","));
+        }
+        self
+    }
+
     /// Verifies if current request is for a deletion-request ping.
     pub fn is_deletion_request(&self) -> bool {
         // The path format should be `/submit/<app_id>/<ping_name>/<schema_version/<doc_id>`
@@ -56,6 +56,9 @@ pub fn new_glean(tempdir: Option<tempfile::TempDir>) -> (Glean, tempfile::TempDi
         upload_enabled: true,
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
     let glean = Glean::new(cfg).unwrap();
 
@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use std::fs;
 
 use glean_core::metrics::*;
-use glean_core::{CommonMetricData, Lifetime};
+use glean_core::{test_get_num_recorded_errors, CommonMetricData, ErrorType, Lifetime};
 
 #[test]
 fn record_properly_records_without_optional_arguments() {
@@ -73,8 +73,8 @@ fn record_properly_records_with_optional_arguments() {
         assert_eq!("test_event_no_optional", event.name);
         let extra = event.extra.unwrap();
         assert_eq!(2, extra.len());
-        assert_eq!("value1", extra["key1"]);
-        assert_eq!("value2", extra["key2"]);
+        assert_eq!(Some("value1"), extra["key1"].as_str());
+        assert_eq!(Some("value2"), extra["key2"].as_str());
     }
 }
 
@@ -288,3 +288,89 @@ fn snapshot_sorts_the_timestamps() {
             .unwrap()
     );
 }
+
+#[test]
+fn extra_keys_can_be_recorded_by_name() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = EventMetric::new(
+        CommonMetricData {
+            name: "test_event_by_name".into(),
+            category: "telemetry".into(),
+            send_in_pings: vec!["store1".into()],
+            disabled: false,
+            lifetime: Lifetime::Ping,
+            ..Default::default()
+        },
+        vec!["key1".into(), "key2".into()],
+    );
+
+    let mut extra = HashMap::new();
+    extra.insert("key2".to_string(), "value2".into());
+    metric.record_with_names(&glean, 1000, extra);
+
+    let events = metric.test_get_value(&glean, "store1").unwrap();
+    assert_eq!(1, events.len());
+    let extra = events[0].extra.as_ref().unwrap();
+    assert_eq!(1, extra.len());
+    assert_eq!(Some("value2"), extra["key2"].as_str());
+
+    // Unknown keys drop the event and report an error.
+    let mut extra = HashMap::new();
+    extra.insert("key1".to_string(), "value1".into());
+    extra.insert("key3".to_string(), "value3".into());
+    metric.record_with_names(&glean, 2000, extra);
+
+    assert_eq!(1, metric.test_get_value(&glean, "store1").unwrap().len());
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum TestKeys {
+    Key1,
+    Key2,
+}
+
+impl ExtraKeys for TestKeys {
+    const ALLOWED_KEYS: &'static [&'static str] = &["key1", "key2"];
+
+    fn as_str(self) -> &'static str {
+        Self::ALLOWED_KEYS[self as usize]
+    }
+}
+
+#[test]
+fn extra_keys_can_be_recorded_with_typed_keys() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = EventMetric::new(
+        CommonMetricData {
+            name: "test_event_typed".into(),
+            category: "telemetry".into(),
+            send_in_pings: vec!["store1".into()],
+            disabled: false,
+            lifetime: Lifetime::Ping,
+            ..Default::default()
+        },
+        TestKeys::ALLOWED_KEYS
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+    );
+
+    let mut extra = HashMap::new();
+    extra.insert(TestKeys::Key1, 5.into());
+    extra.insert(TestKeys::Key2, true.into());
+    metric.record_with_keys(&glean, 1000, extra);
+    metric.record_with_keys::<TestKeys, _>(&glean, 2000, None);
+
+    let events = metric.test_get_value(&glean, "store1").unwrap();
+    assert_eq!(2, events.len());
+    let extra = events[0].extra.as_ref().unwrap();
+    assert_eq!(ExtraValue::Integer(5), extra["key1"]);
+    assert_eq!(ExtraValue::Boolean(true), extra["key2"]);
+    assert!(events[1].extra.is_none());
+}
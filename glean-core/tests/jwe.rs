@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+use crate::common::*;
+
+use serde_json::json;
+
+use glean_core::metrics::*;
+use glean_core::storage::StorageManager;
+use glean_core::{test_get_num_recorded_errors, ErrorType};
+use glean_core::{CommonMetricData, Lifetime};
+
+const HEADER: &str = "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ";
+const KEY: &str = "OKOawDo13gRp2ojaHV7LFpZcgV7T6DVZKTyKOMTYUmKoTCVJRgckCL9kiMT03JGe";
+const INIT_VECTOR: &str = "48V1_ALb6US04U3b";
+const CIPHER_TEXT: &str = "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6ji";
+const AUTH_TAG: &str = "XFBoMYUZodetZdvTiFvSkQ";
+
+fn jwe_metric() -> JweMetric {
+    JweMetric::new(CommonMetricData {
+        name: "jwe_metric".into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    })
+}
+
+fn compact() -> String {
+    [HEADER, KEY, INIT_VECTOR, CIPHER_TEXT, AUTH_TAG].join(".")
+}
+
+#[test]
+fn jwe_serializer_should_correctly_serialize_jwe_values() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = jwe_metric();
+    metric.set_with_compact_representation(&glean, compact());
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+    assert_eq!(
+        json!({"jwe": {"telemetry.jwe_metric": compact()}}),
+        snapshot
+    );
+}
+
+#[test]
+fn jwe_can_be_set_from_its_components() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = jwe_metric();
+    metric.set(&glean, HEADER, KEY, INIT_VECTOR, CIPHER_TEXT, AUTH_TAG);
+    assert_eq!(Some(compact()), metric.test_get_value(&glean, "store1"));
+
+    // Direct encryption has no key, IV or tag.
+    metric.set(&glean, HEADER, "", "", CIPHER_TEXT, "");
+    assert_eq!(
+        Some(format!("{}...{}.", HEADER, CIPHER_TEXT)),
+        metric.test_get_value(&glean, "store1")
+    );
+}
+
+#[test]
+fn invalid_jwe_values_are_rejected() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = jwe_metric();
+    // Too few components.
+    metric.set_with_compact_representation(&glean, [HEADER, KEY, CIPHER_TEXT].join("."));
+    // Not BASE64URL.
+    metric.set_with_compact_representation(&glean, compact().replace('_', "/"));
+    // Empty ciphertext.
+    metric.set(&glean, HEADER, KEY, INIT_VECTOR, "", AUTH_TAG);
+    // A component with a separator.
+    metric.set(&glean, HEADER, KEY, INIT_VECTOR, "a.b", AUTH_TAG);
+
+    assert!(metric.test_get_value(&glean, "store1").is_none());
+    assert_eq!(
+        Ok(4),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
@@ -107,6 +107,127 @@ fn can_create_labeled_bool_metric() {
     );
 }
 
+#[test]
+fn can_create_labeled_quantity_metric() {
+    let (glean, _t) = new_glean(None);
+    let mut labeled = LabeledMetric::new(
+        QuantityMetric::new(CommonMetricData {
+            name: "labeled_metric".into(),
+            category: "telemetry".into(),
+            send_in_pings: vec!["store1".into()],
+            disabled: false,
+            lifetime: Lifetime::Ping,
+            ..Default::default()
+        }),
+        Some(vec!["label1".into()]),
+    );
+
+    let metric = labeled.get("label1");
+    metric.set(&glean, 42);
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+
+    assert_eq!(
+        json!({
+            "labeled_quantity": {
+                "telemetry.labeled_metric": { "label1": 42 }
+            }
+        }),
+        snapshot
+    );
+}
+
+#[test]
+fn can_create_labeled_timespan_metric() {
+    let (glean, _t) = new_glean(None);
+    let mut labeled = LabeledMetric::new(
+        TimespanMetric::new(
+            CommonMetricData {
+                name: "labeled_metric".into(),
+                category: "telemetry".into(),
+                send_in_pings: vec!["store1".into()],
+                disabled: false,
+                lifetime: Lifetime::Ping,
+                ..Default::default()
+            },
+            TimeUnit::Nanosecond,
+        ),
+        None,
+    );
+
+    let mut metric = labeled.get("label1");
+    metric.set_start(&glean, 0);
+    metric.set_stop(&glean, 100);
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+
+    assert_eq!(
+        json!({
+            "labeled_timespan": {
+                "telemetry.labeled_metric": {
+                    "label1": { "value": 100, "time_unit": "nanosecond" }
+                }
+            }
+        }),
+        snapshot
+    );
+}
+
+#[test]
+fn can_create_labeled_distribution_metrics() {
+    let (glean, _t) = new_glean(None);
+    let meta = |name: &str| CommonMetricData {
+        name: name.into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    };
+
+    let mut timing = LabeledMetric::new(
+        TimingDistributionMetric::new(meta("labeled_timing"), TimeUnit::Nanosecond),
+        Some(vec!["label1".into()]),
+    );
+    let mut memory = LabeledMetric::new(
+        MemoryDistributionMetric::new(meta("labeled_memory"), MemoryUnit::Byte),
+        Some(vec!["label1".into()]),
+    );
+    let mut custom = LabeledMetric::new(
+        CustomDistributionMetric::new(meta("labeled_custom"), 1, 100, 10, HistogramType::Linear),
+        None,
+    );
+
+    timing
+        .get("label1")
+        .accumulate_samples_signed(&glean, vec![1, 2, 3]);
+    memory.get("label1").accumulate(&glean, 1024);
+    custom
+        .get("dynamic_label")
+        .accumulate_samples_signed(&glean, vec![50]);
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+
+    assert_eq!(
+        6,
+        snapshot["labeled_timing_distribution"]["telemetry.labeled_timing"]["label1"]["sum"]
+    );
+    assert_eq!(
+        1024,
+        snapshot["labeled_memory_distribution"]["telemetry.labeled_memory"]["label1"]["sum"]
+    );
+    assert_eq!(
+        50,
+        snapshot["labeled_custom_distribution"]["telemetry.labeled_custom"]["dynamic_label"]["sum"]
+    );
+}
+
 #[test]
 fn can_use_multiple_labels() {
     let (glean, _t) = new_glean(None);
@@ -27,6 +27,9 @@ fn serializer_should_correctly_serialize_memory_distribution() {
         upload_enabled: true,
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
 
     {
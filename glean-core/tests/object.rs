@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+use crate::common::*;
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use glean_core::metrics::*;
+use glean_core::storage::StorageManager;
+use glean_core::{test_get_num_recorded_errors, ErrorType};
+use glean_core::{CommonMetricData, Lifetime};
+
+fn object_metric() -> ObjectMetric {
+    let mut item = HashMap::new();
+    item.insert("name".to_string(), ObjectSchema::String);
+    item.insert("size".to_string(), ObjectSchema::Number);
+    item.insert("enabled".to_string(), ObjectSchema::Boolean);
+
+    ObjectMetric::new(
+        CommonMetricData {
+            name: "object_metric".into(),
+            category: "telemetry".into(),
+            send_in_pings: vec!["store1".into()],
+            disabled: false,
+            lifetime: Lifetime::Ping,
+            ..Default::default()
+        },
+        ObjectSchema::Array {
+            items: Box::new(ObjectSchema::Object(item)),
+            max_length: 2,
+        },
+    )
+}
+
+#[test]
+fn object_serializer_should_correctly_serialize_objects() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = object_metric();
+    metric.set(
+        &glean,
+        json!([{"name": "a", "size": 1, "enabled": true}, {"name": "b"}]),
+    );
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+    assert_eq!(
+        json!({
+            "object": {
+                "telemetry.object_metric": [
+                    {"name": "a", "size": 1, "enabled": true},
+                    {"name": "b"}
+                ]
+            }
+        }),
+        snapshot
+    );
+}
+
+#[test]
+fn object_can_be_set_from_a_json_string() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = object_metric();
+    metric.set_string(&glean, r#"[{"size": 2.5}]"#);
+
+    assert_eq!(
+        Some(json!([{"size": 2.5}])),
+        metric.test_get_value(&glean, "store1")
+    );
+}
+
+#[test]
+fn objects_not_matching_the_schema_are_rejected() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = object_metric();
+    // Not an array.
+    metric.set(&glean, json!({"name": "a"}));
+    // Too many items.
+    metric.set(&glean, json!([{}, {}, {}]));
+    // Unknown key.
+    metric.set(&glean, json!([{"color": "red"}]));
+    // Wrong value type.
+    metric.set(&glean, json!([{"size": "big"}]));
+    // Not JSON.
+    metric.set_string(&glean, "[{");
+
+    assert!(metric.test_get_value(&glean, "store1").is_none());
+    assert_eq!(
+        Ok(5),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
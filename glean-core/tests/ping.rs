@@ -6,7 +6,8 @@ mod common;
 use crate::common::*;
 
 use glean_core::metrics::*;
-use glean_core::CommonMetricData;
+use glean_core::{CommonMetricData, Glean, Lifetime};
+use serde_json::Value as JsonValue;
 
 #[test]
 fn write_ping_to_disk() {
@@ -101,3 +102,133 @@ fn empty_pings_with_flag_are_sent() {
     assert_eq!(false, ping2.submit(&glean, None).unwrap());
     assert_eq!(1, get_queued_pings(glean.get_data_path()).unwrap().len());
 }
+
+/// The queued `baseline` pings, by reason.
+fn get_baseline_pings(glean: &Glean) -> Vec<(String, JsonValue)> {
+    get_queued_pings(glean.get_data_path())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(url, _)| url.contains("/baseline/"))
+        .map(|(_, ping)| {
+            (
+                ping["ping_info"]["reason"].as_str().unwrap().to_string(),
+                ping,
+            )
+        })
+        .collect()
+}
+
+/// A metric in the `baseline` ping, as an empty ping is not stored.
+fn baseline_locale() -> StringMetric {
+    StringMetric::new(CommonMetricData {
+        name: "locale".into(),
+        category: "local".into(),
+        send_in_pings: vec!["baseline".into()],
+        lifetime: Lifetime::Application,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn client_active_and_inactive_submit_baseline_pings() {
+    let (glean, _t) = new_glean(None);
+    baseline_locale().set(&glean, "en-US");
+
+    glean.handle_client_active();
+    assert!(glean.is_dirty_flag_set());
+    let pings = get_baseline_pings(&glean);
+    assert_eq!(1, pings.len());
+    assert_eq!("active", pings[0].0);
+    assert!(pings[0].1["metrics"]["timespan"]["glean.baseline.duration"].is_null());
+
+    glean.handle_client_inactive();
+    assert!(!glean.is_dirty_flag_set());
+    let pings = get_baseline_pings(&glean);
+    assert_eq!(2, pings.len());
+    let (_, inactive) = pings
+        .iter()
+        .find(|(reason, _)| reason == "inactive")
+        .unwrap();
+    assert!(inactive["metrics"]["timespan"]["glean.baseline.duration"]["value"].is_u64());
+}
+
+#[test]
+fn unfinished_active_session_submits_dirty_startup_baseline_ping() {
+    let (glean, t) = new_glean(None);
+    glean.handle_client_active();
+    assert!(get_baseline_pings(&glean).is_empty());
+    drop(glean);
+
+    // The process was killed before becoming inactive.
+    let (glean, _t) = new_glean(Some(t));
+    baseline_locale().set(&glean, "en-US");
+    glean.handle_client_active();
+
+    let mut reasons: Vec<_> = get_baseline_pings(&glean)
+        .into_iter()
+        .map(|(reason, _)| reason)
+        .collect();
+    reasons.sort();
+    assert_eq!(vec!["active", "dirty_startup"], reasons);
+}
+
+#[test]
+fn dirty_startup_is_only_checked_on_the_first_activation() {
+    let (glean, _t) = new_glean(None);
+    baseline_locale().set(&glean, "en-US");
+
+    // Becoming active twice without becoming inactive in between is not a dirty startup.
+    glean.handle_client_active();
+    glean.handle_client_active();
+
+    let reasons: Vec<_> = get_baseline_pings(&glean)
+        .into_iter()
+        .map(|(reason, _)| reason)
+        .collect();
+    assert_eq!(vec!["active", "active"], reasons);
+}
+
+#[test]
+fn client_active_starts_a_new_session() {
+    let (glean, _t) = new_glean(None);
+    assert_eq!(None, glean.session_id());
+    assert_eq!(0, glean.session_count());
+
+    glean.handle_client_active();
+    let first_session = glean.session_id().unwrap();
+    assert_eq!(1, glean.session_count());
+
+    glean.handle_client_inactive();
+    assert_eq!(Some(first_session), glean.session_id());
+
+    glean.handle_client_active();
+    assert_ne!(first_session, glean.session_id().unwrap());
+    assert_eq!(2, glean.session_count());
+}
+
+#[test]
+fn session_lifetime_metrics_are_cleared_when_the_session_ends() {
+    let (glean, _t) = new_glean(None);
+    let metric = StringMetric::new(CommonMetricData {
+        name: "session_string".into(),
+        category: "local".into(),
+        send_in_pings: vec!["baseline".into()],
+        lifetime: Lifetime::Session,
+        ..Default::default()
+    });
+
+    glean.handle_client_active();
+    metric.set(&glean, "in session");
+    glean.handle_client_inactive();
+
+    let pings = get_baseline_pings(&glean);
+    let (_, inactive) = pings
+        .iter()
+        .find(|(reason, _)| reason == "inactive")
+        .unwrap();
+    assert_eq!(
+        "in session",
+        inactive["metrics"]["string"]["local.session_string"]
+    );
+    assert_eq!(None, metric.test_get_value(&glean, "baseline"));
+}
@@ -17,6 +17,9 @@ fn set_up_basic_ping() -> (Glean, PingMaker, PingType, tempfile::TempDir) {
         upload_enabled: true,
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
     let mut glean = Glean::new(cfg).unwrap();
     let ping_maker = PingMaker::new();
@@ -0,0 +1,124 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+use crate::common::*;
+
+use serde_json::json;
+
+use glean_core::metrics::*;
+use glean_core::storage::StorageManager;
+use glean_core::{test_get_num_recorded_errors, ErrorType};
+use glean_core::{CommonMetricData, Lifetime};
+
+fn meta(name: &str) -> CommonMetricData {
+    CommonMetricData {
+        name: name.into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rate_smoke() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = RateMetric::new(meta("rate"));
+
+    // Adding 0 doesn't error.
+    metric.add_to_numerator(&glean, 0);
+    metric.add_to_denominator(&glean, 0);
+    assert!(
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None).is_err()
+    );
+
+    metric.add_to_numerator(&glean, 1);
+    metric.add_to_denominator(&glean, 2);
+    metric.add_to_denominator(&glean, 3);
+    assert_eq!(
+        Some(Rate {
+            numerator: 1,
+            denominator: 5
+        }),
+        metric.test_get_value(&glean, "store1")
+    );
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+    assert_eq!(
+        json!({"rate": {"telemetry.rate": {"numerator": 1, "denominator": 5}}}),
+        snapshot
+    );
+}
+
+#[test]
+fn rate_rejects_negative_values() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = RateMetric::new(meta("rate"));
+
+    metric.add_to_numerator(&glean, -1);
+    metric.add_to_denominator(&glean, -1);
+    assert!(metric.test_get_value(&glean, "store1").is_none());
+    assert_eq!(
+        Ok(2),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
+
+#[test]
+fn denominator_feeds_all_numerators() {
+    let (glean, _t) = new_glean(None);
+
+    let numerator1 = NumeratorMetric::new(meta("numerator1"));
+    let numerator2 = NumeratorMetric::new(meta("numerator2"));
+    let denominator = DenominatorMetric::new(
+        meta("denominator"),
+        vec![numerator1.clone(), numerator2.clone()],
+    );
+
+    numerator1.add_to_numerator(&glean, 1);
+    numerator2.add_to_numerator(&glean, 2);
+    denominator.add(&glean, 7);
+
+    assert_eq!(Some(7), denominator.test_get_value(&glean, "store1"));
+    assert_eq!(
+        Some(Rate {
+            numerator: 1,
+            denominator: 7
+        }),
+        numerator1.test_get_value(&glean, "store1")
+    );
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", false)
+        .unwrap();
+    assert_eq!(
+        json!({
+            "counter": {"telemetry.denominator": 7},
+            "rate": {
+                "telemetry.numerator1": {"numerator": 1, "denominator": 7},
+                "telemetry.numerator2": {"numerator": 2, "denominator": 7},
+            }
+        }),
+        snapshot
+    );
+
+    // Invalid amounts are reported on the denominator only.
+    denominator.add(&glean, 0);
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, denominator.meta(), ErrorType::InvalidValue, None)
+    );
+    assert_eq!(
+        Some(7),
+        numerator2
+            .test_get_value(&glean, "store1")
+            .map(|rate| rate.denominator)
+    );
+}
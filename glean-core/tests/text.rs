@@ -0,0 +1,100 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+use crate::common::*;
+
+use serde_json::json;
+
+use glean_core::metrics::*;
+use glean_core::ping::PingMaker;
+use glean_core::{test_get_num_recorded_errors, ErrorType};
+use glean_core::{CommonMetricData, Lifetime};
+
+fn text_metric(pings: Vec<String>) -> TextMetric {
+    TextMetric::new(CommonMetricData {
+        name: "text_metric".into(),
+        category: "telemetry".into(),
+        send_in_pings: pings,
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn set_value_properly_sets_the_value() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = text_metric(vec!["store1".into()]);
+    let text = "a long\nmultiline text ".repeat(100);
+    metric.set(&glean, text.clone());
+
+    assert_eq!(Some(text), metric.test_get_value(&glean, "store1"));
+}
+
+#[test]
+fn long_text_values_are_truncated() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = text_metric(vec!["store1".into()]);
+    let text = "0123456789".repeat(25 * 1024);
+    metric.set(&glean, text.clone());
+
+    assert_eq!(
+        Some(&text[..200 * 1024]),
+        metric.test_get_value(&glean, "store1").as_deref()
+    );
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidOverflow, None)
+    );
+}
+
+#[test]
+fn text_is_only_sent_in_pings_that_include_it() {
+    let (mut glean, _t) = new_glean(None);
+
+    let plain_ping = PingType::new("plain", true, false, vec![]);
+    let mut text_ping = PingType::new("with-text", true, false, vec![]);
+    text_ping.include_text = true;
+    glean.register_ping_type(&plain_ping);
+    glean.register_ping_type(&text_ping);
+
+    let metric = text_metric(vec!["plain".into(), "with-text".into()]);
+    metric.set(&glean, "some text");
+
+    // Recording for a ping without text is reported.
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+
+    let ping_maker = PingMaker::new();
+    let content = ping_maker.collect(&glean, &plain_ping, None).unwrap();
+    assert!(content["metrics"].get("text").is_none());
+
+    let content = ping_maker.collect(&glean, &text_ping, None).unwrap();
+    assert_eq!(
+        json!({"telemetry.text_metric": "some text"}),
+        content["metrics"]["text"]
+    );
+}
+
+#[test]
+fn text_is_not_stored_for_pings_that_dont_include_it() {
+    let (mut glean, _t) = new_glean(None);
+
+    let plain_ping = PingType::new("plain", true, false, vec![]);
+    glean.register_ping_type(&plain_ping);
+
+    let metric = text_metric(vec!["plain".into()]);
+    metric.set(&glean, "some text");
+
+    assert_eq!(None, metric.test_get_value(&glean, "plain"));
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
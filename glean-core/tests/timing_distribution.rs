@@ -29,6 +29,9 @@ fn serializer_should_correctly_serialize_timing_distribution() {
         upload_enabled: true,
         max_events: None,
         delay_ping_lifetime_io: false,
+        rate_limit: None,
+        storage_quota: None,
+        database_backend: None,
     };
 
     {
@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+use crate::common::*;
+
+use serde_json::json;
+
+use glean_core::metrics::*;
+use glean_core::storage::StorageManager;
+use glean_core::{test_get_num_recorded_errors, ErrorType};
+use glean_core::{CommonMetricData, Lifetime};
+
+fn url_metric() -> UrlMetric {
+    UrlMetric::new(CommonMetricData {
+        name: "url_metric".into(),
+        category: "telemetry".into(),
+        send_in_pings: vec!["store1".into()],
+        disabled: false,
+        lifetime: Lifetime::Ping,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn url_serializer_should_correctly_serialize_urls() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = url_metric();
+    metric.set(&glean, "https://example.com/path?query=1");
+
+    let snapshot = StorageManager
+        .snapshot_as_json(glean.storage(), "store1", true)
+        .unwrap();
+    assert_eq!(
+        json!({"url": {"telemetry.url_metric": "https://example.com/path?query=1"}}),
+        snapshot
+    );
+}
+
+#[test]
+fn urls_without_a_valid_scheme_are_rejected() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = url_metric();
+    metric.set(&glean, "example.com");
+    metric.set(&glean, "1nvalid://example.com");
+
+    assert!(metric.test_get_value(&glean, "store1").is_none());
+    assert_eq!(
+        Ok(2),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
+
+#[test]
+fn data_urls_are_rejected() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = url_metric();
+    metric.set(&glean, "data:text/plain;base64,SGVsbG8=");
+    metric.set(&glean, "DATA:text/plain,hello");
+    metric.set(&glean, "ab:é");
+
+    assert_eq!(
+        Some("ab:é"),
+        metric.test_get_value(&glean, "store1").as_deref()
+    );
+    assert_eq!(
+        Ok(2),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidValue, None)
+    );
+}
+
+#[test]
+fn long_urls_are_truncated() {
+    let (glean, _t) = new_glean(None);
+
+    let metric = url_metric();
+    let url = format!("https://example.com/{}", "a".repeat(10000));
+    metric.set(&glean, url.clone());
+
+    assert_eq!(
+        Some(&url[..8192]),
+        metric.test_get_value(&glean, "store1").as_deref()
+    );
+    assert_eq!(
+        Ok(1),
+        test_get_num_recorded_errors(&glean, metric.meta(), ErrorType::InvalidOverflow, None)
+    );
+}